@@ -0,0 +1,77 @@
+//! Rebuild bat's cache, which is where bat (and delta) load themes from, so
+//! the rerendered `oxidize.tmTheme` is picked up.
+
+use crate::{ctx::Ctx, log, transaction::Change};
+use std::{
+    env,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The rendered theme, relative to the live tree. Its `out` header links it
+/// into `~/.config/bat/themes`.
+const THEME_FILE: &str = "bat/oxidize.tmTheme";
+
+/// Whether `changes` touch the theme, so the cache is out of date.
+pub fn changed(changes: &[Change]) -> bool {
+    changes.iter().any(|c| c.path() == Path::new(THEME_FILE))
+}
+
+/// The first of `bat` and `batcat` (Debian's name for it) in `path`.
+fn find_in(path: &OsStr) -> Option<PathBuf> {
+    ["bat", "batcat"]
+        .iter()
+        .find_map(|name| env::split_paths(path).map(|dir| dir.join(name)).find(|p| p.is_file()))
+}
+
+/// The command line [`run`] would use. `None` if bat isn't installed.
+pub fn command() -> Option<Vec<String>> {
+    let bat = find_in(&env::var_os("PATH")?)?;
+    Some(vec![bat.display().to_string(), "cache".to_owned(), "--build".to_owned()])
+}
+
+/// Rebuild the cache if the live tree has the theme.
+pub fn run(ctx: &Ctx) {
+    if !ctx.live_dir.join(THEME_FILE).is_file() {
+        return;
+    }
+    let Some(args) = command() else {
+        log::debug!("bat is not installed");
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+        Ok(out) if !out.status.success() => {
+            log::warn!("bat: {}", String::from_utf8_lossy(&out.stderr).trim());
+        }
+        Err(e) => log::warn!("bat: {e}"),
+        Ok(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_bat_under_either_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (a, b) = (tmp.path().join("a"), tmp.path().join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let path = env::join_paths([&a, &b]).unwrap();
+        assert_eq!(find_in(&path), None);
+
+        fs::write(b.join("batcat"), "").unwrap();
+        assert_eq!(find_in(&path), Some(b.join("batcat")));
+        fs::write(b.join("bat"), "").unwrap();
+        assert_eq!(find_in(&path), Some(b.join("bat")));
+
+        assert!(changed(&[Change::Added("gtk/gtk-4.0.css".into()), Change::Modified(THEME_FILE.into())]));
+        assert!(!changed(&[Change::Modified("delta/oxidize.gitconfig".into())]));
+    }
+}
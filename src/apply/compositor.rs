@@ -0,0 +1,65 @@
+//! Find the running compositor and bring it up to date after a switch.
+
+use super::{hyprland, niri, river, sway};
+use crate::{log, theme::Theme};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compositor {
+    Hyprland,
+    Sway,
+    Niri,
+    River,
+}
+
+/// How each compositor is updated, from `oxidize.toml`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Settings {
+    /// `[hyprland] reload`.
+    pub hyprland: hyprland::Reload,
+    /// `[sway] reload`.
+    pub sway: sway::Reload,
+}
+
+/// The compositor of this session, from the IPC socket variable each one
+/// exports to its clients. river has none, so it is recognized by
+/// `XDG_CURRENT_DESKTOP`.
+pub fn detect() -> Option<Compositor> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    if var("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        Some(Compositor::Hyprland)
+    } else if var("SWAYSOCK").is_some() {
+        Some(Compositor::Sway)
+    } else if var("NIRI_SOCKET").is_some() {
+        Some(Compositor::Niri)
+    } else if var("XDG_CURRENT_DESKTOP").is_some_and(|d| d.split(':').any(|d| d.eq_ignore_ascii_case("river"))) {
+        Some(Compositor::River)
+    } else {
+        None
+    }
+}
+
+/// The command lines [`run`] would run, in order.
+pub fn commands(theme: &Theme, settings: &Settings) -> Vec<Vec<String>> {
+    match detect() {
+        Some(Compositor::Hyprland) => hyprland::command(theme, settings.hyprland).into_iter().collect(),
+        Some(Compositor::Sway) => sway::command(theme, settings.sway).into_iter().collect(),
+        Some(Compositor::Niri) => vec![niri::command()],
+        Some(Compositor::River) => river::commands(theme),
+        None => Vec::new(),
+    }
+}
+
+/// Push `theme` into the running compositor. No-op outside a supported one.
+pub fn run(theme: &Theme, settings: &Settings) {
+    for args in commands(theme, settings) {
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]);
+        log::command(&cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .ok();
+    }
+}
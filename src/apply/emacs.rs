@@ -0,0 +1,94 @@
+//! Retheme running Emacs (usually a daemon) via `emacsclient --eval`.
+
+use crate::{ctx::Ctx, log, theme::Theme};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The generated theme file, relative to the live tree.
+const THEME_FILE: &str = "emacs/oxidize-theme.el";
+
+/// What to evaluate, from `[emacs]` in oxidize.toml.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// `generated = true`: load the rendered `oxidize-theme.el`.
+    Generated,
+    /// `light = "..."`, `dark = "..."`: load one of two Emacs themes by the
+    /// theme's variant.
+    Themes { light: String, dark: String },
+    /// `eval = "..."`: any expression.
+    Eval(String),
+}
+
+/// An elisp string literal.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The expression for `source`, switching off whatever themes are enabled
+/// before enabling the new one.
+fn expression(ctx: &Ctx, theme: &Theme, source: &Source) -> String {
+    const DISABLE: &str = "(mapc #'disable-theme custom-enabled-themes)";
+    match source {
+        Source::Generated => {
+            let file = ctx.current_link.join(THEME_FILE);
+            format!("(progn {DISABLE} (load-file {}) (enable-theme 'oxidize))", quote(&file.display().to_string()))
+        }
+        Source::Themes { light, dark } => {
+            let name = if theme.is_light { light } else { dark };
+            format!("(progn {DISABLE} (load-theme (intern {}) t))", quote(name))
+        }
+        Source::Eval(expr) => expr.clone(),
+    }
+}
+
+/// The `emacsclient` command line [`run`] would use. `None` if Emacs isn't
+/// configured, or the generated theme is asked for but `tree` (the live
+/// tree, or a stage) lacks it.
+pub fn command(ctx: &Ctx, theme: &Theme, source: Option<&Source>, tree: &Path) -> Option<Vec<String>> {
+    let source = source?;
+    if *source == Source::Generated && !tree.join(THEME_FILE).is_file() {
+        log::debug!("emacs: no {THEME_FILE} in the live tree");
+        return None;
+    }
+    Some(vec!["emacsclient".to_owned(), "--eval".to_owned(), expression(ctx, theme, source)])
+}
+
+/// Send the theme to the running Emacs server, if there is one.
+pub fn run(ctx: &Ctx, theme: &Theme, source: Option<&Source>) {
+    let Some(args) = command(ctx, theme, source, &ctx.live_dir) else {
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    // Spawn, not status: a busy Emacs would hold up the switch.
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expressions_pick_the_variant_and_quote() {
+        let ctx = Ctx {
+            current_link: "/home/me/\"odd\"/current".into(),
+            ..Ctx::under("/home/me".as_ref())
+        };
+        let mut theme = Theme { is_light: true, ..Theme::for_test(&[]) };
+        let themes = Source::Themes { light: "modus-operandi".into(), dark: "modus-vivendi".into() };
+        assert!(expression(&ctx, &theme, &themes).ends_with("(load-theme (intern \"modus-operandi\") t))"));
+        theme.is_light = false;
+        assert!(expression(&ctx, &theme, &themes).contains("modus-vivendi"));
+        assert!(
+            expression(&ctx, &theme, &Source::Generated)
+                .contains("(load-file \"/home/me/\\\"odd\\\"/current/emacs/oxidize-theme.el\")")
+        );
+    }
+}
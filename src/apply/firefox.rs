@@ -0,0 +1,190 @@
+//! Firefox: copy the rendered `userChrome.css` and `userContent.css` into
+//! the `chrome/` dir of every profile in `profiles.ini`.
+//!
+//! Firefox reads them only at startup, and only with
+//! `toolkit.legacyUserProfileCustomizations.stylesheets` on, which
+//! `[firefox] pref = true` sets in each profile's `user.js`. The files go out
+//! like `#! copy = true` templates (see [`crate::external`]).
+
+use crate::{ctx::Ctx, render::External};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// Stylesheets in the live tree, copied to `<profile>/chrome/<name>`.
+const STYLESHEETS: [&str; 2] = ["userChrome.css", "userContent.css"];
+
+const PREF: &str = "toolkit.legacyUserProfileCustomizations.stylesheets";
+
+/// Dirs holding `profiles.ini` under `$HOME`: the classic location and the
+/// Flatpak one. Newer releases use `mozilla/firefox` under the XDG config dir.
+const ROOTS: [&str; 2] = [".mozilla/firefox", ".var/app/org.mozilla.firefox/.mozilla/firefox"];
+
+/// `[firefox]` in oxidize.toml.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Settings {
+    pub enable: bool,
+    /// Turn the stylesheet pref on in `user.js`.
+    pub pref: bool,
+}
+
+/// Profile dirs listed in `root/profiles.ini` that exist.
+fn profiles_in(root: &Path) -> Vec<PathBuf> {
+    let Ok(ini) = fs::read_to_string(root.join("profiles.ini")) else {
+        return Vec::new();
+    };
+    let mut profiles = Vec::new();
+    let mut section = "";
+    let (mut path, mut relative) = (None, true);
+    let mut finish = |section: &str, path: &mut Option<&str>, relative: bool| {
+        if section.starts_with("Profile")
+            && let Some(p) = path.take()
+        {
+            profiles.push(if relative { root.join(p) } else { PathBuf::from(p) });
+        }
+    };
+    for line in ini.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            finish(section, &mut path, relative);
+            (section, relative) = (name, true);
+        } else if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "Path" => path = Some(value.trim()),
+                "IsRelative" => relative = value.trim() != "0",
+                _ => {}
+            }
+        }
+    }
+    finish(section, &mut path, relative);
+    profiles.retain(|p| p.is_dir());
+    profiles
+}
+
+/// Every Firefox profile dir.
+fn profiles(ctx: &Ctx) -> Vec<PathBuf> {
+    let config_home = ctx.config_dir.parent().unwrap_or(&ctx.config_dir);
+    ROOTS
+        .iter()
+        .map(|root| ctx.home_dir.join(root))
+        .chain(std::iter::once(config_home.join("mozilla/firefox")))
+        .flat_map(|root| profiles_in(&root))
+        .collect()
+}
+
+/// `src` (a `user.js`) with [`PREF`] set to true.
+fn with_pref(src: &str) -> String {
+    let line = format!("user_pref(\"{PREF}\", true);");
+    let mut found = false;
+    let mut out = String::new();
+    for l in src.lines() {
+        if l.contains(&format!("\"{PREF}\"")) {
+            found = true;
+            out.push_str(&line);
+        } else {
+            out.push_str(l);
+        }
+        out.push('\n');
+    }
+    if !found {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// The copies for each profile: the stylesheets `stage` has, and with
+/// `pref`, a `user.js` staged as `firefox/<n>/user.js`.
+pub fn stage(ctx: &Ctx, settings: Settings, stage: &Path) -> Result<Vec<External>> {
+    if !settings.enable {
+        return Ok(Vec::new());
+    }
+    stage_profiles(settings, stage, profiles(ctx))
+}
+
+fn stage_profiles(settings: Settings, stage: &Path, profiles: Vec<PathBuf>) -> Result<Vec<External>> {
+    let sheets: Vec<PathBuf> = STYLESHEETS
+        .iter()
+        .map(|name| Path::new("firefox").join(name))
+        .filter(|out| stage.join(out).is_file())
+        .collect();
+    let mut externals = Vec::new();
+    for (i, profile) in profiles.into_iter().enumerate() {
+        for out in &sheets {
+            let name = out.file_name().unwrap_or_default();
+            externals.push(External { out: out.clone(), target: profile.join("chrome").join(name), copy: true });
+        }
+        if settings.pref {
+            let target = profile.join("user.js");
+            let src = match fs::read_to_string(&target) {
+                Ok(src) => src,
+                Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e).with_context(|| format!("read {}", target.display())),
+            };
+            let out = PathBuf::from(format!("firefox/{i}/user.js"));
+            let path = stage.join(&out);
+            fs::create_dir_all(path.parent().unwrap_or(stage)).with_context(|| format!("create {}", path.display()))?;
+            fs::write(&path, with_pref(&src)).with_context(|| format!("write {}", path.display()))?;
+            externals.push(External { out, target, copy: true });
+        }
+    }
+    Ok(externals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_stylesheets_and_pref_per_profile() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let root = tmp.path().join(".mozilla/firefox");
+        let xdg = tmp.path().join("config/mozilla/firefox");
+        let elsewhere = tmp.path().join("elsewhere");
+        for dir in [root.join("abc.default-release"), xdg.join("xyz.default"), elsewhere.clone()] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(
+            root.join("profiles.ini"),
+            format!(
+                "[Install4F96D1932A9F858E]\nDefault=abc.default-release\n\n\
+                 [Profile1]\nName=other\nIsRelative=0\nPath={}\n\n\
+                 [Profile0]\nName=default-release\nIsRelative=1\nPath=abc.default-release\nDefault=1\n\n\
+                 [Profile2]\nName=gone\nIsRelative=1\nPath=gone\n\n\
+                 [General]\nVersion=2\n",
+                elsewhere.display()
+            ),
+        )
+        .unwrap();
+        fs::write(xdg.join("profiles.ini"), "[Profile0]\nPath=xyz.default\n").unwrap();
+        assert_eq!(profiles(&ctx), [elsewhere.clone(), root.join("abc.default-release"), xdg.join("xyz.default")]);
+
+        fs::write(elsewhere.join("user.js"), "user_pref(\"a\", 1);\nuser_pref(\"".to_owned() + PREF + "\", false);\n")
+            .unwrap();
+        let stage_dir = tmp.path().join("stage");
+        fs::create_dir_all(stage_dir.join("firefox")).unwrap();
+        fs::write(stage_dir.join("firefox/userChrome.css"), "").unwrap();
+        assert!(stage(&ctx, Settings { enable: false, pref: true }, &stage_dir).unwrap().is_empty());
+        let externals = stage(&ctx, Settings { enable: true, pref: true }, &stage_dir).unwrap();
+        let targets: Vec<_> = externals.iter().map(|e| e.target.strip_prefix(tmp.path()).unwrap()).collect();
+        assert_eq!(
+            targets,
+            [
+                Path::new("elsewhere/chrome/userChrome.css"),
+                Path::new("elsewhere/user.js"),
+                Path::new(".mozilla/firefox/abc.default-release/chrome/userChrome.css"),
+                Path::new(".mozilla/firefox/abc.default-release/user.js"),
+                Path::new("config/mozilla/firefox/xyz.default/chrome/userChrome.css"),
+                Path::new("config/mozilla/firefox/xyz.default/user.js"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(stage_dir.join("firefox/0/user.js")).unwrap(),
+            format!("user_pref(\"a\", 1);\nuser_pref(\"{PREF}\", true);\n")
+        );
+        assert_eq!(fs::read_to_string(stage_dir.join("firefox/1/user.js")).unwrap(), format!("user_pref(\"{PREF}\", true);\n"));
+    }
+}
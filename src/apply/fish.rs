@@ -0,0 +1,88 @@
+//! Set fish's universal color variables, which every running fish shell
+//! picks up at its next prompt.
+
+use crate::{color::Rgb, ctx::Ctx, log, render::engine::resolve_key, theme::Theme};
+use std::{
+    io::ErrorKind,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The rendered colors, relative to the live tree.
+const COLORS_FILE: &str = "fish/colors.fish";
+
+/// Core color variables from the theme's `bg`, `fg` and `accent`, matching
+/// the default pack's `colors.fish`. Empty if a color is missing.
+fn variables(theme: &Theme) -> Vec<(&'static str, String)> {
+    let color = |name: &str| resolve_key(&theme.vars, name).and_then(|(_, v)| Rgb::parse(v));
+    let (Some(bg), Some(fg), Some(accent)) = (color("bg"), color("fg"), color("accent")) else {
+        return Vec::new();
+    };
+    let (muted, selection) = (fg.mix(bg, 0.5).to_hex(), bg.mix(accent, 0.3).to_hex());
+    let (fg, accent) = (fg.to_hex(), accent.to_hex());
+    vec![
+        ("fish_color_normal", fg.clone()),
+        ("fish_color_command", accent.clone()),
+        ("fish_color_keyword", accent.clone()),
+        ("fish_color_param", fg.clone()),
+        ("fish_color_comment", muted.clone()),
+        ("fish_color_autosuggestion", muted.clone()),
+        ("fish_color_selection", format!("--background={selection}")),
+        ("fish_pager_color_prefix", accent),
+        ("fish_pager_color_completion", fg),
+        ("fish_pager_color_description", muted),
+    ]
+}
+
+/// The `fish` command line [`run`] would use: source the rendered
+/// `colors.fish` if `tree` (the live tree, or a stage) has one, else set the
+/// core variables directly.
+pub fn command(ctx: &Ctx, theme: &Theme, tree: &Path) -> Option<Vec<String>> {
+    let script = if tree.join(COLORS_FILE).is_file() {
+        format!("source '{}'", ctx.current_link.join(COLORS_FILE).display().to_string().replace('\'', "\\'"))
+    } else {
+        let variables = variables(theme);
+        if variables.is_empty() {
+            return None;
+        }
+        variables.iter().map(|(name, value)| format!("set -U {name} '{value}'")).collect::<Vec<_>>().join("; ")
+    };
+    Some(vec!["fish".to_owned(), "-c".to_owned(), script])
+}
+
+/// Apply the theme's colors to fish.
+pub fn run(ctx: &Ctx, theme: &Theme) {
+    let Some(args) = command(ctx, theme, &ctx.live_dir) else {
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+        Ok(out) if !out.status.success() => {
+            log::warn!("fish: {}", String::from_utf8_lossy(&out.stderr).trim());
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => log::debug!("fish is not installed"),
+        Err(e) => log::warn!("fish: {e}"),
+        Ok(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_core_variables_without_the_colors_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let theme = Theme::for_test(&[("palette_bg", "#000000"), ("palette_fg", "#FFFFFF"), ("palette_accent", "#89b4fa")]);
+        let args = command(&ctx, &theme, tmp.path()).unwrap();
+        assert!(args[2].starts_with("set -U fish_color_normal '#ffffff'; set -U fish_color_command '#89b4fa'; "));
+
+        std::fs::create_dir_all(tmp.path().join("fish")).unwrap();
+        std::fs::write(tmp.path().join(COLORS_FILE), "").unwrap();
+        let args = command(&ctx, &theme, tmp.path()).unwrap();
+        assert_eq!(args[2], format!("source '{}'", ctx.current_link.join(COLORS_FILE).display()));
+    }
+}
@@ -1,31 +1,64 @@
 //! Apply GNOME color-scheme, GTK theme, and icon theme via `gsettings`.
 
-use crate::theme::Theme;
+use crate::{log, theme::Theme};
 use std::process::{Command, Stdio};
 
-const SCHEMA: &str = "org.gnome.desktop.interface";
+pub const SCHEMA: &str = "org.gnome.desktop.interface";
+
+/// Every key under [`SCHEMA`] that `run` may write.
+pub const KEYS: [&str; 3] = ["color-scheme", "gtk-theme", "icon-theme"];
+
+/// `gtk-theme` for light and dark themes.
+pub const GTK_THEMES: (&str, &str) = ("adw-gtk3", "adw-gtk3-dark");
 
 pub fn run(theme: &Theme, no_icons: bool) {
+    for (key, value) in settings(theme, no_icons) {
+        gsettings_set(SCHEMA, key, value);
+    }
+}
+
+/// The `(key, value)` pairs `run` writes under [`SCHEMA`].
+pub fn settings(theme: &Theme, no_icons: bool) -> Vec<(&'static str, &str)> {
     let (color_scheme, gtk_theme) = if theme.is_light {
-        ("prefer-light", "adw-gtk3")
+        ("prefer-light", GTK_THEMES.0)
     } else {
-        ("prefer-dark", "adw-gtk3-dark")
+        ("prefer-dark", GTK_THEMES.1)
     };
 
-    gsettings_set(SCHEMA, "color-scheme", color_scheme);
-    gsettings_set(SCHEMA, "gtk-theme", gtk_theme);
-
-    if !no_icons {
-        if let Some(icon) = theme.icon_theme.as_deref() {
-            gsettings_set(SCHEMA, "icon-theme", icon);
-        }
+    let mut out = vec![("color-scheme", color_scheme), ("gtk-theme", gtk_theme)];
+    if !no_icons && let Some(icon) = theme.icon_theme.as_deref() {
+        out.push(("icon-theme", icon));
     }
+    out
+}
+
+/// `gsettings get` prints GVariant text (`'prefer-dark'`); strip the quotes.
+pub fn gsettings_get(schema: &str, key: &str) -> Option<String> {
+    let mut cmd = Command::new("gsettings");
+    cmd.args(["get", schema, key]);
+    log::command(&cmd);
+    let out = cmd.stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    (out.status.success() && !text.trim().is_empty()).then(|| text.trim().trim_matches('\'').to_owned())
+}
+
+/// `(key, current, new)` for each setting `run` would change. Empty when
+/// `gsettings` isn't available.
+pub fn pending(theme: &Theme, no_icons: bool) -> Vec<(&'static str, String, &str)> {
+    settings(theme, no_icons)
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let current = gsettings_get(SCHEMA, key)?;
+            (current != value).then_some((key, current, value))
+        })
+        .collect()
 }
 
-fn gsettings_set(schema: &str, key: &str, value: &str) {
-    Command::new("gsettings")
-        .args(["set", schema, key, value])
-        .stdin(Stdio::null())
+pub fn gsettings_set(schema: &str, key: &str, value: &str) {
+    let mut cmd = Command::new("gsettings");
+    cmd.args(["set", schema, key, value]);
+    log::command(&cmd);
+    cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
@@ -0,0 +1,47 @@
+//! Make running GTK apps reload the pack's `gtk.css` files.
+//!
+//! GTK reads `~/.config/gtk-{3,4}.0/gtk.css` again only when the theme
+//! changes, and a switch between two themes of the same variant leaves
+//! `gtk-theme` as it was. So the setting is moved to a stock theme and
+//! back, which every app notices.
+
+use super::gnome;
+use crate::{ctx::Ctx, theme::Theme};
+use std::{path::Path, thread, time::Duration};
+
+/// The rendered stylesheets, relative to the live tree.
+const STYLESHEETS: [&str; 2] = ["gtk/gtk-3.0.css", "gtk/gtk-4.0.css"];
+
+/// Built into GTK itself, so always there to switch to.
+const DETOUR: &str = "Adwaita";
+
+/// Long enough for apps to see the detour before the real value comes back.
+const PAUSE: Duration = Duration::from_millis(250);
+
+/// The `gtk-theme` values [`run`] sets, in order. Empty if `tree` (the live
+/// tree, or a stage) has no stylesheet.
+pub fn values(theme: &Theme, tree: &Path) -> Vec<&'static str> {
+    if !STYLESHEETS.iter().any(|s| tree.join(s).is_file()) {
+        return Vec::new();
+    }
+    let gtk_theme = if theme.is_light { gnome::GTK_THEMES.0 } else { gnome::GTK_THEMES.1 };
+    vec![DETOUR, gtk_theme]
+}
+
+/// The `gsettings` command lines [`run`] would use.
+pub fn commands(theme: &Theme, tree: &Path) -> Vec<Vec<String>> {
+    values(theme, tree)
+        .into_iter()
+        .map(|v| ["gsettings", "set", gnome::SCHEMA, "gtk-theme", v].map(str::to_owned).to_vec())
+        .collect()
+}
+
+/// Reload the stylesheets in running apps.
+pub fn run(ctx: &Ctx, theme: &Theme) {
+    for (i, value) in values(theme, &ctx.live_dir).into_iter().enumerate() {
+        if i > 0 {
+            thread::sleep(PAUSE);
+        }
+        gnome::gsettings_set(gnome::SCHEMA, "gtk-theme", value);
+    }
+}
@@ -0,0 +1,142 @@
+//! User hook scripts: every executable in `hooks/pre-set.d/` runs before a
+//! switch is staged, and every one in `hooks/post-set.d/` after the apply
+//! steps. Hooks run one at a time in file name order, with the theme
+//! described in `OXIDIZE_*` environment variables.
+
+use crate::{ctx::Ctx, log, theme::Theme};
+use anyhow::{Context, Result, bail};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long one hook may run before it is killed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    PreSet,
+    PostSet,
+}
+
+impl Stage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Stage::PreSet => "pre-set",
+            Stage::PostSet => "post-set",
+        }
+    }
+}
+
+/// The hooks [`run`] would run for `stage`, in order.
+pub fn list(ctx: &Ctx, stage: Stage) -> Vec<PathBuf> {
+    scripts(&dir(ctx, stage))
+}
+
+fn dir(ctx: &Ctx, stage: Stage) -> PathBuf {
+    ctx.hooks_dir.join(format!("{}.d", stage.as_str()))
+}
+
+/// Executables in `hooks/<stage>.d/`, sorted. Dotfiles and `~` backups are
+/// skipped.
+fn scripts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !name.ends_with('~')
+        })
+        .map(|e| e.path())
+        .filter(|p| fs::metadata(p).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+/// Run the hooks of `stage` for `theme`. Every hook runs; the error lists
+/// the ones that failed or timed out.
+pub fn run(ctx: &Ctx, stage: Stage, theme: &Theme, timeout: Duration) -> Result<()> {
+    let mut failed = Vec::new();
+    for script in list(ctx, stage) {
+        let mut cmd = Command::new(&script);
+        cmd.env("OXIDIZE_HOOK", stage.as_str())
+            .env("OXIDIZE_THEME", &theme.name)
+            .env("OXIDIZE_VARIANT", if theme.is_light { "light" } else { "dark" })
+            .env("OXIDIZE_THEME_DIR", &theme.root)
+            .env("OXIDIZE_LIVE_DIR", &ctx.live_dir);
+        log::command(&cmd);
+        if let Err(e) = run_one(&mut cmd, timeout) {
+            let name = script.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            failed.push(format!("{name}: {e:#}"));
+        }
+    }
+    if !failed.is_empty() {
+        bail!("{} hook(s) failed:\n  {}", stage.as_str(), failed.join("\n  "));
+    }
+    Ok(())
+}
+
+fn run_one(cmd: &mut Command, timeout: Duration) -> Result<()> {
+    let mut child = cmd.spawn().context("spawn")?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().context("wait")? {
+            if !status.success() {
+                bail!("{status}");
+            }
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("timed out after {}s", timeout.as_secs_f64());
+        }
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_executables_in_order_and_reports_failures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx {
+            hooks_dir: tmp.path().to_owned(),
+            ..Ctx::under(tmp.path())
+        };
+        let dir = tmp.path().join("post-set.d");
+        fs::create_dir(&dir).unwrap();
+        let log = tmp.path().join("log");
+        let script = |name: &str, body: &str, mode: u32| {
+            let path = dir.join(name);
+            fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        };
+        script("20-second", &format!("echo \"$OXIDIZE_HOOK $OXIDIZE_THEME\" >> {}", log.display()), 0o755);
+        script("10-first", &format!("echo first >> {}; exit 3", log.display()), 0o755);
+        script("30-slow", "sleep 5", 0o755);
+        script("40-not-executable", "exit 1", 0o644);
+
+        let theme = Theme {
+            name: "nord".into(),
+            root: tmp.path().to_owned(),
+            ..Theme::for_test(&[])
+        };
+        let err = run(&ctx, Stage::PostSet, &theme, Duration::from_millis(300)).unwrap_err().to_string();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "first\npost-set nord\n");
+        assert!(err.contains("10-first: exit status: 3") && err.contains("30-slow: timed out"), "{err}");
+        assert!(!err.contains("40-not-executable"), "{err}");
+    }
+}
@@ -0,0 +1,43 @@
+//! Push theme colors into a running Hyprland session via `hyprctl`.
+
+use crate::theme::Theme;
+
+/// How `set` brings a running Hyprland up to date: `[hyprland] reload`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Reload {
+    /// Set the border gradients with `hyprctl keyword`, leaving the rest of
+    /// the config alone.
+    #[default]
+    Keyword,
+    /// `hyprctl reload`, which rereads the config and with it any sourced
+    /// `hyprland-colors.conf`.
+    Full,
+}
+
+impl Reload {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "keyword" => Some(Reload::Keyword),
+            "full" => Some(Reload::Full),
+            _ => None,
+        }
+    }
+}
+
+/// The `hyprctl` command line: a full reload, or the active/inactive border
+/// gradients set live. `None` if the theme has no gradients to set.
+pub fn command(theme: &Theme, reload: Reload) -> Option<Vec<String>> {
+    if reload == Reload::Full {
+        return Some(vec!["hyprctl".to_owned(), "reload".to_owned()]);
+    }
+
+    let keywords: Vec<String> = [
+        ("general:col.active_border", "hypr_gradient_active"),
+        ("general:col.inactive_border", "hypr_gradient_inactive"),
+    ]
+    .into_iter()
+    .filter_map(|(keyword, var)| Some(format!("keyword {keyword} {}", theme.vars.get(var)?)))
+    .collect();
+
+    (!keywords.is_empty()).then(|| vec!["hyprctl".to_owned(), "--batch".to_owned(), keywords.join(" ; ")])
+}
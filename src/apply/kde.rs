@@ -0,0 +1,96 @@
+//! KDE Plasma: apply a color scheme (and optionally a global theme) in place
+//! of the GNOME settings, which Plasma ignores.
+
+use crate::{ctx::Ctx, log, theme::Theme};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The rendered scheme, relative to the live tree. Its `out` header links it
+/// into `~/.local/share/color-schemes`, where Plasma finds it by name.
+const SCHEME_FILE: &str = "kde/Oxidize.colors";
+const SCHEME: &str = "Oxidize";
+
+/// Stock schemes, for light and dark themes.
+const BREEZE: (&str, &str) = ("BreezeLight", "BreezeDark");
+
+/// `[kde]` in oxidize.toml: global theme (look-and-feel) packages to apply
+/// with `lookandfeeltool`, for light and dark themes.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub light: Option<String>,
+    pub dark: Option<String>,
+}
+
+/// Whether this is a Plasma session.
+pub fn detected() -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.split(':').any(|d| d.eq_ignore_ascii_case("kde")))
+}
+
+/// The command lines [`run`] would use: the global theme if one is set, else
+/// the Breeze scheme for the variant, then the rendered scheme if `tree` (the
+/// live tree, or a stage) has one. `plasma-apply-colorscheme` does nothing
+/// when asked for the scheme already in use, so going through another one
+/// first is what makes Plasma pick up the rerendered file.
+pub fn commands(theme: &Theme, settings: &Settings, tree: &Path) -> Vec<Vec<String>> {
+    let cmd = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    let (look_and_feel, breeze) = if theme.is_light {
+        (&settings.light, BREEZE.0)
+    } else {
+        (&settings.dark, BREEZE.1)
+    };
+    let mut commands = vec![match look_and_feel {
+        Some(package) => cmd(&["lookandfeeltool", "--apply", package]),
+        None => cmd(&["plasma-apply-colorscheme", breeze]),
+    }];
+    if tree.join(SCHEME_FILE).is_file() {
+        commands.push(cmd(&["plasma-apply-colorscheme", SCHEME]));
+    }
+    commands
+}
+
+/// Apply the color scheme (and global theme) to the running session.
+pub fn run(ctx: &Ctx, theme: &Theme, settings: &Settings) {
+    for args in commands(theme, settings, &ctx.live_dir) {
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]);
+        log::command(&cmd);
+        match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+            Ok(out) if !out.status.success() => {
+                log::warn!("{}: {}", args[0], String::from_utf8_lossy(&out.stderr).trim());
+            }
+            Err(e) => log::warn!("{}: {e}", args[0]),
+            Ok(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detours_through_breeze_unless_a_global_theme_is_set() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut theme = Theme::for_test(&[]);
+        let mut settings = Settings::default();
+        let programs = |commands: Vec<Vec<String>>| commands.into_iter().map(|c| c.join(" ")).collect::<Vec<_>>();
+        assert_eq!(programs(commands(&theme, &settings, tmp.path())), ["plasma-apply-colorscheme BreezeDark"]);
+
+        fs::create_dir_all(tmp.path().join("kde")).unwrap();
+        fs::write(tmp.path().join(SCHEME_FILE), "").unwrap();
+        assert_eq!(
+            programs(commands(&theme, &settings, tmp.path())),
+            ["plasma-apply-colorscheme BreezeDark", "plasma-apply-colorscheme Oxidize"]
+        );
+
+        theme.is_light = true;
+        settings.light = Some("org.kde.breeze.desktop".into());
+        assert_eq!(
+            programs(commands(&theme, &settings, tmp.path())),
+            ["lookandfeeltool --apply org.kde.breeze.desktop", "plasma-apply-colorscheme Oxidize"]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Launcher themes for rofi, wofi and fuzzel.
+//!
+//! Launchers read their theme each time they open, so nothing is signalled;
+//! this step only links the rendered `launcher/` files to where each
+//! launcher looks for them. The links go through `current/`, so later
+//! switches need no new links.
+
+use crate::{ctx::Ctx, log, util};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// `(launcher, file in the live tree, path under ~/.config)`.
+const LAUNCHERS: [(&str, &str, &str); 3] = [
+    ("rofi", "launcher/rofi.rasi", "rofi/themes/oxidize.rasi"),
+    ("wofi", "launcher/wofi.css", "wofi/style.css"),
+    ("fuzzel", "launcher/fuzzel.ini", "fuzzel/oxidize.ini"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub launcher: &'static str,
+    pub path: PathBuf,
+    pub target: PathBuf,
+}
+
+/// The links [`run`] would make: for each launcher whose config dir exists
+/// and whose theme `tree` (the live tree, or a stage) has. A regular file in
+/// the way is left alone.
+pub fn links(ctx: &Ctx, tree: &Path) -> Vec<Link> {
+    let config_home = ctx.config_dir.parent().unwrap_or(&ctx.config_dir);
+    LAUNCHERS
+        .into_iter()
+        .filter(|(launcher, _, _)| config_home.join(launcher).is_dir())
+        .filter(|(_, out, _)| tree.join(out).is_file())
+        .map(|(launcher, out, path)| Link {
+            launcher,
+            path: config_home.join(path),
+            target: ctx.current_link.join(out),
+        })
+        .filter(|link| !fs::symlink_metadata(&link.path).is_ok_and(|m| !m.file_type().is_symlink()))
+        .collect()
+}
+
+/// Link every launcher theme into place.
+pub fn run(ctx: &Ctx) {
+    for link in links(ctx, &ctx.live_dir) {
+        if fs::read_link(&link.path).is_ok_and(|t| t == link.target) {
+            continue;
+        }
+        log::debug!("{}: link {} -> {}", link.launcher, link.path.display(), link.target.display());
+        let made = link.path.parent().map_or(Ok(()), fs::create_dir_all).map_err(anyhow::Error::from);
+        if let Err(e) = made.and_then(|()| util::symlink_force(&link.target, &link.path)) {
+            log::warn!("{}: {e:#}", link.launcher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_installed_launchers_without_clobbering() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let config_home = tmp.path().join("config");
+        fs::create_dir_all(ctx.live_dir.join("launcher")).unwrap();
+        for file in ["rofi.rasi", "wofi.css", "fuzzel.ini"] {
+            fs::write(ctx.live_dir.join("launcher").join(file), "").unwrap();
+        }
+        for dir in ["rofi", "wofi"] {
+            fs::create_dir_all(config_home.join(dir)).unwrap();
+        }
+        fs::write(config_home.join("wofi/style.css"), "mine").unwrap();
+
+        run(&ctx);
+        assert_eq!(
+            fs::read_link(config_home.join("rofi/themes/oxidize.rasi")).unwrap(),
+            ctx.current_link.join("launcher/rofi.rasi")
+        );
+        assert_eq!(fs::read_to_string(config_home.join("wofi/style.css")).unwrap(), "mine");
+        assert!(!config_home.join("fuzzel").exists());
+        assert!(links(&ctx, &ctx.live_dir).iter().all(|l| l.launcher == "rofi"));
+    }
+}
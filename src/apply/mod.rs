@@ -1,14 +1,72 @@
-//! Desktop apply steps — GNOME settings, app reloads, wallpaper cycling.
+//! Desktop apply steps — GNOME settings, compositor, app reloads, wallpaper cycling.
 
+pub mod bat;
+pub mod compositor;
+pub mod emacs;
+pub mod firefox;
+pub mod fish;
 pub mod gnome;
+pub mod gtk;
+pub mod hooks;
+pub mod hyprland;
+pub mod kde;
+pub mod launcher;
+pub mod neovim;
+pub mod niri;
+pub mod qutebrowser;
 pub mod reload;
+pub mod river;
+pub mod sway;
+pub mod tmux;
+pub mod verify;
+pub mod vscode;
+pub mod waybar;
 pub mod wallpaper;
+pub mod xresources;
 
-#[derive(Debug, Clone, Copy)]
+use anyhow::{Result, bail};
+
+#[derive(Debug, Clone, Copy, Default)]
 pub struct ApplyFlags {
     pub no_apply: bool,
     pub no_gnome: bool,
+    /// Don't apply the KDE color scheme.
+    pub no_kde: bool,
     pub no_icons: bool,
     pub no_reload: bool,
     pub no_wallpaper: bool,
+    /// Don't link launcher themes into the launchers' config dirs.
+    pub no_launcher: bool,
+    /// Skip `hooks/pre-set.d` and `hooks/post-set.d`.
+    pub no_hooks: bool,
+    /// Update files and symlinks only; spawn no processes and send no signals.
+    pub minimal: bool,
+}
+
+impl ApplyFlags {
+    /// Turn off the desktop step that doesn't apply here: Plasma ignores the
+    /// GNOME settings and gets its own step instead.
+    pub fn detect_desktop(&mut self) {
+        if kde::detected() {
+            self.no_gnome = true;
+        } else {
+            self.no_kde = true;
+        }
+    }
+
+    /// Turn off a single apply step by name, as used in presets.
+    pub fn skip(&mut self, step: &str) -> Result<()> {
+        match step {
+            "apply" => self.no_apply = true,
+            "gnome" => self.no_gnome = true,
+            "kde" => self.no_kde = true,
+            "icons" => self.no_icons = true,
+            "reload" => self.no_reload = true,
+            "wallpaper" => self.no_wallpaper = true,
+            "launcher" => self.no_launcher = true,
+            "hooks" => self.no_hooks = true,
+            other => bail!("unknown apply step '{other}'"),
+        }
+        Ok(())
+    }
 }
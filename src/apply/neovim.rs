@@ -0,0 +1,87 @@
+//! Recolor open Neovim instances over their RPC sockets.
+
+use crate::log;
+use std::{
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Ex command run in every instance when `[neovim] command` isn't set.
+pub const DEFAULT_COMMAND: &str = "colorscheme oxidize";
+
+/// Server sockets under `dir`: Neovim names them `nvim.<pid>.<n>`, either
+/// directly in `$XDG_RUNTIME_DIR` or somewhere in a per-user `nvim.<user>/`
+/// subdir.
+fn sockets_in(dir: &Path) -> Vec<PathBuf> {
+    let mut sockets: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(3)
+        .into_iter()
+        .filter_entry(|e| e.depth() != 1 || !e.file_type().is_dir() || e.file_name().to_string_lossy().starts_with("nvim"))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_socket() && e.file_name().to_string_lossy().starts_with("nvim."))
+        .map(|e| e.into_path())
+        .collect();
+    sockets.sort();
+    sockets
+}
+
+/// Sockets of the running Neovim servers.
+pub fn sockets() -> Vec<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map_or_else(Vec::new, |dir| sockets_in(Path::new(&dir)))
+}
+
+/// One `nvim --remote-expr` call per running instance, each running
+/// `command` (an Ex command) without disturbing the editor's mode.
+pub fn commands(command: &str) -> Vec<Vec<String>> {
+    let expr = format!("execute('{}')", command.replace('\'', "''"));
+    sockets()
+        .into_iter()
+        .map(|socket| {
+            vec![
+                "nvim".to_owned(),
+                "--server".to_owned(),
+                socket.display().to_string(),
+                "--remote-expr".to_owned(),
+                expr.clone(),
+            ]
+        })
+        .collect()
+}
+
+/// Send `command` to every running instance. Sockets left by crashed
+/// instances just refuse the connection.
+pub fn run(command: &str) {
+    for args in commands(command) {
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]);
+        log::command(&cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::net::UnixListener};
+
+    #[test]
+    fn finds_nvim_sockets_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("nvim.me")).unwrap();
+        fs::create_dir_all(tmp.path().join("other")).unwrap();
+        let _listeners: Vec<UnixListener> = ["nvim.100.0", "nvim.me/nvim.200.0", "other/nvim.300.0", "wayland-1"]
+            .iter()
+            .map(|p| UnixListener::bind(tmp.path().join(p)).unwrap())
+            .collect();
+        fs::write(tmp.path().join("nvim.400.0"), "").unwrap();
+
+        let found: Vec<PathBuf> = sockets_in(tmp.path()).iter().map(|s| s.strip_prefix(tmp.path()).unwrap().to_owned()).collect();
+        assert_eq!(found, [PathBuf::from("nvim.100.0"), PathBuf::from("nvim.me/nvim.200.0")]);
+    }
+}
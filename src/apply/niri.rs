@@ -0,0 +1,7 @@
+//! Make a running niri reread its config via `niri msg`.
+
+/// `niri msg action load-config-file`. niri also reloads on its own when the
+/// config file changes, but not when only an included file's target does.
+pub fn command() -> Vec<String> {
+    ["niri", "msg", "action", "load-config-file"].map(str::to_owned).to_vec()
+}
@@ -0,0 +1,48 @@
+//! Have a running qutebrowser reread its config, which sources the rendered
+//! `colors.py`.
+//!
+//! Run with a command argument, `qutebrowser` hands it to the instance that
+//! is already running over IPC and exits. With none running it would start a
+//! browser instead, so nothing is sent then.
+
+use crate::{ctx::Ctx, log, session};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The rendered colors, relative to the live tree.
+const COLORS_FILE: &str = "qutebrowser/colors.py";
+
+/// The command line [`run`] would use, if `tree` (the live tree, or a stage)
+/// has the colors and qutebrowser is running (in this session, when scoped).
+pub fn command(ctx: &Ctx, tree: &Path) -> Option<Vec<String>> {
+    if !tree.join(COLORS_FILE).is_file() {
+        return None;
+    }
+    let pids = match &ctx.session {
+        Some(display) => session::pids("qutebrowser", display),
+        None => session::named("qutebrowser"),
+    };
+    if pids.is_empty() {
+        return None;
+    }
+    Some(vec!["qutebrowser".to_owned(), ":config-source".to_owned()])
+}
+
+/// Reload the config of the running qutebrowser.
+pub fn run(ctx: &Ctx) {
+    let Some(args) = command(ctx, &ctx.live_dir) else {
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+        Ok(out) if !out.status.success() => {
+            log::warn!("qutebrowser: {}", String::from_utf8_lossy(&out.stderr).trim());
+        }
+        Err(e) => log::warn!("qutebrowser: {e}"),
+        Ok(_) => {}
+    }
+}
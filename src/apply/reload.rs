@@ -1,35 +1,93 @@
 //! Reload running apps after a theme change.
 
-use crate::ctx::Ctx;
-use std::{
-    process::{Command, Stdio},
-};
+use super::waybar::{self, Instance};
+use crate::{ctx::Ctx, log, session, util};
+use std::process::{Command, Stdio};
 
-/// Reload waybar, mako, kitty, btop, and XDG portals.
-pub fn run(ctx: &Ctx) {
-    pkill_signal("waybar", "SIGUSR2");
-
-    detach(Command::new("makoctl").arg("reload"));
+/// One thing [`run`] does to a running app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Send `signal` to every `process`, or only this session's when scoped.
+    Signal { process: &'static str, signal: &'static str },
+    /// Send `signal` to these processes only.
+    SignalPids { process: &'static str, signal: &'static str, pids: Vec<u32> },
+    /// Stop a waybar and start it again with the same command line.
+    RestartWaybar(Instance),
+    /// Spawn a command without waiting for it.
+    Spawn(Vec<String>),
+}
 
+/// What [`run`] would do, in order.
+pub fn actions(ctx: &Ctx, waybar: &waybar::Settings) -> Vec<Action> {
+    let spawn = |args: &[&str]| Action::Spawn(args.iter().map(|a| a.to_string()).collect());
+    let mut actions = waybar_actions(ctx, waybar);
+    actions.push(spawn(&["makoctl", "reload"]));
     // Use spawn (not status) — systemctl can block on D-Bus activation.
     for service in [
         "xdg-desktop-portal.service",
         "xdg-desktop-portal-gtk.service",
     ] {
-        detach(Command::new("systemctl").args(["--user", "restart", service]));
+        actions.push(spawn(&["systemctl", "--user", "restart", service]));
+    }
+    actions.push(Action::Signal { process: "btop", signal: "SIGUSR2" });
+    actions.push(Action::Signal { process: "kitty", signal: "SIGUSR1" });
+    actions.push(Action::Signal { process: "ghostty", signal: "SIGUSR1" });
+    // Helix rereads its config, and with it the theme, on SIGUSR1. The
+    // binary is `hx` upstream but `helix` in some distributions.
+    actions.push(Action::Signal { process: "hx", signal: "SIGUSR1" });
+    actions.push(Action::Signal { process: "helix", signal: "SIGUSR1" });
+    actions.extend(touch_configs(ctx));
+    actions
+}
+
+fn waybar_actions(ctx: &Ctx, settings: &waybar::Settings) -> Vec<Action> {
+    const SIGNAL: Action = Action::Signal { process: "waybar", signal: "SIGUSR2" };
+    match settings.mode {
+        waybar::Mode::Signal if settings.configs.is_empty() => vec![SIGNAL],
+        waybar::Mode::Signal => {
+            let pids: Vec<u32> = waybar::instances(ctx, settings).iter().map(|i| i.pid).collect();
+            if pids.is_empty() {
+                return Vec::new();
+            }
+            vec![Action::SignalPids { process: "waybar", signal: "SIGUSR2", pids }]
+        }
+        waybar::Mode::Restart => waybar::instances(ctx, settings).into_iter().map(Action::RestartWaybar).collect(),
     }
-    pkill_signal("btop", "SIGUSR2");
-    pkill_signal("kitty", "SIGUSR1");
-    pkill_signal("ghostty", "SIGUSR1");
-    reload_alacritty(ctx);
 }
 
-fn pkill_signal(name: &str, signal: &str) {
+/// Reload waybar, mako, kitty, btop, Helix, and XDG portals.
+pub fn run(ctx: &Ctx, waybar: &waybar::Settings) {
+    for action in actions(ctx, waybar) {
+        match action {
+            Action::Signal { process, signal } => pkill_signal(ctx, process, signal),
+            Action::SignalPids { signal, pids, .. } => {
+                detach(Command::new("kill").arg(format!("-{signal}")).args(pids.iter().map(u32::to_string)));
+            }
+            Action::RestartWaybar(instance) => {
+                if let Err(e) = waybar::restart(&instance) {
+                    log::warn!("{e:#}");
+                }
+            }
+            Action::Spawn(args) => detach(Command::new(&args[0]).args(&args[1..])),
+        }
+    }
+}
+
+/// Signal every `name` process, or only this session's when scoped.
+fn pkill_signal(ctx: &Ctx, name: &str, signal: &str) {
     let flag = format!("-{signal}");
-    detach(Command::new("pkill").args([flag.as_str(), name]));
+    let Some(display) = &ctx.session else {
+        detach(Command::new("pkill").args([flag.as_str(), name]));
+        return;
+    };
+    let pids = session::pids(name, display);
+    if !pids.is_empty() {
+        detach(Command::new("kill").arg(&flag).args(pids.iter().map(u32::to_string)));
+    }
 }
 
 fn detach(cmd: &mut Command) {
+    log::command(cmd);
     cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -37,11 +95,17 @@ fn detach(cmd: &mut Command) {
         .ok();
 }
 
-// 'touch' alacritty.toml for alacritty to hot reload.
-// TODO: need a better lookup structure
-fn reload_alacritty(ctx: &Ctx) {
-    let conf = ctx.config_dir.parent().unwrap_or(&ctx.config_dir).join("alacritty/alacritty.toml");
-    if conf.exists() {
-        detach(Command::new("touch").arg(conf));
-    }
+/// Config files whose apps reload when they change but not when a file
+/// they include does, relative to `~/.config` (or `~` with a leading `~/`).
+const TOUCH: [&str; 3] = ["alacritty/alacritty.toml", "wezterm/wezterm.lua", "~/.wezterm.lua"];
+
+/// `touch` each of [`TOUCH`] that exists, so its app hot-reloads.
+fn touch_configs(ctx: &Ctx) -> Vec<Action> {
+    let config_home = ctx.config_dir.parent().unwrap_or(&ctx.config_dir);
+    TOUCH
+        .into_iter()
+        .map(|path| if path.starts_with('~') { util::expand_home(path) } else { config_home.join(path) })
+        .filter(|conf| conf.exists())
+        .map(|conf| Action::Spawn(vec!["touch".into(), conf.display().to_string()]))
+        .collect()
 }
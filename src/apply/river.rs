@@ -0,0 +1,38 @@
+//! Push theme colors into a running river session via `riverctl`.
+
+use crate::{color::Rgb, render::engine::resolve_key, theme::Theme};
+
+/// One `riverctl` call per color: the focused and unfocused borders from
+/// `accent` and `bg`, the urgent border from `red` when the theme has one,
+/// and the background from `bg`.
+pub fn commands(theme: &Theme) -> Vec<Vec<String>> {
+    let color = |name: &str| {
+        resolve_key(&theme.vars, name)
+            .and_then(|(_, v)| Rgb::parse(v))
+            .map(|c| format!("0x{}", &c.to_hex()[1..]))
+    };
+    [
+        ("border-color-focused", color("accent")),
+        ("border-color-unfocused", color("bg")),
+        ("border-color-urgent", color("red")),
+        ("background-color", color("bg")),
+    ]
+    .into_iter()
+    .filter_map(|(setting, value)| Some(vec!["riverctl".to_owned(), setting.to_owned(), value?]))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_colors_the_theme_has() {
+        let theme = Theme::for_test(&[("palette_bg", "#1e1e2e"), ("palette_accent", "#89B4FA")]);
+        let settings: Vec<String> = commands(&theme).iter().map(|c| c[1..].join(" ")).collect();
+        assert_eq!(
+            settings,
+            ["border-color-focused 0x89b4fa", "border-color-unfocused 0x1e1e2e", "background-color 0x1e1e2e"]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Push theme colors into a running sway session via `swaymsg`.
+
+use crate::{color::Rgb, render::engine::resolve_key, theme::Theme};
+
+/// How `set` brings a running sway up to date: `[sway] reload`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Reload {
+    /// `swaymsg reload`, which rereads the config and with it any included
+    /// `sway/colors`.
+    #[default]
+    Full,
+    /// Set the `client.*` border colors live, without the flicker and
+    /// bar restarts of a full reload.
+    Client,
+}
+
+impl Reload {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "full" => Some(Reload::Full),
+            "client" => Some(Reload::Client),
+            _ => None,
+        }
+    }
+}
+
+/// `client.<class> <border> <background> <text> <indicator> <child_border>`
+/// for each window class, from the theme's `bg`, `fg` and `accent`.
+fn client_colors(theme: &Theme) -> Option<Vec<String>> {
+    let color = |name: &str| resolve_key(&theme.vars, name).and_then(|(_, v)| Rgb::parse(v)).map(Rgb::to_hex);
+    let (bg, fg, accent) = (color("bg")?, color("fg")?, color("accent")?);
+    Some(
+        [
+            ("focused", &accent, &accent, &bg),
+            ("focused_inactive", &bg, &bg, &fg),
+            ("unfocused", &bg, &bg, &fg),
+        ]
+        .into_iter()
+        .map(|(class, border, background, text)| format!("client.{class} {border} {background} {text} {border} {border}"))
+        .collect(),
+    )
+}
+
+/// The `swaymsg` command line: a full reload, or the client colors set
+/// live. `None` if the theme lacks the colors to set.
+pub fn command(theme: &Theme, reload: Reload) -> Option<Vec<String>> {
+    let commands = match reload {
+        Reload::Full => "reload".to_owned(),
+        Reload::Client => client_colors(theme)?.join("; "),
+    };
+    Some(vec!["swaymsg".to_owned(), commands])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_colors_follow_the_palette() {
+        let theme = Theme::for_test(&[("palette_bg", "#1E1E2E"), ("palette_fg", "#cdd6f4"), ("palette_accent", "#89b4fa")]);
+        let args = command(&theme, Reload::Client).unwrap();
+        assert_eq!(args[0], "swaymsg");
+        assert!(
+            args[1].starts_with("client.focused #89b4fa #89b4fa #1e1e2e #89b4fa #89b4fa; client.focused_inactive #1e1e2e"),
+            "{}",
+            args[1]
+        );
+        assert_eq!(command(&theme, Reload::Full).unwrap(), ["swaymsg", "reload"]);
+    }
+}
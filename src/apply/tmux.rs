@@ -0,0 +1,95 @@
+//! Recolor a running tmux server, so every existing session updates at once.
+
+use crate::{color::Rgb, ctx::Ctx, log, render::engine::resolve_key, theme::Theme};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The rendered theme file, relative to the live tree.
+const THEME_FILE: &str = "tmux/tmux-theme.conf";
+
+/// Global style options from the theme's `bg`, `fg` and `accent`, matching
+/// the default pack's `tmux-theme.conf`. Empty if a color is missing.
+fn options(theme: &Theme) -> Vec<(&'static str, String)> {
+    let color = |name: &str| resolve_key(&theme.vars, name).and_then(|(_, v)| Rgb::parse(v));
+    let (Some(bg), Some(fg), Some(accent)) = (color("bg"), color("fg"), color("accent")) else {
+        return Vec::new();
+    };
+    let (bg, fg, accent, border) = (bg.to_hex(), fg.to_hex(), accent.to_hex(), fg.mix(bg, 0.5).to_hex());
+    vec![
+        ("status-style", format!("bg={bg},fg={fg}")),
+        ("window-status-current-style", format!("fg={accent},bold")),
+        ("pane-border-style", format!("fg={border}")),
+        ("pane-active-border-style", format!("fg={accent}")),
+        ("message-style", format!("bg={accent},fg={bg}")),
+        ("mode-style", format!("bg={accent},fg={bg}")),
+    ]
+}
+
+/// The `tmux` command line [`run`] would use: source the rendered
+/// `tmux-theme.conf` if `tree` (the live tree, or a stage) has one, else set
+/// the core styles directly. `None` when no tmux server is running.
+pub fn command(ctx: &Ctx, theme: &Theme, tree: &Path) -> Option<Vec<String>> {
+    if !server_running() {
+        return None;
+    }
+    let mut args = vec!["tmux".to_owned()];
+    if tree.join(THEME_FILE).is_file() {
+        args.extend(["source-file".to_owned(), ctx.current_link.join(THEME_FILE).display().to_string()]);
+        return Some(args);
+    }
+    let options = options(theme);
+    if options.is_empty() {
+        return None;
+    }
+    for (i, (option, value)) in options.into_iter().enumerate() {
+        if i > 0 {
+            args.push(";".to_owned());
+        }
+        args.extend(["set".to_owned(), "-g".to_owned(), option.to_owned(), value]);
+    }
+    Some(args)
+}
+
+fn server_running() -> bool {
+    Command::new("tmux")
+        .arg("has-session")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Apply the theme to every session of the running tmux server.
+pub fn run(ctx: &Ctx, theme: &Theme) {
+    let Some(args) = command(ctx, theme, &ctx.live_dir) else {
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+        Ok(out) if !out.status.success() => {
+            log::warn!("tmux: {}", String::from_utf8_lossy(&out.stderr).trim());
+        }
+        Err(e) => log::warn!("tmux: {e}"),
+        Ok(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_follow_the_palette() {
+        let mut theme = Theme::for_test(&[("palette_bg", "#000000"), ("palette_fg", "#FFFFFF"), ("palette_accent", "#89b4fa")]);
+        let options = options(&theme);
+        assert_eq!(options[0], ("status-style", "bg=#000000,fg=#ffffff".to_owned()));
+        assert_eq!(options[3], ("pane-active-border-style", "fg=#89b4fa".to_owned()));
+        theme.vars.remove("palette_accent");
+        assert!(super::options(&theme).is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+//! Post-apply verification: read back what the apply steps were supposed to
+//! change and report anything that didn't take.
+
+use super::{ApplyFlags, gnome};
+use crate::{ctx::Ctx, log, session, theme::Theme};
+use std::{
+    fs,
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// Grace period for asynchronous reloads (waybar, awww) before checking.
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// State captured before the apply steps run.
+pub struct Watch {
+    waybar_running: bool,
+    wezterm_running: bool,
+}
+
+impl Watch {
+    pub fn before(ctx: &Ctx) -> Self {
+        Self {
+            waybar_running: is_running(ctx, "waybar"),
+            wezterm_running: is_running(ctx, "wezterm-gui"),
+        }
+    }
+
+    /// Check every enabled apply step. Returns the list of discrepancies.
+    pub fn check(&self, ctx: &Ctx, theme: &Theme, flags: &ApplyFlags) -> Vec<String> {
+        thread::sleep(SETTLE);
+        let mut problems = Vec::new();
+
+        if !flags.no_gnome {
+            for (key, want) in gnome::settings(theme, flags.no_icons) {
+                match gnome::gsettings_get(gnome::SCHEMA, key) {
+                    Some(got) if got == want => log::debug!("gsettings {key} = {got}"),
+                    Some(got) => problems.push(format!("gsettings {key} is '{got}', expected '{want}'")),
+                    None => problems.push(format!("gsettings {key} could not be read")),
+                }
+            }
+        }
+
+        if !flags.no_reload && self.waybar_running && !is_running(ctx, "waybar") {
+            problems.push("waybar was running before the reload and is gone now".to_owned());
+        }
+
+        // A config WezTerm fails to load leaves it running on the old one,
+        // but a crash on reload shows up as the mux no longer answering.
+        if !flags.no_reload && self.wezterm_running && output(Command::new("wezterm").args(["cli", "list"])).is_none() {
+            problems.push("wezterm stopped answering `wezterm cli list` after the reload".to_owned());
+        }
+
+        if !flags.no_wallpaper
+            && let Ok(want) = fs::canonicalize(&ctx.background_link)
+        {
+            let want = want.to_string_lossy();
+            match output(Command::new("awww").arg("query")) {
+                Some(out) if out.contains(want.as_ref()) => log::debug!("awww displays {want}"),
+                Some(_) => problems.push(format!("awww is not displaying {want}")),
+                None => problems.push("awww query failed".to_owned()),
+            }
+        }
+
+        problems
+    }
+}
+
+fn is_running(ctx: &Ctx, name: &str) -> bool {
+    if let Some(display) = &ctx.session {
+        return !session::pids(name, display).is_empty();
+    }
+    let mut cmd = Command::new("pgrep");
+    cmd.args(["-x", name]);
+    log::command(&cmd);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Run `cmd` and return stdout if it exited successfully.
+fn output(cmd: &mut Command) -> Option<String> {
+    log::command(cmd);
+    let out = cmd.stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
@@ -0,0 +1,168 @@
+//! VS Code: point `workbench.colorTheme` at the light or dark theme to match,
+//! and optionally fill `workbench.colorCustomizations` from the palette.
+//!
+//! VS Code won't use a symlinked `settings.json`, so the user's file is
+//! patched in place of the two keys (see [`json::set_member`]), staged into
+//! the live tree as `vscode/<n>/settings.json`, and copied out like a
+//! `#! copy = true` template (see [`crate::external`]).
+
+use crate::{
+    color::Rgb,
+    ctx::Ctx,
+    json::{self, Value},
+    render::{External, engine::resolve_key},
+    theme::Theme,
+};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// Config dirs of the VS Code builds looked for under `~/.config`.
+const APPS: [&str; 3] = ["Code", "Code - OSS", "VSCodium"];
+
+/// `[vscode]` in oxidize.toml.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// `workbench.colorTheme` for light and dark themes.
+    pub light: Option<String>,
+    pub dark: Option<String>,
+    /// Write `workbench.colorCustomizations` from the palette.
+    pub customizations: bool,
+    /// Settings files to patch; those of every installed build if empty.
+    pub files: Vec<PathBuf>,
+}
+
+impl Settings {
+    fn enabled(&self) -> bool {
+        self.light.is_some() || self.dark.is_some() || self.customizations
+    }
+}
+
+/// The settings files to patch.
+fn files(ctx: &Ctx, settings: &Settings) -> Vec<PathBuf> {
+    if !settings.files.is_empty() {
+        return settings.files.clone();
+    }
+    let config_home = ctx.config_dir.parent().unwrap_or(&ctx.config_dir);
+    APPS.iter()
+        .map(|app| config_home.join(app).join("User"))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.join("settings.json"))
+        .collect()
+}
+
+/// Editor, terminal and chrome colors from the theme's palette.
+fn customizations(theme: &Theme) -> Option<Value> {
+    let color = |name: &str| resolve_key(&theme.vars, name).and_then(|(_, v)| Rgb::parse(v));
+    let (bg, fg) = (color("bg")?, color("fg")?);
+    let accent = color("accent").unwrap_or(fg);
+    let surface = bg.mix(fg, 0.05);
+    let mut colors: Vec<(String, Value)> = [
+        ("editor.background", bg),
+        ("editor.foreground", fg),
+        ("editor.lineHighlightBackground", bg.mix(fg, 0.06)),
+        ("editor.selectionBackground", bg.mix(accent, 0.3)),
+        ("editorCursor.foreground", accent),
+        ("editorLineNumber.foreground", fg.mix(bg, 0.5)),
+        ("editorLineNumber.activeForeground", accent),
+        ("sideBar.background", surface),
+        ("activityBar.background", surface),
+        ("titleBar.activeBackground", surface),
+        ("statusBar.background", surface),
+        ("statusBar.foreground", fg),
+        ("panel.background", bg),
+        ("terminal.background", bg),
+        ("terminal.foreground", fg),
+        ("focusBorder", accent),
+        ("button.background", accent),
+        ("button.foreground", bg),
+        ("activityBarBadge.background", accent),
+        ("activityBarBadge.foreground", bg),
+    ]
+    .into_iter()
+    .map(|(key, c)| (key.to_owned(), c.to_hex().into()))
+    .collect();
+
+    const ANSI: [&str; 8] = ["Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White"];
+    for i in 0..16 {
+        if let Some(c) = color(&format!("color{i}")) {
+            let bright = if i >= 8 { "Bright" } else { "" };
+            colors.push((format!("terminal.ansi{bright}{}", ANSI[i % 8]), c.to_hex().into()));
+        }
+    }
+    Some(Value::Obj(colors))
+}
+
+/// `src` with the keys `settings` asks for set for `theme`.
+fn patch(src: &str, theme: &Theme, settings: &Settings) -> Result<String> {
+    let mut out = src.to_owned();
+    let name = if theme.is_light { &settings.light } else { &settings.dark };
+    if let Some(name) = name {
+        out = json::set_member(&out, "workbench.colorTheme", &name.as_str().into())?;
+    }
+    if settings.customizations
+        && let Some(colors) = customizations(theme)
+    {
+        out = json::set_member(&out, "workbench.colorCustomizations", &colors)?;
+    }
+    Ok(out)
+}
+
+/// Stage a patched copy of each settings file into `stage`, returning the
+/// copies for [`crate::external::write_all`] to put in place.
+pub fn stage(ctx: &Ctx, theme: &Theme, settings: &Settings, stage: &Path) -> Result<Vec<External>> {
+    if !settings.enabled() {
+        return Ok(Vec::new());
+    }
+    let mut externals = Vec::new();
+    for (i, target) in files(ctx, settings).into_iter().enumerate() {
+        let src = match fs::read_to_string(&target) {
+            Ok(src) => src,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("read {}", target.display())),
+        };
+        let patched = patch(&src, theme, settings).with_context(|| format!("parse {}", target.display()))?;
+        let out = PathBuf::from(format!("vscode/{i}/settings.json"));
+        let path = stage.join(&out);
+        fs::create_dir_all(path.parent().unwrap_or(stage)).with_context(|| format!("create {}", path.display()))?;
+        fs::write(&path, patched).with_context(|| format!("write {}", path.display()))?;
+        externals.push(External { out, target, copy: true });
+    }
+    Ok(externals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_patched_settings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let config_home = tmp.path().join("config");
+        fs::create_dir_all(config_home.join("VSCodium/User")).unwrap();
+        let target = config_home.join("VSCodium/User/settings.json");
+        fs::write(&target, "{\n    \"editor.fontSize\": 14 // mine\n}\n").unwrap();
+        let theme = Theme::for_test(&[("palette_bg", "#000000"), ("palette_fg", "#ffffff"), ("palette_color9", "#ff0000")]);
+        let settings = Settings {
+            light: Some("Default Light Modern".into()),
+            dark: Some("Default Dark Modern".into()),
+            customizations: true,
+            files: Vec::new(),
+        };
+
+        let stage_dir = tmp.path().join("stage");
+        let externals = stage(&ctx, &theme, &settings, &stage_dir).unwrap();
+        assert_eq!(externals.len(), 1);
+        assert_eq!(externals[0].target, target);
+        let patched = fs::read_to_string(stage_dir.join(&externals[0].out)).unwrap();
+        assert!(patched.contains("14, // mine\n"), "{patched}");
+        let value = json::parse(&patched).unwrap();
+        assert_eq!(value.get("workbench.colorTheme").and_then(Value::as_str), Some("Default Dark Modern"));
+        let colors = value.get("workbench.colorCustomizations").unwrap();
+        assert_eq!(colors.get("terminal.ansiBrightRed").and_then(Value::as_str), Some("#ff0000"));
+    }
+}
@@ -1,19 +1,71 @@
-//! Wallpaper cycling via `awww`.
-use crate::{ctx::Ctx, theme::Theme, util};
-use anyhow::Result;
+//! Wallpaper cycling and pinning via `awww`.
+use crate::{ctx::Ctx, log, theme::Theme, util};
+use anyhow::{Context, Result, bail};
 use std::{
     fs,
+    io::Read,
     path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
+/// Which way to step through the sorted wallpaper list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Next,
+    Prev,
+}
+
 /// Cycle to the next wallpaper and launch `awww`.
 pub fn run(ctx: &Ctx, theme: &Theme) -> Result<()> {
+    cycle(ctx, theme, Direction::Next)
+}
+
+/// Step through the theme's wallpapers in `dir`, wrapping around.
+pub fn cycle(ctx: &Ctx, theme: &Theme, dir: Direction) -> Result<()> {
+    if advance(ctx, theme, dir)? {
+        change_wallpaper(&ctx.background_link);
+    } else {
+        notify(&format!("No wallpaper found for theme '{}'", theme.name));
+    }
+    Ok(())
+}
+
+/// Apply the main wallpaper to every output, then give each output in
+/// `outputs` its own theme's wallpaper (tracked by `background.<output>`).
+pub fn run_outputs(ctx: &Ctx, theme: &Theme, outputs: &[(String, Theme)]) -> Result<()> {
+    if advance(ctx, theme, Direction::Next)? {
+        // Wait, so the per-output calls below land after the global one.
+        wait(wallpaper_cmd(&ctx.background_link, None));
+    }
+
+    for (output, out_theme) in outputs {
+        let link = output_link(ctx, output);
+        let candidates = collect_from(ctx, out_theme, out_theme.backgrounds_dir.as_deref());
+        if candidates.is_empty() {
+            log::debug!("no wallpaper for output {output} (theme '{}')", out_theme.name);
+            continue;
+        }
+        let current = fs::canonicalize(&link).ok().map(|p| p.to_string_lossy().into_owned());
+        let next = pick_next(&candidates, current.as_deref());
+        log::debug!("wallpaper[{output}] -> {}", next.display());
+        util::symlink_force(next, &link)?;
+        wait(wallpaper_cmd(&link, Some(output)));
+    }
+    Ok(())
+}
+
+/// The `background.<output>` link next to the main `background` link.
+pub fn output_link(ctx: &Ctx, output: &str) -> PathBuf {
+    ctx.background_link.with_file_name(format!("background.{output}"))
+}
+
+/// Point the `background` symlink at the next candidate without running any
+/// external command. Returns `false` if the theme has no wallpapers.
+pub fn advance(ctx: &Ctx, theme: &Theme, dir: Direction) -> Result<bool> {
     let candidates = collect_candidates(ctx, theme);
 
     if candidates.is_empty() {
-        notify(&format!("No wallpaper found for theme '{}'", theme.name));
-        return Ok(());
+        return Ok(false);
     }
 
     // Canonicalize the link itself so relative symlink targets resolve
@@ -22,13 +74,104 @@ pub fn run(ctx: &Ctx, theme: &Theme) -> Result<()> {
         .ok()
         .map(|p| p.to_string_lossy().into_owned());
 
+    let next = match dir {
+        Direction::Next => pick_next(&candidates, current.as_deref()),
+        Direction::Prev => pick_prev(&candidates, current.as_deref()),
+    };
+    log::debug!("wallpaper -> {}", next.display());
+    point_background(ctx, next)?;
+    Ok(true)
+}
+
+/// The image [`run`] would point the `background` link at once `theme` is
+/// live, without touching anything. `None` if the theme has no wallpapers.
+pub fn peek(ctx: &Ctx, theme: &Theme) -> Option<PathBuf> {
+    // The live tree may still be the old theme's, so look in the theme's own
+    // backgrounds dir and name the result as it will be seen through `current`.
+    let theme_bg = theme.backgrounds_dir.as_deref();
+    let candidates = collect_from(ctx, theme, theme_bg);
+    if candidates.is_empty() {
+        return None;
+    }
+    let current = fs::canonicalize(&ctx.background_link).ok().map(|p| p.to_string_lossy().into_owned());
     let next = pick_next(&candidates, current.as_deref());
-    util::symlink_force(&next, &ctx.background_link)?;
+    Some(match theme_bg.and_then(|bg| next.strip_prefix(bg).ok()) {
+        Some(rel) => ctx.current_link.join("backgrounds").join(rel),
+        None => next.clone(),
+    })
+}
+
+/// The `awww` command line that shows the `background` link.
+pub fn command(ctx: &Ctx) -> Vec<String> {
+    argv(&ctx.background_link, None)
+}
+
+/// Update this session's background link, and the shared one if distinct.
+fn point_background(ctx: &Ctx, target: &Path) -> Result<()> {
+    util::symlink_force(target, &ctx.background_link)?;
+    if ctx.shared_background_link != ctx.background_link {
+        util::symlink_force(target, &ctx.shared_background_link)?;
+    }
+    Ok(())
+}
+
+/// Point the background links back at `target` (or remove them), and show
+/// it when `show` is set.
+pub fn restore(ctx: &Ctx, target: Option<&Path>, show: bool) -> Result<()> {
+    match target {
+        Some(target) => {
+            point_background(ctx, target)?;
+            if show {
+                change_wallpaper(&ctx.background_link);
+            }
+        }
+        None => {
+            for link in [&ctx.background_link, &ctx.shared_background_link] {
+                if fs::symlink_metadata(link).is_ok_and(|m| m.file_type().is_symlink()) {
+                    fs::remove_file(link).with_context(|| format!("remove {}", link.display()))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pin a specific image as the wallpaper.
+pub fn set(ctx: &Ctx, path: &Path) -> Result<()> {
+    let path = fs::canonicalize(path).with_context(|| format!("resolve {}", path.display()))?;
+    if !path.is_file() {
+        bail!("not a file: {}", path.display());
+    }
+    if !is_image(&path).with_context(|| format!("read {}", path.display()))? {
+        bail!("not a supported image: {}", path.display());
+    }
 
+    log::debug!("wallpaper -> {}", path.display());
+    point_background(ctx, &path)?;
     change_wallpaper(&ctx.background_link);
     Ok(())
 }
 
+/// ISO media brands of AVIF and HEIC images; MP4 and QuickTime video share
+/// the `ftyp` box but not these.
+const HEIF_BRANDS: [&[u8]; 5] = [b"avif", b"avis", b"heic", b"heix", b"mif1"];
+
+/// Sniff the file header for an image format `awww` can decode.
+fn is_image(path: &Path) -> std::io::Result<bool> {
+    let mut head = [0u8; 12];
+    let n = fs::File::open(path)?.read(&mut head)?;
+    let head = &head[..n];
+
+    Ok(head.starts_with(b"\x89PNG")
+        || head.starts_with(&[0xFF, 0xD8, 0xFF])
+        || head.starts_with(b"GIF8")
+        || head.starts_with(b"BM")
+        || head.starts_with(b"II*\0")
+        || head.starts_with(b"MM\0*")
+        || (head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP"))
+        || (head.get(4..8) == Some(b"ftyp") && head.get(8..12).is_some_and(|b| HEIF_BRANDS.contains(&b))))
+}
+
 struct Candidate {
     path: PathBuf,
     canonical: Option<String>,
@@ -36,6 +179,11 @@ struct Candidate {
 
 /// Collect, deduplicate, and sort all wallpaper file paths.
 fn collect_candidates(ctx: &Ctx, theme: &Theme) -> Vec<Candidate> {
+    collect_from(ctx, theme, Some(&ctx.current_link.join("backgrounds")))
+}
+
+/// Like [`collect_candidates`], with an explicit theme backgrounds dir.
+fn collect_from(ctx: &Ctx, theme: &Theme, theme_bg: Option<&Path>) -> Vec<Candidate> {
     let mut paths: Vec<PathBuf> = Vec::new();
 
     let user_bg = ctx.config_dir.join("backgrounds").join(&theme.name);
@@ -43,9 +191,8 @@ fn collect_candidates(ctx: &Ctx, theme: &Theme) -> Vec<Candidate> {
         paths.extend(list_files(&user_bg));
     }
 
-    let theme_bg = ctx.current_link.join("backgrounds");
-    if theme_bg.is_dir() {
-        paths.extend(list_files(&theme_bg));
+    if let Some(theme_bg) = theme_bg.filter(|d| d.is_dir()) {
+        paths.extend(list_files(theme_bg));
     }
 
     paths.sort();
@@ -61,13 +208,13 @@ fn collect_candidates(ctx: &Ctx, theme: &Theme) -> Vec<Candidate> {
         .collect()
 }
 
-/// List all files directly inside `dir` (non-recursive).
+/// List the images directly inside `dir` (non-recursive).
 fn list_files(dir: &Path) -> Vec<PathBuf> {
     fs::read_dir(dir)
         .map(|rd| {
             rd.flatten()
                 .map(|e| e.path())
-                .filter(|p| p.is_file())
+                .filter(|p| p.is_file() && is_image(p).unwrap_or(false))
                 .collect()
         })
         .unwrap_or_default()
@@ -86,23 +233,78 @@ fn pick_next<'a>(candidates: &'a [Candidate], current: Option<&str>) -> &'a Path
     &candidates[idx].path
 }
 
+/// Select the candidate before `current`, wrapping around to the end.
+fn pick_prev<'a>(candidates: &'a [Candidate], current: Option<&str>) -> &'a PathBuf {
+    let len = candidates.len();
+    let idx = current
+        .and_then(|cur| {
+            candidates
+                .iter()
+                .position(|c| c.canonical.as_deref() == Some(cur))
+        })
+        .map_or(len - 1, |i| (i + len - 1) % len);
+
+    &candidates[idx].path
+}
+
 /// Change wallpaper using awww.
 fn change_wallpaper(path: &Path) {
-    Command::new("awww")
-        .args(["img", &path.to_string_lossy(), "--transition-type=none"])
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .ok();
+    wallpaper_cmd(path, None).spawn().ok();
+}
+
+/// `awww img`, optionally limited to a single output.
+fn argv(path: &Path, output: Option<&str>) -> Vec<String> {
+    let mut args = vec!["awww".to_owned(), "img".to_owned(), path.to_string_lossy().into_owned()];
+    args.push("--transition-type=none".to_owned());
+    if let Some(output) = output {
+        args.extend(["--outputs".to_owned(), output.to_owned()]);
+    }
+    args
+}
+
+fn wallpaper_cmd(path: &Path, output: Option<&str>) -> Command {
+    let args = argv(path, output);
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    cmd
 }
+
+fn wait(mut cmd: Command) {
+    if let Err(e) = cmd.status() {
+        log::warn!("awww: {e}");
+    }
+}
+
 // Notifications
 fn notify(msg: &str) {
-    Command::new("notify-send")
-        .args([msg, "-t", "2000"])
-        .stdin(Stdio::null())
+    let mut cmd = Command::new("notify-send");
+    cmd.args([msg, "-t", "2000"]);
+    log::command(&cmd);
+    cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
         .ok();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_heif_images_but_not_video() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = |name: &str, head: &[u8]| {
+            let path = tmp.path().join(name);
+            fs::write(&path, head).unwrap();
+            is_image(&path).unwrap()
+        };
+        assert!(file("a.avif", b"\0\0\0\x1cftypavif\0\0\0\0"));
+        assert!(file("b.heic", b"\0\0\0\x18ftypheic\0\0\0\0"));
+        assert!(!file("c.mp4", b"\0\0\0\x20ftypisom\0\0\x02\0"));
+        assert!(!file("d.mov", b"\0\0\0\x14ftypqt  \0\0\0\0"));
+        assert!(file("e.png", b"\x89PNG\r\n\x1a\n"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Waybar: reload its style with `SIGUSR2`, or restart it outright.
+//!
+//! `SIGUSR2` makes waybar reread `style.css`, but not every change takes
+//! (new modules, changed selectors), so restarting can be chosen instead.
+//! Either way, `[waybar] configs` narrows it to the instances started with
+//! one of those `-c` config files.
+
+use crate::{ctx::Ctx, log, session};
+use anyhow::{Context, Result, bail};
+use std::{
+    fs,
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a stopped instance gets to exit, and how long a restarted one
+/// must stay up to count as back.
+const EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// `[waybar] mode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Signal,
+    Restart,
+}
+
+impl Mode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "signal" => Some(Mode::Signal),
+            "restart" => Some(Mode::Restart),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub mode: Mode,
+    /// Only instances started with one of these config files; all if empty.
+    pub configs: Vec<PathBuf>,
+}
+
+/// A running waybar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    pub pid: u32,
+    /// Its command line, to start it again with.
+    pub argv: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl Instance {
+    fn read(pid: u32) -> Option<Self> {
+        let cmdline = fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+        let argv: Vec<String> = cmdline
+            .split(|&b| b == 0)
+            .filter(|a| !a.is_empty())
+            .map(|a| String::from_utf8_lossy(a).into_owned())
+            .collect();
+        (!argv.is_empty()).then(|| Self {
+            pid,
+            argv,
+            cwd: fs::read_link(format!("/proc/{pid}/cwd")).ok(),
+        })
+    }
+
+    /// The `-c` / `--config` file it was started with.
+    fn config(&self) -> Option<PathBuf> {
+        let mut args = self.argv.iter().skip(1);
+        while let Some(arg) = args.next() {
+            let path = match arg.as_str() {
+                "-c" | "--config" => args.next().map(String::as_str),
+                a => a.strip_prefix("--config=").or_else(|| a.strip_prefix("-c").filter(|p| !p.is_empty())),
+            };
+            if let Some(path) = path {
+                return Some(match &self.cwd {
+                    Some(cwd) => cwd.join(path),
+                    None => PathBuf::from(path),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// The waybar instances `settings` applies to, in this session when scoped.
+pub fn instances(ctx: &Ctx, settings: &Settings) -> Vec<Instance> {
+    let pids = match &ctx.session {
+        Some(display) => session::pids("waybar", display),
+        None => session::named("waybar"),
+    };
+    let same = |a: &Path, b: &Path| a == b || fs::canonicalize(a).is_ok_and(|a| fs::canonicalize(b).is_ok_and(|b| a == b));
+    pids.into_iter()
+        .filter_map(Instance::read)
+        .filter(|i| {
+            settings.configs.is_empty()
+                || i.config().is_some_and(|c| settings.configs.iter().any(|want| same(&c, want)))
+        })
+        .collect()
+}
+
+/// Stop `instance`, start it again with the same command line, and check
+/// that it stays up.
+pub fn restart(instance: &Instance) -> Result<()> {
+    let mut kill = Command::new("kill");
+    kill.args(["-TERM", &instance.pid.to_string()]);
+    log::command(&kill);
+    kill.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status().context("kill")?;
+
+    let proc_dir = PathBuf::from(format!("/proc/{}", instance.pid));
+    let deadline = Instant::now() + EXIT_TIMEOUT;
+    while proc_dir.exists() {
+        if Instant::now() >= deadline {
+            bail!("waybar ({}) did not exit", instance.pid);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let mut cmd = Command::new(&instance.argv[0]);
+    cmd.args(&instance.argv[1..]);
+    if let Some(cwd) = instance.cwd.as_deref().filter(|d| d.is_dir()) {
+        cmd.current_dir(cwd);
+    }
+    log::command(&cmd);
+    // Own process group, so it outlives a Ctrl-C in the terminal that ran us.
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .context("start waybar")?;
+    thread::sleep(SETTLE);
+    if let Some(status) = child.try_wait().context("wait for waybar")? {
+        bail!("waybar exited right after restarting ({status})");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_config_argument() {
+        let instance = |args: &[&str]| Instance {
+            pid: 1,
+            argv: std::iter::once("waybar").chain(args.iter().copied()).map(str::to_owned).collect(),
+            cwd: Some("/home/me".into()),
+        };
+        assert_eq!(instance(&["-c", "/etc/top.jsonc"]).config(), Some("/etc/top.jsonc".into()));
+        assert_eq!(instance(&["-l", "info", "--config=bar.jsonc"]).config(), Some("/home/me/bar.jsonc".into()));
+        assert_eq!(instance(&["-cbar.jsonc", "-s", "x.css"]).config(), Some("/home/me/bar.jsonc".into()));
+        assert_eq!(instance(&["-s", "style.css"]).config(), None);
+    }
+}
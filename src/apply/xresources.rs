@@ -0,0 +1,43 @@
+//! Merge the rendered X resources into XWayland, for the X11 apps that read
+//! their colors from the resource database.
+
+use crate::{ctx::Ctx, log};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The rendered resources, relative to the live tree.
+const RESOURCES_FILE: &str = "xresources/Xresources";
+
+/// The `xrdb` command line [`run`] would use. `None` without an X display
+/// (a pure Wayland session), or if `tree` (the live tree, or a stage) has no
+/// resources file.
+pub fn command(ctx: &Ctx, tree: &Path) -> Option<Vec<String>> {
+    if std::env::var_os("DISPLAY").is_none_or(|d| d.is_empty()) {
+        return None;
+    }
+    if !tree.join(RESOURCES_FILE).is_file() {
+        return None;
+    }
+    let file = ctx.current_link.join(RESOURCES_FILE);
+    Some(vec!["xrdb".to_owned(), "-merge".to_owned(), file.display().to_string()])
+}
+
+/// Merge the theme's resources; apps started afterwards use them.
+pub fn run(ctx: &Ctx) {
+    let Some(args) = command(ctx, &ctx.live_dir) else {
+        log::debug!("xresources: no X display or no resources file");
+        return;
+    };
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    log::command(&cmd);
+    match cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output() {
+        Ok(out) if !out.status.success() => {
+            log::warn!("xrdb: {}", String::from_utf8_lossy(&out.stderr).trim());
+        }
+        Err(e) => log::warn!("xrdb: {e}"),
+        Ok(_) => {}
+    }
+}
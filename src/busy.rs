@@ -0,0 +1,85 @@
+//! Detect processes holding files open inside the live tree.
+//!
+//! Some apps crash when their config file disappears mid-read, so `set` can
+//! optionally scan `/proc/*/fd` before the commit swaps `live/` away.
+
+use crate::log;
+use std::{
+    fs,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// What to do about open files before committing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    Ignore,
+    Warn,
+    /// Poll until no process holds a file open, up to the given timeout.
+    Wait(Duration),
+}
+
+/// A process with at least one file open under the scanned directory.
+#[derive(Debug, Clone)]
+pub struct Holder {
+    pub pid: u32,
+    pub comm: String,
+}
+
+const POLL: Duration = Duration::from_millis(100);
+
+/// Apply `policy` to `dir`. Never fails — this is advisory only.
+pub fn guard(dir: &Path, policy: Policy) {
+    let deadline = match policy {
+        Policy::Ignore => return,
+        Policy::Warn => Instant::now(),
+        Policy::Wait(timeout) => Instant::now() + timeout,
+    };
+
+    loop {
+        let holders = holders(dir);
+        if holders.is_empty() {
+            return;
+        }
+        if Instant::now() >= deadline {
+            for h in &holders {
+                log::warn!("{} (pid {}) has files open in {}", h.comm, h.pid, dir.display());
+            }
+            return;
+        }
+        log::debug!("waiting for {} process(es) to release {}", holders.len(), dir.display());
+        thread::sleep(POLL);
+    }
+}
+
+/// Scan `/proc` for processes with an fd pointing inside `dir`.
+///
+/// Processes we can't inspect (other users, races with exit) are skipped.
+pub fn holders(dir: &Path) -> Vec<Holder> {
+    let Ok(dir) = fs::canonicalize(dir) else {
+        return Vec::new();
+    };
+    let Ok(procs) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let me = std::process::id();
+
+    procs
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|&pid| pid != me)
+        .filter(|pid| {
+            fs::read_dir(format!("/proc/{pid}/fd")).is_ok_and(|fds| {
+                fds.flatten()
+                    .any(|fd| fs::read_link(fd.path()).is_ok_and(|target| target.starts_with(&dir)))
+            })
+        })
+        .map(|pid| Holder {
+            pid,
+            comm: fs::read_to_string(format!("/proc/{pid}/comm"))
+                .map(|s| s.trim().to_owned())
+                .unwrap_or_else(|_| "?".to_owned()),
+        })
+        .collect()
+}
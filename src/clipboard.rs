@@ -0,0 +1,31 @@
+//! Wayland clipboard access via `wl-copy`.
+
+use crate::log;
+use anyhow::{Context, Result};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Place `text` on the clipboard.
+pub fn copy(text: &str) -> Result<()> {
+    let mut cmd = Command::new("wl-copy");
+    log::command(&cmd);
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn wl-copy (is wl-clipboard installed?)")?;
+
+    child
+        .stdin
+        .take()
+        .context("wl-copy stdin")?
+        .write_all(text.as_bytes())
+        .context("write to wl-copy")?;
+
+    let status = child.wait().context("wait for wl-copy")?;
+    anyhow::ensure!(status.success(), "wl-copy exited with {status}");
+    Ok(())
+}
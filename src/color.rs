@@ -0,0 +1,376 @@
+//! Color parsing, formatting, and adjustment.
+
+/// An 8-bit sRGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self { r: 0, g: 0, b: 0 };
+    pub const WHITE: Self = Self { r: 255, g: 255, b: 255 };
+
+    /// Parse `#rrggbb`, `#rgb`, `#rrggbbaa` or `#rgba` (the leading `#` is
+    /// optional), ignoring any alpha channel.
+    pub fn parse(hex: &str) -> Option<Self> {
+        Self::parse_alpha(hex).map(|(c, _)| c)
+    }
+
+    /// Like [`Rgb::parse`], also returning the alpha channel if present.
+    pub fn parse_alpha(hex: &str) -> Option<(Self, Option<u8>)> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channels: Vec<u8> = match hex.len() {
+            3 | 4 => hex.bytes().map(|b| (b as char).to_digit(16).map(|d| d as u8 * 17)).collect::<Option<_>>()?,
+            6 | 8 => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        let color = Self { r: channels[0], g: channels[1], b: channels[2] };
+        Some((color, channels.get(3).copied()))
+    }
+
+    /// Format as lowercase `#rrggbb`.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Hue in degrees, saturation and lightness in percent.
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+
+        if d == 0.0 {
+            return (0.0, 0.0, l * 100.0);
+        }
+
+        let s = d / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+        (h, s * 100.0, l * 100.0)
+    }
+
+    /// Hue in degrees, saturation and value in percent.
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (h, _, _) = self.to_hsl();
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (h, s * 100.0, max * 100.0)
+    }
+
+    /// OKLab coordinates: perceptual lightness 0–1 and the two opponent axes.
+    pub fn to_oklab(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(srgb_to_linear);
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        (
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        )
+    }
+
+    /// Inverse of [`Rgb::to_oklab`], clamping out-of-gamut channels.
+    pub fn from_oklab(l: f64, a: f64, b: f64) -> Self {
+        let [r, g, b] = oklab_to_linear(l, a, b).map(linear_to_srgb);
+        Self { r, g, b }
+    }
+
+    /// OKLCH: lightness in percent, chroma (0 to about 0.37), hue in degrees.
+    pub fn to_oklch(self) -> (f64, f64, f64) {
+        let (l, a, b) = self.to_oklab();
+        let c = a.hypot(b);
+        let h = if c < 1e-6 { 0.0 } else { b.atan2(a).to_degrees().rem_euclid(360.0) };
+        (l * 100.0, c, h)
+    }
+
+    /// Inverse of [`Rgb::to_oklch`]. Colors outside sRGB keep their
+    /// lightness and hue and lose just enough chroma to fit.
+    pub fn from_oklch(l: f64, c: f64, h: f64) -> Self {
+        let l = (l / 100.0).clamp(0.0, 1.0);
+        let (sin, cos) = h.to_radians().sin_cos();
+        let at = |c: f64| oklab_to_linear(l, c * cos, c * sin);
+        let in_gamut = |rgb: [f64; 3]| rgb.iter().all(|v| (-1e-4..=1.0 + 1e-4).contains(v));
+
+        let mut c = c.max(0.0);
+        if !in_gamut(at(c)) {
+            let (mut lo, mut hi) = (0.0, c);
+            for _ in 0..24 {
+                let mid = (lo + hi) / 2.0;
+                if in_gamut(at(mid)) { lo = mid } else { hi = mid }
+            }
+            c = lo;
+        }
+        let [r, g, b] = at(c).map(linear_to_srgb);
+        Self { r, g, b }
+    }
+
+    /// Raise OKLCH lightness by `amount` percentage points.
+    pub fn lighten(self, amount: f64) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::from_oklch(l + amount, c, h)
+    }
+
+    /// Lower OKLCH lightness by `amount` percentage points.
+    pub fn darken(self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Raise OKLCH chroma by `amount` percent of the sRGB maximum (0.4).
+    pub fn saturate(self, amount: f64) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::from_oklch(l, c + amount * 0.004, h)
+    }
+
+    /// Lower OKLCH chroma by `amount` percent of the sRGB maximum.
+    pub fn desaturate(self, amount: f64) -> Self {
+        self.saturate(-amount)
+    }
+
+    /// Blend towards `other` in OKLab: `ratio` 0 keeps `self`, 1 yields
+    /// `other`.
+    pub fn mix(self, other: Self, ratio: f64) -> Self {
+        let t = ratio.clamp(0.0, 1.0);
+        if t == 0.0 || t == 1.0 {
+            return if t == 0.0 { self } else { other };
+        }
+        let (a, b) = (self.to_oklab(), other.to_oklab());
+        let lerp = |x: f64, y: f64| x + (y - x) * t;
+        Self::from_oklab(lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+    }
+
+    /// Rotate the OKLCH hue by `degrees` (either direction, wraps around).
+    pub fn rotate_hue(self, degrees: f64) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::from_oklch(l, c, h + degrees)
+    }
+
+    /// Closest xterm-256 index among the 6×6×6 cube and the gray ramp
+    /// (16–255). The first 16 are skipped: terminals let users redefine them.
+    pub fn to_ansi256(self) -> u8 {
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        let dist = |c: Self| {
+            let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            d(self.r, c.r) + d(self.g, c.g) + d(self.b, c.b)
+        };
+        let nearest = |v: u8| {
+            (0..6).min_by_key(|&i| (i32::from(LEVELS[i]) - i32::from(v)).abs()).unwrap_or(0)
+        };
+
+        let (ri, gi, bi) = (nearest(self.r), nearest(self.g), nearest(self.b));
+        let cube = Self { r: LEVELS[ri], g: LEVELS[gi], b: LEVELS[bi] };
+        let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+        let avg = (u32::from(self.r) + u32::from(self.g) + u32::from(self.b)) / 3;
+        let gi = (avg.saturating_sub(3) / 10).min(23) as u8;
+        let v = 8 + 10 * gi;
+        let gray = Self { r: v, g: v, b: v };
+
+        if dist(gray) < dist(cube) { 232 + gi } else { cube_index as u8 }
+    }
+
+    /// WCAG relative luminance, 0 (black) to 1 (white).
+    pub fn luminance(self) -> f64 {
+        0.2126 * srgb_to_linear(self.r) + 0.7152 * srgb_to_linear(self.g) + 0.0722 * srgb_to_linear(self.b)
+    }
+
+    /// Perceived brightness (ITU-R BT.601 weights on gamma-encoded values),
+    /// 0 to 1.
+    pub fn brightness(self) -> f64 {
+        (0.299 * f64::from(self.r) + 0.587 * f64::from(self.g) + 0.114 * f64::from(self.b)) / 255.0
+    }
+
+    /// Euclidean distance in OKLab; about 0.02 is the smallest difference
+    /// most people notice.
+    pub fn distance(self, other: Self) -> f64 {
+        let (a, b) = (self.to_oklab(), other.to_oklab());
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+    }
+
+    /// How this color looks with full dichromacy of `kind` (Machado et al.,
+    /// 2009, applied in linear sRGB).
+    pub fn simulate(self, kind: Cvd) -> Self {
+        let m = match kind {
+            Cvd::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Cvd::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Cvd::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+        let lin = [self.r, self.g, self.b].map(srgb_to_linear);
+        let [r, g, b] = m.map(|row| linear_to_srgb(row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]));
+        Self { r, g, b }
+    }
+
+    /// WCAG contrast ratio against `other`, 1 to 21.
+    pub fn contrast(self, other: Self) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+/// A kind of color vision deficiency (dichromacy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Cvd {
+    /// No long-wavelength (red) cones
+    Protanopia,
+    /// No medium-wavelength (green) cones
+    Deuteranopia,
+    /// No short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl Cvd {
+    pub const ALL: [Cvd; 3] = [Cvd::Protanopia, Cvd::Deuteranopia, Cvd::Tritanopia];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cvd::Protanopia => "protanopia",
+            Cvd::Deuteranopia => "deuteranopia",
+            Cvd::Tritanopia => "tritanopia",
+        }
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f64 {
+    let c = f64::from(c) / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let v = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (v * 255.0).round() as u8
+}
+
+/// OKLab to linear sRGB, unclamped.
+fn oklab_to_linear(l: f64, a: f64, b: f64) -> [f64; 3] {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(Rgb::parse("#1E1E2E").unwrap().to_hex(), "#1e1e2e");
+        assert_eq!(Rgb::parse("1e1e2e"), Rgb::parse("#1e1e2e"));
+        assert!(Rgb::parse("#12345").is_none());
+        assert_eq!(Rgb::parse_alpha("#f80"), Some((Rgb { r: 255, g: 136, b: 0 }, None)));
+        assert_eq!(Rgb::parse_alpha("#1e1e2ecc"), Some((Rgb { r: 30, g: 30, b: 46 }, Some(204))));
+        assert_eq!(Rgb::parse_alpha("#f80c").unwrap().1, Some(204));
+    }
+
+    #[test]
+    fn lighten_and_darken_clamp() {
+        let grey = Rgb::parse("#808080").unwrap();
+        assert_eq!(grey.lighten(100.0).to_hex(), "#ffffff");
+        assert_eq!(grey.darken(100.0).to_hex(), "#000000");
+    }
+
+    #[test]
+    fn oklch_round_trip() {
+        for hex in ["#1e1e2e", "#89b4fa", "#f38ba8", "#000000", "#ffffff", "#ff0000"] {
+            let c = Rgb::parse(hex).unwrap();
+            let (l, ch, h) = c.to_oklch();
+            assert_eq!(Rgb::from_oklch(l, ch, h), c, "{hex}");
+        }
+        let (l, _, _) = Rgb::WHITE.to_oklch();
+        assert!((l - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn mix_endpoints_and_midpoint() {
+        let black = Rgb::parse("#000000").unwrap();
+        let white = Rgb::parse("#ffffff").unwrap();
+        assert_eq!(black.mix(white, 0.0), black);
+        assert_eq!(black.mix(white, 1.0), white);
+        // Perceptual midpoint, darker than the sRGB average #808080.
+        assert_eq!(black.mix(white, 0.5).to_hex(), "#636363");
+    }
+
+    #[test]
+    fn hue_rotation_keeps_lightness() {
+        let red = Rgb::parse("#ff0000").unwrap();
+        let (l, _, h) = red.to_oklch();
+        for deg in [120.0, -120.0, 360.0] {
+            let (l2, _, h2) = red.rotate_hue(deg).to_oklch();
+            assert!((l2 - l).abs() < 1.0, "{deg}: {l2} vs {l}");
+            let want = (h + deg).rem_euclid(360.0);
+            assert!((h2 - want).abs() < 2.0 || deg == 360.0, "{deg}: {h2} vs {want}");
+        }
+        let gray = red.desaturate(100.0);
+        assert!(gray.r == gray.g && gray.g == gray.b, "{}", gray.to_hex());
+    }
+
+    #[test]
+    fn ansi256_nearest() {
+        let idx = |hex| Rgb::parse(hex).unwrap().to_ansi256();
+        assert_eq!(idx("#000000"), 16);
+        assert_eq!(idx("#ff0000"), 196);
+        assert_eq!(idx("#ffffff"), 231);
+        assert_eq!(idx("#808080"), 244);
+        assert_eq!(idx("#1e1e2e"), 235);
+    }
+
+    #[test]
+    fn cvd_simulation_merges_red_and_green() {
+        let red = Rgb::parse("#e06c75").unwrap();
+        let green = Rgb::parse("#98c379").unwrap();
+        let blue = Rgb::parse("#61afef").unwrap();
+        // Difference along OKLab's red-green axis.
+        let red_green = |a: Rgb, b: Rgb| (a.to_oklab().1 - b.to_oklab().1).abs();
+        assert!(red_green(red, green) > 0.15);
+        for kind in [Cvd::Protanopia, Cvd::Deuteranopia] {
+            let (r, g) = (red.simulate(kind), green.simulate(kind));
+            assert!(red_green(r, g) < 0.05, "{kind:?}: {} {}", r.to_hex(), g.to_hex());
+        }
+        // Grays are unaffected, blue-yellow confusion leaves red/green apart.
+        assert_eq!(Rgb::WHITE.simulate(Cvd::Tritanopia), Rgb::WHITE);
+        assert!(red.simulate(Cvd::Tritanopia).distance(green.simulate(Cvd::Tritanopia)) > 0.1);
+        assert!(blue.simulate(Cvd::Tritanopia).distance(blue) > 0.05);
+    }
+
+    #[test]
+    fn contrast_extremes() {
+        let black = Rgb::parse("#000000").unwrap();
+        let white = Rgb::parse("#ffffff").unwrap();
+        assert!((black.contrast(white) - 21.0).abs() < 1e-9);
+        assert!((white.contrast(white) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,59 @@
+//! `oxidize completions` — print or install shell completion scripts.
+
+use anyhow::{Context, Result, bail};
+use clap_complete::Shell;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Print the script for `shell` (detected from `$SHELL` when omitted), or
+/// write it into the shell's per-user completion directory.
+pub fn run(mut cmd: clap::Command, shell: Option<Shell>, install: bool) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => Shell::from_env().context("cannot detect the shell from $SHELL; pass it explicitly")?,
+    };
+    let name = cmd.get_name().to_owned();
+
+    if !install {
+        clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let path = install_path(shell, &name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, name, &mut script);
+    fs::write(&path, script).with_context(|| format!("write {}", path.display()))?;
+    println!("installed {shell} completions to {}", path.display());
+
+    if shell == Shell::Zsh {
+        let dir = path.parent().unwrap_or(&path);
+        println!("make sure {} is on $fpath before compinit:", dir.display());
+        println!("  fpath=({} $fpath)", dir.display());
+    }
+    Ok(())
+}
+
+/// Per-user completion file for `shell`.
+fn install_path(shell: Shell, name: &str) -> Result<PathBuf> {
+    let home = PathBuf::from(env::var("HOME").context("$HOME is not set")?);
+    let xdg = |var: &str, fallback: &str| {
+        env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .map_or_else(|| home.join(fallback), PathBuf::from)
+    };
+
+    Ok(match shell {
+        Shell::Fish => xdg("XDG_CONFIG_HOME", ".config").join("fish/completions").join(format!("{name}.fish")),
+        Shell::Bash => xdg("XDG_DATA_HOME", ".local/share").join("bash-completion/completions").join(name),
+        Shell::Zsh => {
+            let zdotdir = env::var_os("ZDOTDIR").filter(|v| !v.is_empty()).map(PathBuf::from);
+            zdotdir.as_deref().unwrap_or(Path::new(&home)).join(".zfunc").join(format!("_{name}"))
+        }
+        other => bail!("--install is not supported for {other}; redirect the printed script instead"),
+    })
+}
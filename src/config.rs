@@ -0,0 +1,358 @@
+//! User configuration from `~/.config/oxidize/oxidize.toml`.
+//!
+//! The file is optional; a missing file yields [`Config::default`].
+
+use crate::{
+    contrast::{self, Level},
+    cvd,
+    theme::Variant,
+    util,
+};
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// Named apply presets: `[preset.<name>] skip = [...]`.
+    pub presets: HashMap<String, Preset>,
+    /// Per-monitor theme overrides: `[output.<name>] theme = "..."`.
+    pub outputs: BTreeMap<String, Output>,
+    /// Environment variables templates may read as `env.NAME`:
+    /// `[env] allow = [...]`.
+    pub env_allow: Vec<String>,
+    /// Extra variables computed for every theme: `[derive] key = "filter(...)"`.
+    pub derive: BTreeMap<String, String>,
+    /// Foreground/background pairs to check: `[contrast] pairs`, `level`.
+    pub contrast: contrast::Rules,
+    /// Pairs that must stay distinguishable with color vision deficiencies:
+    /// `[cvd] pairs`, `min_distance`.
+    pub cvd: cvd::Rules,
+    /// Canonical case of hex color values: `[colors] hex_case = "upper"`.
+    /// `None` leaves values as the theme wrote them.
+    pub upper_hex: Option<bool>,
+    /// Times of day to switch variant-capable themes to light and to dark:
+    /// `[variant] light = "07:00"`, `dark = "19:00"`.
+    pub variant_schedule: Option<(jiff::civil::Time, jiff::civil::Time)>,
+    /// Stable names for themes: `[alias] work = "catppuccin-latte"`.
+    pub aliases: BTreeMap<String, String>,
+    /// Default semantic roles, which a theme's own `[role]` overrides:
+    /// `[role] surface = "bg"`.
+    pub roles: BTreeMap<String, String>,
+    /// How many generations of the live tree to keep for rollback:
+    /// `[generations] keep = 5`. `None` means [`crate::generation::DEFAULT_KEEP`].
+    pub keep_generations: Option<usize>,
+    /// Skip flushing the rendered tree to disk before committing it:
+    /// `[commit] fsync = false`.
+    pub no_fsync: bool,
+    /// How long each hook script may run: `[hooks] timeout = 10` (seconds).
+    pub hook_timeout: Option<std::time::Duration>,
+    /// How to update a running compositor: `[hyprland] reload = "full"`,
+    /// `[sway] reload = "client"`.
+    pub compositor: crate::apply::compositor::Settings,
+    /// How to reload waybar: `[waybar] mode = "restart"`, `configs = [...]`.
+    pub waybar: crate::apply::waybar::Settings,
+    /// Ex command sent to open Neovim instances: `[neovim] command`.
+    pub neovim_command: Option<String>,
+    /// What to evaluate in a running Emacs: `[emacs] generated = true`,
+    /// `light`/`dark` theme names, or `eval`. `None` leaves Emacs alone.
+    pub emacs: Option<crate::apply::emacs::Source>,
+    /// `[vscode]`: which keys to patch in VS Code's `settings.json`.
+    pub vscode: crate::apply::vscode::Settings,
+    /// `[firefox] enable/pref`: copy the stylesheets into Firefox profiles.
+    pub firefox: crate::apply::firefox::Settings,
+    /// `[kde] light/dark`: global themes to apply on Plasma.
+    pub kde: crate::apply::kde::Settings,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Preset {
+    /// Apply steps to skip, by the names [`crate::apply::ApplyFlags::skip`]
+    /// accepts.
+    pub skip: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Output {
+    pub theme: String,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let src = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+        };
+        let root: toml::Value = toml::from_str(&src).with_context(|| format!("parse {}", path.display()))?;
+
+        let mut config = Self::default();
+
+        if let Some(presets) = root.get("preset") {
+            let Some(presets) = presets.as_table() else {
+                bail!("[preset] must be a table");
+            };
+            for (name, body) in presets {
+                let skip = string_list(body.get("skip")).with_context(|| format!("preset.{name}.skip"))?;
+                config.presets.insert(name.clone(), Preset { skip });
+            }
+        }
+
+        if let Some(outputs) = root.get("output") {
+            let Some(outputs) = outputs.as_table() else {
+                bail!("[output] must be a table");
+            };
+            for (name, body) in outputs {
+                let theme = body
+                    .get("theme")
+                    .and_then(|v| v.as_str())
+                    .with_context(|| format!("output.{name}.theme must be a string"))?;
+                config.outputs.insert(name.clone(), Output { theme: theme.to_owned() });
+            }
+        }
+
+        if let Some(env) = root.get("env") {
+            config.env_allow = string_list(env.get("allow")).context("env.allow")?;
+        }
+
+        if let Some(derive) = root.get("derive") {
+            let Some(derive) = derive.as_table() else {
+                bail!("[derive] must be a table");
+            };
+            for (key, rule) in derive {
+                let rule = rule.as_str().with_context(|| format!("derive.{key} must be a string"))?;
+                config.derive.insert(key.clone(), rule.to_owned());
+            }
+        }
+
+        if let Some(table) = root.get("contrast") {
+            if let Some(pairs) = table.get("pairs") {
+                let Some(pairs) = pairs.as_array() else {
+                    bail!("contrast.pairs must be an array of [fg, bg] pairs");
+                };
+                config.contrast.pairs = pairs
+                    .iter()
+                    .map(|pair| match string_list(Some(pair))?.as_slice() {
+                        [fg, bg] => Ok((fg.clone(), bg.clone())),
+                        _ => bail!("expected a [fg, bg] pair"),
+                    })
+                    .collect::<Result<_>>()
+                    .context("contrast.pairs")?;
+            }
+            if let Some(level) = table.get("level") {
+                let level = level.as_str().context("contrast.level must be a string")?;
+                config.contrast.level = Level::parse(level).context("contrast.level")?;
+            }
+        }
+
+        if let Some(table) = root.get("cvd") {
+            if let Some(pairs) = table.get("pairs") {
+                config.cvd.pairs = pairs
+                    .as_array()
+                    .context("cvd.pairs must be an array of [a, b] pairs")?
+                    .iter()
+                    .map(|pair| match string_list(Some(pair))?.as_slice() {
+                        [a, b] => Ok((a.clone(), b.clone())),
+                        _ => bail!("expected an [a, b] pair"),
+                    })
+                    .collect::<Result<_>>()
+                    .context("cvd.pairs")?;
+            }
+            if let Some(min) = table.get("min_distance") {
+                config.cvd.min_distance = min
+                    .as_float()
+                    .or_else(|| min.as_integer().map(|i| i as f64))
+                    .context("cvd.min_distance must be a number")?;
+            }
+        }
+
+        if let Some(case) = root.get("colors").and_then(|c| c.get("hex_case")) {
+            config.upper_hex = match case.as_str() {
+                Some("upper") => Some(true),
+                Some("lower") => Some(false),
+                _ => bail!("colors.hex_case must be \"upper\" or \"lower\""),
+            };
+        }
+
+        if let Some(table) = root.get("variant") {
+            let time = |key: &str| -> Result<jiff::civil::Time> {
+                let value = table
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .with_context(|| format!("variant.{key} must be a time like \"07:00\""))?;
+                value.parse().with_context(|| format!("variant.{key} = '{value}'"))
+            };
+            config.variant_schedule = Some((time("light")?, time("dark")?));
+        }
+
+        if let Some(roles) = root.get("role") {
+            let Some(roles) = roles.as_table() else {
+                bail!("[role] must be a table");
+            };
+            for (role, source) in roles {
+                let source = source.as_str().with_context(|| format!("role.{role} must be a variable name"))?;
+                config.roles.insert(role.clone(), source.to_owned());
+            }
+        }
+
+        if let Some(keep) = root.get("generations").and_then(|g| g.get("keep")) {
+            let keep = keep.as_integer().filter(|k| *k >= 1).context("generations.keep must be a positive integer")?;
+            config.keep_generations = Some(keep as usize);
+        }
+
+        if let Some(fsync) = root.get("commit").and_then(|c| c.get("fsync")) {
+            config.no_fsync = !fsync.as_bool().context("commit.fsync must be true or false")?;
+        }
+
+        if let Some(timeout) = root.get("hooks").and_then(|h| h.get("timeout")) {
+            let secs = timeout
+                .as_float()
+                .or_else(|| timeout.as_integer().map(|i| i as f64))
+                .filter(|s| *s > 0.0)
+                .context("hooks.timeout must be a positive number of seconds")?;
+            config.hook_timeout = Some(std::time::Duration::from_secs_f64(secs));
+        }
+
+        if let Some(reload) = root.get("hyprland").and_then(|h| h.get("reload")) {
+            config.compositor.hyprland = reload
+                .as_str()
+                .and_then(crate::apply::hyprland::Reload::parse)
+                .context("hyprland.reload must be \"keyword\" or \"full\"")?;
+        }
+
+        if let Some(reload) = root.get("sway").and_then(|s| s.get("reload")) {
+            config.compositor.sway = reload
+                .as_str()
+                .and_then(crate::apply::sway::Reload::parse)
+                .context("sway.reload must be \"full\" or \"client\"")?;
+        }
+
+        if let Some(waybar) = root.get("waybar") {
+            if let Some(mode) = waybar.get("mode") {
+                config.waybar.mode = mode
+                    .as_str()
+                    .and_then(crate::apply::waybar::Mode::parse)
+                    .context("waybar.mode must be \"signal\" or \"restart\"")?;
+            }
+            if let Some(configs) = waybar.get("configs") {
+                let configs = configs.as_array().context("waybar.configs must be a list of paths")?;
+                for path in configs {
+                    let path = path.as_str().context("waybar.configs must be a list of paths")?;
+                    config.waybar.configs.push(util::expand_home(path));
+                }
+            }
+        }
+
+        if let Some(command) = root.get("neovim").and_then(|n| n.get("command")) {
+            let command = command.as_str().context("neovim.command must be a string")?;
+            config.neovim_command = Some(command.to_owned());
+        }
+
+        if let Some(emacs) = root.get("emacs") {
+            use crate::apply::emacs::Source;
+            let string = |key: &str| -> Result<Option<String>> {
+                let value = emacs.get(key).map(|v| v.as_str().with_context(|| format!("emacs.{key} must be a string")));
+                Ok(value.transpose()?.map(str::to_owned))
+            };
+            config.emacs = match (string("eval")?, string("light")?, string("dark")?) {
+                (Some(expr), _, _) => Some(Source::Eval(expr)),
+                (None, Some(light), Some(dark)) => Some(Source::Themes { light, dark }),
+                (None, Some(_), None) | (None, None, Some(_)) => bail!("emacs.light and emacs.dark must be set together"),
+                (None, None, None) => {
+                    let generated = emacs.get("generated").map(|g| g.as_bool().context("emacs.generated must be true or false"));
+                    generated.transpose()?.unwrap_or(false).then_some(Source::Generated)
+                }
+            };
+        }
+
+        if let Some(vscode) = root.get("vscode") {
+            for (key, slot) in [("light", &mut config.vscode.light), ("dark", &mut config.vscode.dark)] {
+                if let Some(name) = vscode.get(key) {
+                    *slot = Some(name.as_str().with_context(|| format!("vscode.{key} must be a theme name"))?.to_owned());
+                }
+            }
+            if let Some(customizations) = vscode.get("customizations") {
+                config.vscode.customizations =
+                    customizations.as_bool().context("vscode.customizations must be true or false")?;
+            }
+            if let Some(files) = vscode.get("settings") {
+                let files = files.as_array().context("vscode.settings must be a list of paths")?;
+                for path in files {
+                    let path = path.as_str().context("vscode.settings must be a list of paths")?;
+                    config.vscode.files.push(util::expand_home(path));
+                }
+            }
+        }
+
+        if let Some(firefox) = root.get("firefox") {
+            for (key, slot) in [("enable", &mut config.firefox.enable), ("pref", &mut config.firefox.pref)] {
+                if let Some(value) = firefox.get(key) {
+                    *slot = value.as_bool().with_context(|| format!("firefox.{key} must be true or false"))?;
+                }
+            }
+        }
+
+        if let Some(kde) = root.get("kde") {
+            for (key, slot) in [("light", &mut config.kde.light), ("dark", &mut config.kde.dark)] {
+                if let Some(package) = kde.get(key) {
+                    *slot = Some(package.as_str().with_context(|| format!("kde.{key} must be a package name"))?.to_owned());
+                }
+            }
+        }
+
+        if let Some(aliases) = root.get("alias") {
+            let Some(aliases) = aliases.as_table() else {
+                bail!("[alias] must be a table");
+            };
+            for (alias, theme) in aliases {
+                let theme = theme.as_str().with_context(|| format!("alias.{alias} must be a theme name"))?;
+                config.aliases.insert(alias.clone(), theme.to_owned());
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The Ex command that recolors open Neovim instances.
+    pub fn neovim_command(&self) -> &str {
+        self.neovim_command.as_deref().unwrap_or(crate::apply::neovim::DEFAULT_COMMAND)
+    }
+
+    /// The theme `name` stands for: an alias's target, or `name` itself.
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
+    }
+
+    /// Aliases that point at `theme`.
+    pub fn aliases_of(&self, theme: &str) -> Vec<&str> {
+        self.aliases.iter().filter(|(_, t)| *t == theme).map(|(a, _)| a.as_str()).collect()
+    }
+
+    /// The variant the schedule calls for at `now`, if there is a schedule.
+    pub fn scheduled_variant(&self, now: jiff::civil::Time) -> Option<Variant> {
+        let (light, dark) = self.variant_schedule?;
+        let is_light = if light <= dark {
+            light <= now && now < dark
+        } else {
+            now >= light || now < dark
+        };
+        Some(if is_light { Variant::Light } else { Variant::Dark })
+    }
+}
+
+/// Read an optional array of strings.
+fn string_list(value: Option<&toml::Value>) -> Result<Vec<String>> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+    let Some(items) = value.as_array() else {
+        bail!("expected an array of strings");
+    };
+    items
+        .iter()
+        .map(|v| v.as_str().map(str::to_owned).context("expected a string"))
+        .collect()
+}
@@ -0,0 +1,111 @@
+//! WCAG contrast checks between foreground/background pairs of a theme.
+//!
+//! ```toml
+//! [contrast]
+//! pairs = [["fg", "bg"], ["accent", "bg"]]
+//! level = "AAA"
+//! ```
+
+use crate::{color::Rgb, render::engine::resolve_key};
+use anyhow::{Result, bail};
+use std::{collections::HashMap, fmt};
+
+/// A WCAG conformance level for normal text.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Level {
+    /// 3:1, the AA threshold for large text.
+    AaLarge,
+    /// 4.5:1
+    #[default]
+    Aa,
+    /// 7:1
+    Aaa,
+}
+
+impl Level {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "AA-LARGE" => Self::AaLarge,
+            "AA" => Self::Aa,
+            "AAA" => Self::Aaa,
+            _ => bail!("unknown contrast level '{s}' (expected AA-large, AA or AAA)"),
+        })
+    }
+
+    pub fn min_ratio(self) -> f64 {
+        match self {
+            Self::AaLarge => 3.0,
+            Self::Aa => 4.5,
+            Self::Aaa => 7.0,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AaLarge => "AA-large",
+            Self::Aa => "AA",
+            Self::Aaa => "AAA",
+        })
+    }
+}
+
+/// `[contrast]` from `oxidize.toml`.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub pairs: Vec<(String, String)>,
+    pub level: Level,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            pairs: vec![("fg".to_owned(), "bg".to_owned())],
+            level: Level::Aa,
+        }
+    }
+}
+
+/// Describe every pair whose contrast is below `rules.level`. Pairs with a
+/// key the theme doesn't define, or that isn't a color, are skipped.
+pub fn check(rules: &Rules, vars: &HashMap<String, String>) -> Vec<String> {
+    let color = |name: &str| resolve_key(vars, name).and_then(|(_, v)| Rgb::parse(v));
+    rules
+        .pairs
+        .iter()
+        .filter_map(|(fg, bg)| {
+            let ratio = color(fg)?.contrast(color(bg)?);
+            (ratio < rules.level.min_ratio())
+                .then(|| format!("contrast {fg}/{bg} is {ratio:.2}:1, below {} ({}:1)", rules.level, rules.level.min_ratio()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_pairs_below_level() {
+        let vars = HashMap::from([
+            ("palette_bg".to_owned(), "#1e1e2e".to_owned()),
+            ("palette_fg".to_owned(), "#cdd6f4".to_owned()),
+            ("palette_accent".to_owned(), "#45475a".to_owned()),
+        ]);
+        let mut rules = Rules {
+            pairs: vec![
+                ("fg".into(), "bg".into()),
+                ("accent".into(), "bg".into()),
+                ("missing".into(), "bg".into()),
+            ],
+            level: Level::Aa,
+        };
+        let found = check(&rules, &vars);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].starts_with("contrast accent/bg is 1."), "{found:?}");
+
+        rules.level = Level::Aaa;
+        assert_eq!(check(&rules, &vars).len(), 1);
+    }
+}
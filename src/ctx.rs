@@ -1,3 +1,4 @@
+use crate::session;
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
@@ -5,15 +6,45 @@ use std::path::PathBuf;
 /// Constructed once at startup; never mutated after that.
 #[derive(Clone, Debug)]
 pub struct Ctx {
+    /// `$HOME`, for apps that keep their files there rather than under XDG dirs.
+    pub home_dir: PathBuf,
     pub config_dir: PathBuf,
+    pub config_file: PathBuf,
+    /// Variables every theme must define (see [`crate::schema`]).
+    pub schema_file: PathBuf,
+    /// The user's themes; new themes are created here.
     pub data_dir: PathBuf,
-    pub templates_dir: PathBuf,
+    /// Where themes are looked up, highest precedence first: `data_dir`,
+    /// then `$XDG_DATA_HOME/oxidize/themes`, then each
+    /// `$XDG_DATA_DIRS/oxidize/themes`.
+    pub theme_dirs: Vec<PathBuf>,
+    /// Shared template dirs, highest precedence first: `themes/templates`,
+    /// then `oxidize/templates` under the same data dirs as `theme_dirs`.
+    pub templates_dirs: Vec<PathBuf>,
     pub user_templates_dir: PathBuf,
+    pub overlays_dir: PathBuf,
+    /// `hooks/{pre,post}-set.d/` (see [`crate::apply::hooks`]).
+    pub hooks_dir: PathBuf,
+    /// `hosts/<hostname>/` (see [`crate::host`]).
+    pub host_dir: PathBuf,
     pub generated_dir: PathBuf,
     pub live_dir: PathBuf,
     pub current_link: PathBuf,
     pub current_theme_file: PathBuf,
+    /// GNOME settings from before oxidize first changed them.
+    pub gsettings_baseline: PathBuf,
+    /// Every switch, one line each (see [`crate::journal`]).
+    pub journal_file: PathBuf,
+    /// Wallpaper link for this session (see [`crate::session`]).
     pub background_link: PathBuf,
+    /// `themes/background`, following whichever session switched last.
+    pub shared_background_link: PathBuf,
+    /// `$WAYLAND_DISPLAY`, when set.
+    pub session: Option<String>,
+    pub lock_file: PathBuf,
+    pub pending_file: PathBuf,
+    /// Previous versions of files written outside the live tree.
+    pub backups_dir: PathBuf,
 }
 
 impl Ctx {
@@ -26,20 +57,72 @@ impl Ctx {
             .filter(|s| !s.is_empty())
             .unwrap_or_else(|| format!("{home}/.config"));
 
-        let config_dir = PathBuf::from(xdg).join("oxidize");
+        let data_home = std::env::var("XDG_DATA_HOME")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("{home}/.local/share"));
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_owned());
+        let shared: Vec<PathBuf> = std::iter::once(data_home.as_str())
+            .chain(data_dirs.split(':').filter(|d| !d.is_empty()))
+            .map(|d| PathBuf::from(d).join("oxidize"))
+            .collect();
+
+        Ok(Self::build(
+            PathBuf::from(&home),
+            PathBuf::from(xdg).join("oxidize"),
+            &shared,
+            session::current(),
+            &crate::meta::hostname(),
+        ))
+    }
+
+    /// Everything under `root`, with no session and a fixed hostname, so
+    /// tests never touch (or depend on) the real config.
+    #[cfg(test)]
+    pub fn under(root: &std::path::Path) -> Self {
+        Self::build(root.to_path_buf(), root.join("config/oxidize"), &[root.join("share/oxidize")], None, "test")
+    }
+
+    /// Lay out the paths from the home dir, the config dir and the shared
+    /// data dirs, each an `oxidize` dir under an XDG data dir.
+    fn build(home_dir: PathBuf, config_dir: PathBuf, shared: &[PathBuf], session: Option<String>, hostname: &str) -> Self {
         let themes = config_dir.join("themes");
         let generated_dir = themes.join("generated");
+        let data_dir = themes.join("data");
+        let templates_dir = themes.join("templates");
 
-        Ok(Self {
-            data_dir: themes.join("data"),
-            templates_dir: themes.join("templates"),
+        Self {
+            theme_dirs: std::iter::once(data_dir.clone()).chain(shared.iter().map(|d| d.join("themes"))).collect(),
+            templates_dirs: std::iter::once(templates_dir.clone())
+                .chain(shared.iter().map(|d| d.join("templates")))
+                .collect(),
+            data_dir,
             user_templates_dir: themes.join("user-templates"),
             live_dir: generated_dir.join("live"),
             current_link: themes.join("current"),
             current_theme_file: themes.join("current.theme"),
-            background_link: themes.join("background"),
+            journal_file: themes.join("journal.tsv"),
+            gsettings_baseline: themes.join("gsettings.orig"),
+            background_link: match &session {
+                Some(display) => themes.join("sessions").join(display).join("background"),
+                None => themes.join("background"),
+            },
+            shared_background_link: themes.join("background"),
+            session,
+            lock_file: generated_dir.join(".lock"),
+            pending_file: generated_dir.join(".pending"),
+            backups_dir: generated_dir.join("backups"),
+            overlays_dir: config_dir.join("overlays"),
+            hooks_dir: config_dir.join("hooks"),
+            host_dir: config_dir.join("hosts").join(hostname),
+            config_file: config_dir.join("oxidize.toml"),
+            schema_file: config_dir.join("schema.toml"),
             generated_dir,
+            home_dir,
             config_dir,
-        })
+        }
     }
 }
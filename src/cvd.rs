@@ -0,0 +1,82 @@
+//! Color vision deficiency checks: pairs of colors that must stay
+//! distinguishable under protanopia, deuteranopia and tritanopia.
+//!
+//! ```toml
+//! [cvd]
+//! pairs = [["red", "green"], ["error", "warning"]]
+//! min_distance = 0.04
+//! ```
+
+use crate::{
+    color::{Cvd, Rgb},
+    render::engine::resolve_key,
+};
+use std::collections::HashMap;
+
+/// `[cvd]` from `oxidize.toml`.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub pairs: Vec<(String, String)>,
+    /// Smallest OKLab distance that still counts as distinguishable.
+    pub min_distance: f64,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self { pairs: Vec::new(), min_distance: 0.04 }
+    }
+}
+
+/// Describe every pair that becomes too similar under some deficiency.
+/// Pairs with a key the theme doesn't define, or that isn't a color, are
+/// skipped, as are pairs already too similar with full color vision.
+pub fn check(rules: &Rules, vars: &HashMap<String, String>) -> Vec<String> {
+    let color = |name: &str| resolve_key(vars, name).and_then(|(_, v)| Rgb::parse(v));
+    let mut found = Vec::new();
+    for (a, b) in &rules.pairs {
+        let (Some(ca), Some(cb)) = (color(a), color(b)) else {
+            continue;
+        };
+        if ca.distance(cb) < rules.min_distance {
+            continue;
+        }
+        for kind in Cvd::ALL {
+            let d = ca.simulate(kind).distance(cb.simulate(kind));
+            if d < rules.min_distance {
+                found.push(format!("{a}/{b} are hard to tell apart with {} (distance {d:.3})", kind.as_str()));
+            }
+        }
+    }
+    found
+}
+
+/// `vars` with every hex color replaced by how it looks with `kind`.
+pub fn simulate(vars: &HashMap<String, String>, kind: Cvd) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(k, v)| {
+            let simulated = v.starts_with('#').then(|| Rgb::parse(v)).flatten().map(|c| c.simulate(kind).to_hex());
+            (k.clone(), simulated.unwrap_or_else(|| v.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_pairs_merged_by_simulation() {
+        let vars = HashMap::from([
+            ("palette_red".to_owned(), "#c0504d".to_owned()),
+            ("palette_green".to_owned(), "#6b8e23".to_owned()),
+            ("palette_blue".to_owned(), "#4f81bd".to_owned()),
+        ]);
+        let rules = Rules {
+            pairs: vec![("red".into(), "green".into()), ("red".into(), "blue".into()), ("red".into(), "nope".into())],
+            min_distance: 0.04,
+        };
+        let found = check(&rules, &vars);
+        assert!(!found.is_empty() && found.iter().all(|f| f.starts_with("red/green")), "{found:?}");
+        assert_ne!(simulate(&vars, Cvd::Deuteranopia)["palette_red"], "#c0504d");
+    }
+}
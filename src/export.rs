@@ -0,0 +1,215 @@
+//! Palette exports for tools that don't go through the template engine.
+
+use crate::{color::Rgb, render::engine::resolve_key};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::{collections::HashMap, fmt::Write as _, fs, path::Path};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// `:root { --name: value; }` custom properties
+    Css,
+    /// `$name: value;` variables
+    Scss,
+    /// Termux `colors.properties` (`~/.termux/colors.properties`)
+    Termux,
+    /// tinted-theming Base16 scheme (YAML)
+    Base16,
+    /// Lua table for Neovim colorschemes (`require("oxidize.palette")`)
+    Lua,
+}
+
+impl Format {
+    /// File name used when the export is written into the live tree.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Format::Css => "colors.css",
+            Format::Scss => "_colors.scss",
+            Format::Termux => "colors.properties",
+            Format::Base16 => "base16.yaml",
+            Format::Lua => "nvim/lua/oxidize/palette.lua",
+        }
+    }
+}
+
+/// Render every hex color in `vars` in the requested format.
+pub fn render(format: Format, vars: &HashMap<String, String>) -> String {
+    let mut colors: Vec<(String, &str)> = vars
+        .iter()
+        .filter(|(_, v)| v.starts_with('#'))
+        .map(|(k, v)| (k.replace('_', "-"), v.as_str()))
+        .collect();
+    colors.sort();
+
+    let mut out = String::new();
+    match format {
+        Format::Css => {
+            out.push_str(":root {\n");
+            for (name, value) in &colors {
+                let _ = writeln!(out, "  --{name}: {value};");
+            }
+            out.push_str("}\n");
+        }
+        Format::Scss => {
+            for (name, value) in &colors {
+                let _ = writeln!(out, "${name}: {value};");
+            }
+        }
+        Format::Termux => out.push_str(&termux(vars)),
+        Format::Base16 => out.push_str(&base16(vars)),
+        Format::Lua => out.push_str(&lua(vars)),
+    }
+    out
+}
+
+/// Termux only knows the terminal roles, so look those up by name; roles
+/// the theme doesn't define are left out and Termux keeps its defaults.
+fn termux(vars: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let roles: [(&str, &[&str]); 3] = [
+        ("background", &["background", "bg"]),
+        ("foreground", &["foreground", "fg"]),
+        ("cursor", &["cursor", "foreground", "fg"]),
+    ];
+    for (key, names) in roles {
+        if let Some(value) = names.iter().find_map(|n| hex(vars, n)) {
+            let _ = writeln!(out, "{key}={value}");
+        }
+    }
+    for i in 0..16 {
+        if let Some(value) = hex(vars, &format!("color{i}")) {
+            let _ = writeln!(out, "color{i}={value}");
+        }
+    }
+    out
+}
+
+/// A Base16 scheme. Themes that define `base00`–`base0F` are exported as
+/// they are; otherwise the grays are blended from bg to fg and the accents
+/// taken from the usual color names or the terminal palette.
+fn base16(vars: &HashMap<String, String>) -> String {
+    let is_light = vars.get("theme_is_light").is_some_and(|v| v == "true");
+    let color = |names: &[&str]| names.iter().find_map(|n| hex(vars, n)).and_then(Rgb::parse);
+    let (black, white) = (Rgb::BLACK, Rgb::WHITE);
+    let bg = color(&["base00", "bg", "background"]).unwrap_or(if is_light { white } else { black });
+    let fg = color(&["base05", "fg", "foreground"]).unwrap_or(if is_light { black } else { white });
+    let extreme = if is_light { black } else { white };
+    let accent = color(&["accent"]).unwrap_or(fg);
+    let red = color(&["base08", "red", "color1"]).unwrap_or(accent);
+    let yellow = color(&["base0A", "yellow", "color3"]).unwrap_or(accent);
+
+    let slots = [
+        bg,
+        color(&["base01"]).unwrap_or_else(|| bg.mix(fg, 0.08)),
+        color(&["base02"]).unwrap_or_else(|| bg.mix(fg, 0.16)),
+        color(&["base03"]).unwrap_or_else(|| bg.mix(fg, 0.4)),
+        color(&["base04"]).unwrap_or_else(|| bg.mix(fg, 0.7)),
+        fg,
+        color(&["base06"]).unwrap_or_else(|| fg.mix(extreme, 0.4)),
+        color(&["base07"]).unwrap_or_else(|| fg.mix(extreme, 0.8)),
+        red,
+        color(&["base09", "orange"]).unwrap_or_else(|| red.mix(yellow, 0.5)),
+        yellow,
+        color(&["base0B", "green", "color2"]).unwrap_or(accent),
+        color(&["base0C", "cyan", "teal", "color6"]).unwrap_or(accent),
+        color(&["base0D", "blue", "color4"]).unwrap_or(accent),
+        color(&["base0E", "magenta", "purple", "color5"]).unwrap_or(accent),
+        color(&["base0F", "brown"]).unwrap_or_else(|| red.darken(15.0)),
+    ];
+
+    let name = vars.get("theme_name").map_or("oxidize", String::as_str);
+    let mut out = format!(
+        "system: \"base16\"\nname: \"{name}\"\nauthor: \"oxidize\"\nvariant: \"{}\"\npalette:\n",
+        if is_light { "light" } else { "dark" }
+    );
+    for (i, c) in slots.iter().enumerate() {
+        let _ = writeln!(out, "  base{i:02X}: \"{}\"", c.to_hex());
+    }
+    out
+}
+
+/// Role names a colorscheme can rely on, resolved like template variables
+/// (`bg` finds `palette_bg`) and added next to the theme's own keys.
+const LUA_ROLES: [&str; 12] =
+    ["bg", "fg", "accent", "cursor", "red", "orange", "yellow", "green", "cyan", "blue", "magenta", "secondary"];
+
+/// `return { name = ..., background = "dark", colors = { key = "#..." } }`,
+/// with every hex color under its own key, the common role names, and
+/// `color0`–`color15`.
+fn lua(vars: &HashMap<String, String>) -> String {
+    let mut colors: Vec<(String, &str)> =
+        vars.iter().filter(|(_, v)| v.starts_with('#')).map(|(k, v)| (k.clone(), v.as_str())).collect();
+    let terminal = (0..16).map(|i| format!("color{i}"));
+    for role in LUA_ROLES.map(str::to_owned).into_iter().chain(terminal) {
+        if !vars.contains_key(&role)
+            && let Some(value) = hex(vars, &role)
+        {
+            colors.push((role, value));
+        }
+    }
+    colors.sort();
+
+    let is_light = vars.get("theme_is_light").is_some_and(|v| v == "true");
+    let name = vars.get("theme_name").map_or("oxidize", String::as_str);
+    let mut out = format!(
+        "-- Generated by oxidize; read by colors/oxidize.lua.\nreturn {{\n  name = {},\n  background = \"{}\",\n  colors = {{\n",
+        lua_string(name),
+        if is_light { "light" } else { "dark" }
+    );
+    for (key, value) in &colors {
+        let is_ident = key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !key.starts_with(|c: char| c.is_ascii_digit());
+        if is_ident {
+            let _ = writeln!(out, "    {key} = {},", lua_string(value));
+        } else {
+            let _ = writeln!(out, "    [{}] = {},", lua_string(key), lua_string(value));
+        }
+    }
+    out.push_str("  },\n}\n");
+    out
+}
+
+fn lua_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn hex<'v>(vars: &'v HashMap<String, String>, name: &str) -> Option<&'v str> {
+    resolve_key(vars, name).map(|(_, v)| v).filter(|v| v.starts_with('#'))
+}
+
+/// Write the CSS, SCSS and Lua exports into `stage`, unless a template or
+/// theme file already produced a file of the same name.
+pub fn stage(vars: &HashMap<String, String>, stage: &Path) -> Result<()> {
+    for format in [Format::Css, Format::Scss, Format::Lua] {
+        let path = stage.join(format.file_name());
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+            }
+            fs::write(&path, render(format, vars)).with_context(|| format!("write {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base16_prefers_explicit_slots() {
+        let vars = HashMap::from([
+            ("theme_name".to_owned(), "night".to_owned()),
+            ("palette_bg".to_owned(), "#000000".to_owned()),
+            ("palette_fg".to_owned(), "#ffffff".to_owned()),
+            ("palette_red".to_owned(), "#ff0000".to_owned()),
+            ("base16_base0D".to_owned(), "#0000ff".to_owned()),
+        ]);
+        let out = render(Format::Base16, &vars);
+        assert!(out.starts_with("system: \"base16\"\nname: \"night\""), "{out}");
+        assert!(out.contains("  base00: \"#000000\"\n"), "{out}");
+        assert!(out.contains("  base05: \"#ffffff\"\n"), "{out}");
+        assert!(out.contains("  base08: \"#ff0000\"\n"), "{out}");
+        assert!(out.contains("  base0D: \"#0000ff\"\n"), "{out}");
+        assert_eq!(out.lines().filter(|l| l.starts_with("  base")).count(), 16);
+    }
+}
@@ -0,0 +1,166 @@
+//! Real writes to files outside the live tree (`#! copy = true`).
+//!
+//! Some apps refuse to follow a symlink into `current/`, so their configs are
+//! copied out after the live tree is committed. Whatever sat at a target
+//! before is saved under `generated/backups/<absolute path>` first, and if any
+//! write in the batch fails, every target is put back the way it was.
+//!
+//! Each generation lists its copies in `gen-<n>.externals`, so `rollback`
+//! can write that generation's versions back out.
+
+use crate::{ctx::Ctx, generation, log, render::External};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// What a target looked like before this run touched it.
+enum Original {
+    Missing,
+    File(PathBuf),
+    Link(PathBuf),
+}
+
+struct Saved {
+    target: PathBuf,
+    original: Original,
+}
+
+/// Copy every `copy` external from the live tree to its target.
+pub fn write_all(ctx: &Ctx, externals: &[External]) -> Result<()> {
+    let mut saved = Vec::new();
+    for ext in externals.iter().filter(|e| e.copy) {
+        let result = backup(ctx, &ext.target).and_then(|original| {
+            saved.push(Saved {
+                target: ext.target.clone(),
+                original,
+            });
+            write(&ctx.live_dir.join(&ext.out), &ext.target)
+        });
+        if let Err(e) = result {
+            restore_all(&saved);
+            return Err(e).with_context(|| format!("write {} (previous files restored)", ext.target.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Remember which files generation `number` copied out, as `out<TAB>target`
+/// lines.
+pub fn record(ctx: &Ctx, number: u32, externals: &[External]) -> Result<()> {
+    let lines: String = externals
+        .iter()
+        .filter(|e| e.copy)
+        .map(|e| format!("{}\t{}\n", e.out.display(), e.target.display()))
+        .collect();
+    let path = generation::sidecar(&ctx.generated_dir, number, "externals");
+    fs::write(&path, lines).with_context(|| format!("write {}", path.display()))
+}
+
+/// Copy generation `number`'s files out again, from the live tree (which
+/// must already point at it). All or nothing, like [`write_all`].
+pub fn restore_generation(ctx: &Ctx, number: u32) -> Result<()> {
+    let path = generation::sidecar(&ctx.generated_dir, number, "externals");
+    let list = match fs::read_to_string(&path) {
+        Ok(list) => list,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+    };
+    let externals: Vec<External> = list
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(out, target)| External {
+            out: out.into(),
+            target: target.into(),
+            copy: true,
+        })
+        .collect();
+    write_all(ctx, &externals)
+}
+
+/// Where the previous version of `target` is kept.
+fn backup_path(ctx: &Ctx, target: &Path) -> PathBuf {
+    ctx.backups_dir.join(target.strip_prefix("/").unwrap_or(target))
+}
+
+fn backup(ctx: &Ctx, target: &Path) -> Result<Original> {
+    let Ok(meta) = fs::symlink_metadata(target) else {
+        return Ok(Original::Missing);
+    };
+    if meta.file_type().is_symlink() {
+        let dest = fs::read_link(target).with_context(|| format!("read link {}", target.display()))?;
+        return Ok(Original::Link(dest));
+    }
+
+    let saved = backup_path(ctx, target);
+    if let Some(parent) = saved.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::copy(target, &saved).with_context(|| format!("back up {}", target.display()))?;
+    log::debug!("backed up {} to {}", target.display(), saved.display());
+    Ok(Original::File(saved))
+}
+
+/// Replace `target` with a copy of `src` via a sibling temp file.
+fn write(src: &Path, target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let tmp = target.with_extension("oxidize-tmp");
+    fs::copy(src, &tmp).with_context(|| format!("copy {} to {}", src.display(), tmp.display()))?;
+    fs::rename(&tmp, target).with_context(|| format!("rename into place {}", target.display()))?;
+    log::debug!("wrote {}", target.display());
+    Ok(())
+}
+
+fn restore_all(saved: &[Saved]) {
+    for s in saved.iter().rev() {
+        if let Err(e) = restore(s) {
+            log::warn!("restore {}: {e:#}", s.target.display());
+        }
+    }
+}
+
+fn restore(s: &Saved) -> Result<()> {
+    match &s.original {
+        Original::Missing => match fs::remove_file(&s.target) {
+            Err(e) if !matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => Err(e.into()),
+            _ => Ok(()),
+        },
+        Original::File(saved) => write(saved, &s.target),
+        Original::Link(dest) => crate::util::symlink_force(dest, &s.target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_writes_that_generations_copies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let generated_dir = ctx.generated_dir.clone();
+        let target = tmp.path().join("app/settings.json");
+        let externals = [External {
+            out: "app/settings.json".into(),
+            target: target.clone(),
+            copy: true,
+        }];
+        for body in ["old", "new"] {
+            let stage = generated_dir.join(".stage");
+            fs::create_dir_all(stage.join("app")).unwrap();
+            fs::write(stage.join("app/settings.json"), body).unwrap();
+            let number = generation::commit(&ctx, &stage, "t\n", false).unwrap();
+            record(&ctx, number, &externals).unwrap();
+            write_all(&ctx, &externals).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+
+        generation::point_live(&ctx, 1).unwrap();
+        restore_generation(&ctx, 1).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+    }
+}
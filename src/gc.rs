@@ -0,0 +1,110 @@
+//! `oxidize gc` — clean up what crashed or interrupted runs leave behind in
+//! `generated/`. Callers must hold the run lock, so no stage is in use.
+
+use crate::{ctx::Ctx, generation, lock, log};
+use std::{fs, path::Path, time::Duration};
+
+/// How old a ticket's temp file must be to count as abandoned.
+const TICKET_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Orphaned `.stage.*` directories removed.
+    pub stages: usize,
+    /// Generations beyond the retention limit removed.
+    pub generations: usize,
+    /// `gen-<n>.*` files whose generation is gone, and stray temp links and
+    /// ticket files.
+    pub leftovers: usize,
+}
+
+/// Remove orphaned stages and leftovers, and prune to `keep` generations.
+pub fn run(ctx: &Ctx, keep: usize) -> Report {
+    let mut report = Report {
+        generations: generation::prune(ctx, keep),
+        ..Report::default()
+    };
+    let Ok(entries) = fs::read_dir(&ctx.generated_dir) else {
+        return report;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = entry.path();
+        let counter = if name.starts_with(".stage.") {
+            &mut report.stages
+        } else if name == ".live.tmp" || stale_ticket(&path, &name) || orphaned_sidecar(&ctx.generated_dir, &name) {
+            &mut report.leftovers
+        } else {
+            continue;
+        };
+        if remove(&path) {
+            *counter += 1;
+        }
+    }
+    report
+}
+
+/// `gen-<n>.<ext>` without a `gen-<n>/` next to it.
+fn orphaned_sidecar(generated_dir: &Path, name: &str) -> bool {
+    name.strip_prefix("gen-")
+        .and_then(|rest| rest.split_once('.'))
+        .is_some_and(|(n, _)| n.parse::<u32>().is_ok() && !generated_dir.join(format!("gen-{n}")).is_dir())
+}
+
+/// A ticket's temp file left by a run that died while writing it. Runs write
+/// them without the lock, so a fresh one may still be in use.
+fn stale_ticket(path: &Path, name: &str) -> bool {
+    name.starts_with(lock::PENDING_TMP)
+        && fs::symlink_metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|t| t.elapsed().is_ok_and(|age| age > TICKET_TTL))
+}
+
+fn remove(path: &Path) -> bool {
+    log::debug!("removing {}", path.display());
+    let result = match fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => fs::remove_dir_all(path),
+        _ => fs::remove_file(path),
+    };
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("remove {}: {e}", path.display());
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_stages_and_orphans_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let generated_dir = ctx.generated_dir.clone();
+        for dir in [".stage.abc/gtk", "gen-1", "gen-2", "backups"] {
+            fs::create_dir_all(generated_dir.join(dir)).unwrap();
+        }
+        let files = ["gen-1.theme", "gen-2.theme", "gen-3.undo", ".pending", ".pending.tmpOld", ".pending.tmpNew", ".lock"];
+        for file in files {
+            fs::write(generated_dir.join(file), "").unwrap();
+        }
+        let old = std::time::SystemTime::now() - 2 * TICKET_TTL;
+        let abandoned = fs::File::options().write(true).open(generated_dir.join(".pending.tmpOld")).unwrap();
+        abandoned.set_modified(old).unwrap();
+        generation::point_live(&ctx, 2).unwrap();
+
+        let report = run(&ctx, 5);
+        assert_eq!(report, Report { stages: 1, generations: 0, leftovers: 2 });
+        let mut left: Vec<_> = fs::read_dir(&generated_dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [".lock", ".pending", ".pending.tmpNew", "backups", "gen-1", "gen-1.theme", "gen-2", "gen-2.theme", "live"]
+        );
+        assert_eq!(run(&ctx, 1), Report { stages: 0, generations: 1, leftovers: 0 });
+    }
+}
@@ -0,0 +1,180 @@
+//! Numbered generations of the live tree.
+//!
+//! Each commit moves the staged tree to `generated/gen-<n>/` and repoints
+//! the `live` symlink at it, so older trees stay around for `rollback`. The
+//! theme record of each generation (the `current.theme` it was written with)
+//! sits next to it in `gen-<n>.theme`, and the files it copied outside the
+//! live tree in `gen-<n>.externals` (see [`crate::external`]).
+
+use crate::{ctx::Ctx, log, util};
+use anyhow::{Context, Result, bail};
+use std::{
+    fs,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Generations kept when `[generations] keep` isn't set.
+pub const DEFAULT_KEEP: usize = 5;
+
+pub struct Generation {
+    pub number: u32,
+    pub path: PathBuf,
+    pub created: Option<SystemTime>,
+    /// Theme name and variant, as in `current.theme`.
+    pub record: Option<String>,
+}
+
+impl Generation {
+    fn at(generated_dir: &Path, number: u32) -> Self {
+        let path = generated_dir.join(format!("gen-{number}"));
+        Self {
+            number,
+            created: fs::symlink_metadata(&path).and_then(|m| m.modified()).ok(),
+            record: fs::read_to_string(sidecar(generated_dir, number, "theme")).ok(),
+            path,
+        }
+    }
+
+    /// `name` or `name (variant)`.
+    pub fn theme(&self) -> Option<String> {
+        let mut lines = self.record.as_deref()?.lines().map(str::trim);
+        let name = lines.next().filter(|n| !n.is_empty())?;
+        Some(match lines.next().filter(|v| !v.is_empty()) {
+            Some(variant) => format!("{name} ({variant})"),
+            None => name.to_owned(),
+        })
+    }
+}
+
+/// `gen-<number>.<ext>`, a file kept and pruned along with the generation.
+pub fn sidecar(generated_dir: &Path, number: u32, ext: &str) -> PathBuf {
+    generated_dir.join(format!("gen-{number}.{ext}"))
+}
+
+/// Every generation on disk, oldest first.
+pub fn list(ctx: &Ctx) -> Vec<Generation> {
+    let Ok(entries) = fs::read_dir(&ctx.generated_dir) else {
+        return Vec::new();
+    };
+    let mut numbers: Vec<u32> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| e.file_name().to_str()?.strip_prefix("gen-")?.parse().ok())
+        .collect();
+    numbers.sort_unstable();
+    numbers.into_iter().map(|n| Generation::at(&ctx.generated_dir, n)).collect()
+}
+
+/// The generation `live` points at.
+pub fn current(ctx: &Ctx) -> Option<u32> {
+    let target = fs::read_link(&ctx.live_dir).ok()?;
+    target.file_name()?.to_str()?.strip_prefix("gen-")?.parse().ok()
+}
+
+/// Move `tree` into place as a new generation, record `record` with it, and
+/// point `live` at it. Returns the new generation's number.
+///
+/// A `live/` left as a plain directory by older versions becomes a
+/// generation of its own first, so it can still be rolled back to.
+///
+/// With `fsync`, the tree is flushed before it is published and
+/// `generated/` after each rename.
+pub fn commit(ctx: &Ctx, tree: &Path, record: &str, fsync: bool) -> Result<u32> {
+    adopt(ctx)?;
+    let next = list(ctx).last().map_or(1, |g| g.number + 1);
+    let path = ctx.generated_dir.join(format!("gen-{next}"));
+    if fsync {
+        util::fsync_tree(tree)?;
+    }
+    fs::rename(tree, &path).with_context(|| format!("rename stage -> {}", path.display()))?;
+    let record_path = sidecar(&ctx.generated_dir, next, "theme");
+    fs::write(&record_path, record).with_context(|| format!("write {}", record_path.display()))?;
+    if fsync {
+        util::fsync_tree(&record_path)?;
+        util::fsync_dir(&ctx.generated_dir)?;
+    }
+    point_live(ctx, next)?;
+    if fsync {
+        util::fsync_dir(&ctx.generated_dir)?;
+    }
+    Ok(next)
+}
+
+/// Turn a `live/` directory from before generations into the newest
+/// generation, with `live` pointing at it. Does nothing otherwise.
+pub fn adopt(ctx: &Ctx) -> Result<()> {
+    if !fs::symlink_metadata(&ctx.live_dir).is_ok_and(|m| m.is_dir()) {
+        return Ok(());
+    }
+    let number = list(ctx).last().map_or(1, |g| g.number + 1);
+    let path = ctx.generated_dir.join(format!("gen-{number}"));
+    fs::rename(&ctx.live_dir, &path).context("move old live dir into a generation")?;
+    point_live(ctx, number)
+}
+
+/// Atomically repoint `live` at generation `number`.
+pub fn point_live(ctx: &Ctx, number: u32) -> Result<()> {
+    let target = format!("gen-{number}");
+    if !ctx.generated_dir.join(&target).is_dir() {
+        bail!("generation {number} does not exist (see `oxidize generations`)");
+    }
+    // Relative, so the whole generated/ dir can move.
+    let tmp = ctx.generated_dir.join(".live.tmp");
+    let _ = fs::remove_file(&tmp);
+    symlink(&target, &tmp).with_context(|| format!("create {}", tmp.display()))?;
+    fs::rename(&tmp, &ctx.live_dir).with_context(|| format!("point {} at {target}", ctx.live_dir.display()))
+}
+
+/// Delete all but the newest `keep` generations. The one `live` points at
+/// is always kept. Returns how many were removed.
+pub fn prune(ctx: &Ctx, keep: usize) -> usize {
+    let gens = list(ctx);
+    let live = current(ctx);
+    let excess = gens.len().saturating_sub(keep);
+    let mut removed = 0;
+    for g in gens.iter().take(excess).filter(|g| Some(g.number) != live) {
+        log::debug!("removing generation {}", g.number);
+        if let Err(e) = fs::remove_dir_all(&g.path) {
+            log::warn!("remove {}: {e}", g.path.display());
+            continue;
+        }
+        removed += 1;
+        for ext in ["theme", "externals", "undo"] {
+            let _ = fs::remove_file(sidecar(&ctx.generated_dir, g.number, ext));
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commits_roll_back_and_prune() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = Ctx::under(tmp.path());
+        let generated_dir = ctx.generated_dir.clone();
+        // A pre-generations live/ directory is kept as generation 1.
+        fs::create_dir_all(ctx.live_dir.join("gtk")).unwrap();
+        for theme in ["a", "b", "c"] {
+            let stage = generated_dir.join(".stage");
+            fs::create_dir(&stage).unwrap();
+            fs::write(stage.join("name"), theme).unwrap();
+            commit(&ctx, &stage, &format!("{theme}\ndark\n"), true).unwrap();
+        }
+        assert_eq!(list(&ctx).iter().map(|g| g.number).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(current(&ctx), Some(4));
+        assert_eq!(fs::read_to_string(ctx.live_dir.join("name")).unwrap(), "c");
+
+        point_live(&ctx, 2).unwrap();
+        assert_eq!(fs::read_to_string(ctx.live_dir.join("name")).unwrap(), "a");
+        assert!(point_live(&ctx, 9).is_err());
+
+        prune(&ctx, 1);
+        let left: Vec<_> = list(&ctx).iter().map(|g| (g.number, g.theme())).collect();
+        assert_eq!(left, [(2, Some("a (dark)".to_owned())), (4, Some("c (dark)".to_owned()))]);
+    }
+}
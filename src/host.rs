@@ -0,0 +1,77 @@
+//! Per-machine differences from `hosts/<hostname>/` in the config dir, for
+//! configs synced between several machines.
+//!
+//! ```text
+//! hosts/<hostname>/
+//! ├── host.toml          # theme = "..." (default theme on this machine)
+//! ├── colors.toml        # variables merged over every theme
+//! └── backgrounds/<theme>/
+//! ```
+
+use crate::{render::engine::build_vars_from_colors, theme::Theme};
+use anyhow::{Context, Result, bail};
+use std::{fs, path::Path};
+
+/// The theme `host.toml` names, used when no theme is given or recorded.
+pub fn default_theme(dir: &Path) -> Result<Option<String>> {
+    let path = dir.join("host.toml");
+    let src = match fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+    };
+    let table: toml::Table = src.parse().with_context(|| format!("parse {}", path.display()))?;
+    let mut theme = None;
+    for (key, value) in table {
+        match key.as_str() {
+            "theme" => {
+                let name = value.as_str().with_context(|| format!("{}: theme must be a string", path.display()))?;
+                theme = Some(name.to_owned());
+            }
+            other => bail!("{}: unknown key '{other}'", path.display()),
+        }
+    }
+    Ok(theme)
+}
+
+/// Merge the host's `colors.toml` over `theme`'s variables and swap in its
+/// wallpapers for the theme, if it has any.
+pub fn apply(dir: &Path, theme: &mut Theme) -> Result<()> {
+    let colors = dir.join("colors.toml");
+    if colors.is_file() {
+        theme.vars.extend(build_vars_from_colors(&colors)?);
+    }
+    let backgrounds = dir.join("backgrounds").join(&theme.name);
+    if backgrounds.is_dir() {
+        theme.backgrounds_dir = Some(backgrounds);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_overrides_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data");
+        let host = dir.path().join("host");
+        fs::create_dir_all(data.join("t")).unwrap();
+        fs::create_dir_all(host.join("backgrounds/t")).unwrap();
+        fs::write(data.join("t/colors.toml"), "[palette]\nbg = \"#000000\"\nfg = \"#ffffff\"\n").unwrap();
+        fs::write(host.join("colors.toml"), "[palette]\nbg = \"#111111\"\n\n[ui]\nscale = 2\n").unwrap();
+
+        let mut theme = Theme::load(&[data], "t", None).unwrap();
+        apply(&host, &mut theme).unwrap();
+        assert_eq!((theme.vars["palette_bg"].as_str(), theme.vars["palette_fg"].as_str()), ("#111111", "#ffffff"));
+        assert_eq!(theme.vars["ui_scale"], "2");
+        assert_eq!(theme.backgrounds_dir, Some(host.join("backgrounds/t")));
+
+        assert_eq!(default_theme(&host).unwrap(), None);
+        fs::write(host.join("host.toml"), "theme = \"t\"\n").unwrap();
+        assert_eq!(default_theme(&host).unwrap().as_deref(), Some("t"));
+        fs::write(host.join("host.toml"), "them = \"t\"\n").unwrap();
+        assert!(default_theme(&host).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! `oxidize import` — turn color schemes from other tools into themes.
+
+pub mod yaml;
+
+use crate::{color::Rgb, json};
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// pywal's `colors.json` (default: `~/.cache/wal/colors.json`)
+    Pywal,
+    /// wallust's JSON cache or pywal-style output
+    Wallust,
+    /// VS Code color theme (`*-color-theme.json`)
+    Vscode,
+    /// Alacritty color scheme, TOML or the older YAML
+    Alacritty,
+    /// Gogh theme (`themes/*.yml`)
+    Gogh,
+}
+
+impl Format {
+    /// Where the tool writes its scheme when no path is given.
+    pub fn default_path(self) -> Option<PathBuf> {
+        let cache = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))?;
+        match self {
+            Format::Pywal => Some(cache.join("wal/colors.json")),
+            Format::Wallust | Format::Vscode | Format::Alacritty | Format::Gogh => None,
+        }
+    }
+}
+
+/// A palette read from another tool, in the order it is written out.
+pub struct Scheme {
+    pub colors: Vec<(String, Rgb)>,
+    /// Light or dark, if the format says; otherwise judged from `bg`.
+    pub light: Option<bool>,
+}
+
+impl Scheme {
+    fn get(&self, key: &str) -> Option<Rgb> {
+        self.colors.iter().find(|(k, _)| k == key).map(|(_, c)| *c)
+    }
+}
+
+/// Read `path` as `format`.
+pub fn read(format: Format, path: &Path) -> Result<Scheme> {
+    let src = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    match format {
+        Format::Pywal | Format::Wallust => pywal(&src),
+        Format::Vscode => vscode(&src),
+        Format::Alacritty if path.extension().is_some_and(|e| e == "toml") => {
+            let table: toml::Table = src.parse()?;
+            let mut flat = Vec::new();
+            flatten_toml("", &toml::Value::Table(table), &mut flat);
+            alacritty(&flat)
+        }
+        Format::Alacritty => alacritty(&yaml::flatten(&src)),
+        Format::Gogh => gogh(&yaml::flatten(&src)),
+    }
+    .with_context(|| format!("parse {}", path.display()))
+}
+
+/// pywal nests `background`/`foreground`/`cursor` under `special` and
+/// `color0`–`color15` under `colors`; wallust's cache has them all at the
+/// top level.
+fn pywal(src: &str) -> Result<Scheme> {
+    let root = json::parse(src)?;
+    let lookup = |table: &str, key: &str| {
+        root.get(table)
+            .and_then(|t| t.get(key))
+            .or_else(|| root.get(key))
+            .and_then(json::Value::as_str)
+            .and_then(Rgb::parse)
+    };
+
+    let mut colors = Vec::new();
+    for (key, name) in [("bg", "background"), ("fg", "foreground"), ("cursor", "cursor")] {
+        colors.extend(lookup("special", name).map(|c| (key.to_owned(), c)));
+    }
+    for i in 0..16 {
+        let key = format!("color{i}");
+        colors.extend(lookup("colors", &key).map(|c| (key, c)));
+    }
+    terminal_scheme(colors)
+}
+
+/// Fill in what a terminal scheme may leave out: `bg`/`fg` from
+/// `color0`/`color7`, and `accent` from blue (`color4`).
+fn terminal_scheme(mut colors: Vec<(String, Rgb)>) -> Result<Scheme> {
+    let has = |colors: &[(String, Rgb)], key: &str| colors.iter().any(|(k, _)| k == key);
+    let find = |colors: &[(String, Rgb)], key: &str| colors.iter().find(|(k, _)| k == key).map(|(_, c)| *c);
+    if !(0..8).all(|i| has(&colors, &format!("color{i}"))) {
+        bail!("expected at least color0 through color7");
+    }
+    for (key, from) in [("bg", "color0"), ("fg", "color7"), ("accent", "color4")] {
+        if !has(&colors, key) {
+            let color = find(&colors, from).expect("checked above");
+            colors.push((key.to_owned(), color));
+        }
+    }
+    colors.sort_by_key(|(k, _)| color_index(k));
+    Ok(Scheme { colors, light: None })
+}
+
+/// Sort key putting roles first, then `color0`–`color15` in numeric order.
+fn color_index(key: &str) -> Option<u8> {
+    key.strip_prefix("color").and_then(|n| n.parse().ok())
+}
+
+/// The eight ANSI color names in color0–color7 order.
+const ANSI: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// A color as terminal configs write it: `#rrggbb` or `0xrrggbb`.
+fn terminal_hex(value: &str) -> Option<Rgb> {
+    Rgb::parse(value.strip_prefix("0x").unwrap_or(value))
+}
+
+fn lookup<'a>(flat: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    flat.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+/// Alacritty's `[colors.primary]`, `[colors.cursor]`, `[colors.normal]`
+/// and `[colors.bright]`.
+fn alacritty(flat: &[(String, String)]) -> Result<Scheme> {
+    let color = |key: &str| lookup(flat, &format!("colors.{key}")).and_then(terminal_hex);
+    let mut colors = Vec::new();
+    for (key, from) in [
+        ("bg", "primary.background"),
+        ("fg", "primary.foreground"),
+        ("cursor", "cursor.cursor"),
+        ("selection", "selection.background"),
+    ] {
+        colors.extend(color(from).map(|c| (key.to_owned(), c)));
+    }
+    for (i, name) in ANSI.iter().enumerate() {
+        colors.extend(color(&format!("normal.{name}")).map(|c| (format!("color{i}"), c)));
+        colors.extend(color(&format!("bright.{name}")).map(|c| (format!("color{}", i + 8), c)));
+    }
+    terminal_scheme(colors)
+}
+
+/// Gogh's `color_01`–`color_16` plus `background`, `foreground`, `cursor`
+/// and `variant`.
+fn gogh(flat: &[(String, String)]) -> Result<Scheme> {
+    let mut colors = Vec::new();
+    for (key, from) in [("bg", "background"), ("fg", "foreground"), ("cursor", "cursor")] {
+        colors.extend(lookup(flat, from).and_then(terminal_hex).map(|c| (key.to_owned(), c)));
+    }
+    for i in 0..16 {
+        colors.extend(lookup(flat, &format!("color_{:02}", i + 1)).and_then(terminal_hex).map(|c| (format!("color{i}"), c)));
+    }
+    let mut scheme = terminal_scheme(colors)?;
+    scheme.light = lookup(flat, "variant").map(|v| v == "light");
+    Ok(scheme)
+}
+
+/// Flatten a TOML tree into the dotted keys [`yaml::flatten`] produces.
+fn flatten_toml(prefix: &str, value: &toml::Value, out: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                flatten_toml(&path, value, out);
+            }
+        }
+        toml::Value::String(s) => out.push((prefix.to_owned(), s.clone())),
+        _ => {}
+    }
+}
+
+/// Workbench colors mapped onto oxidize roles; the first key the theme
+/// defines wins.
+const VSCODE_ROLES: &[(&str, &[&str])] = &[
+    ("bg", &["editor.background"]),
+    ("fg", &["editor.foreground", "foreground"]),
+    ("accent", &["focusBorder", "button.background", "activityBarBadge.background"]),
+    ("cursor", &["editorCursor.foreground"]),
+    ("selection", &["editor.selectionBackground"]),
+    ("surface", &["sideBar.background", "activityBar.background"]),
+    ("panel", &["panel.background", "statusBar.background"]),
+    ("border", &["editorGroup.border", "panel.border", "sideBar.border"]),
+    ("muted", &["descriptionForeground", "editorLineNumber.foreground"]),
+    ("error", &["errorForeground", "editorError.foreground"]),
+    ("warning", &["editorWarning.foreground"]),
+];
+
+/// `terminal.ansi*` names in color0–color7 order.
+const VSCODE_ANSI: [&str; 8] = ["Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White"];
+
+/// TextMate scopes for the `syntax_*` keys, matched as scope prefixes.
+const VSCODE_SYNTAX: &[(&str, &[&str])] = &[
+    ("syntax_comment", &["comment"]),
+    ("syntax_string", &["string"]),
+    ("syntax_keyword", &["keyword", "storage"]),
+    ("syntax_number", &["constant.numeric", "constant"]),
+    ("syntax_function", &["entity.name.function", "support.function"]),
+    ("syntax_type", &["entity.name.type", "support.type", "entity.name.class"]),
+    ("syntax_variable", &["variable"]),
+];
+
+/// A VS Code color theme: `colors` for the workbench and terminal, and the
+/// `tokenColors` rules for syntax colors.
+fn vscode(src: &str) -> Result<Scheme> {
+    let root = json::parse(src)?;
+    let Some(workbench) = root.get("colors") else {
+        bail!("no \"colors\" object; is this a VS Code color theme?");
+    };
+    let workbench = |key: &str| workbench.get(key).and_then(json::Value::as_str).and_then(Rgb::parse);
+
+    let mut colors: Vec<(String, Rgb)> = Vec::new();
+    for (role, keys) in VSCODE_ROLES {
+        colors.extend(keys.iter().find_map(|k| workbench(k)).map(|c| (role.to_string(), c)));
+    }
+    if !colors.iter().any(|(k, _)| k == "bg") {
+        bail!("theme defines no editor.background");
+    }
+
+    // `scope` is a string (possibly comma-separated) or an array.
+    let mut rules: Vec<(Vec<&str>, Rgb)> = Vec::new();
+    for rule in root.get("tokenColors").map(json::Value::items).unwrap_or_default() {
+        let fg = rule.get("settings").and_then(|s| s.get("foreground")).and_then(json::Value::as_str);
+        let Some(fg) = fg.and_then(Rgb::parse) else { continue };
+        let scopes: Vec<&str> = match rule.get("scope") {
+            Some(json::Value::Str(s)) => s.split(',').map(str::trim).collect(),
+            Some(v) => v.items().iter().filter_map(json::Value::as_str).collect(),
+            None => continue,
+        };
+        rules.push((scopes, fg));
+    }
+    let matches = |scope: &str, wanted: &str| scope.strip_prefix(wanted).is_some_and(|r| r.is_empty() || r.starts_with('.'));
+    for (key, wanted) in VSCODE_SYNTAX {
+        let hit = wanted
+            .iter()
+            .find_map(|w| rules.iter().find(|(scopes, _)| scopes.iter().any(|s| matches(s, w))));
+        colors.extend(hit.map(|(_, c)| (key.to_string(), *c)));
+    }
+
+    for (i, name) in VSCODE_ANSI.iter().enumerate() {
+        colors.extend(workbench(&format!("terminal.ansi{name}")).map(|c| (format!("color{i}"), c)));
+        colors.extend(workbench(&format!("terminal.ansiBright{name}")).map(|c| (format!("color{}", i + 8), c)));
+    }
+    colors.sort_by_key(|(k, _)| color_index(k));
+
+    let light = root.get("type").and_then(json::Value::as_str).map(|t| t == "light" || t == "hcLight");
+    Ok(Scheme { colors, light })
+}
+
+/// Write `scheme` as a new theme `name` in `data_dir`.
+pub fn write(data_dir: &Path, name: &str, scheme: &Scheme, source: &str, force: bool) -> Result<()> {
+    let root = data_dir.join(name);
+    if root.exists() && !force {
+        bail!("theme '{name}' already exists (pass --force to overwrite its palette)");
+    }
+    fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+
+    let mut colors = format!("# Imported from {source} by `oxidize import`.\n[palette]\n");
+    for (key, color) in &scheme.colors {
+        let _ = writeln!(colors, "{key} = \"{}\"", color.to_hex());
+    }
+    let path = root.join("colors.toml");
+    fs::write(&path, colors).with_context(|| format!("write {}", path.display()))?;
+
+    let is_light = scheme.light.unwrap_or_else(|| scheme.get("bg").is_some_and(|bg| bg.luminance() > 0.18));
+    let path = root.join("theme.toml");
+    let meta = format!("variant = \"{}\"\n", if is_light { "light" } else { "dark" });
+    fs::write(&path, meta).with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pywal_and_wallust_layouts() {
+        let mut nested = String::from(r##"{"special": {"background": "#101010", "foreground": "#e0e0e0"}, "colors": {"##);
+        let mut flat = String::from(r##"{"background": "#101010", "##);
+        for i in 0..16 {
+            let sep = if i < 15 { "," } else { "" };
+            let _ = write!(nested, r##""color{i}": "#0000{i:02x}"{sep}"##);
+            let _ = write!(flat, r##""color{i}": "#0000{i:02x}"{sep}"##);
+        }
+        nested.push_str("}}");
+        flat.push('}');
+
+        let scheme = pywal(&nested).unwrap();
+        assert_eq!(scheme.colors[0].0, "bg");
+        assert_eq!(scheme.get("fg").unwrap().to_hex(), "#e0e0e0");
+        assert_eq!(scheme.get("accent").unwrap().to_hex(), "#000004");
+        assert_eq!(scheme.get("color15").unwrap().to_hex(), "#00000f");
+
+        let scheme = pywal(&flat).unwrap();
+        assert_eq!(scheme.get("bg").unwrap().to_hex(), "#101010");
+        assert_eq!(scheme.get("fg").unwrap().to_hex(), "#000007");
+        assert!(pywal("{}").is_err());
+    }
+
+    #[test]
+    fn vscode_workbench_terminal_and_tokens() {
+        let src = r##"{
+            "type": "light",
+            "colors": {
+                "editor.background": "#fafafa",
+                "editor.foreground": "#383a42",
+                "button.background": "#4078f2",
+                "terminal.ansiRed": "#e45649",
+                "terminal.ansiBrightRed": "#ff6e64cc",
+            },
+            "tokenColors": [
+                { "settings": { "foreground": "#383a42" } },
+                { "scope": "comment.line, punctuation.definition.comment", "settings": { "foreground": "#a0a1a7" } },
+                { "scope": ["keyword.control", "storage.type"], "settings": { "foreground": "#a626a4" } },
+            ],
+        }"##;
+        let scheme = vscode(src).unwrap();
+        let get = |k: &str| scheme.get(k).map(Rgb::to_hex);
+        assert_eq!(get("accent").as_deref(), Some("#4078f2"));
+        assert_eq!(get("syntax_comment").as_deref(), Some("#a0a1a7"));
+        assert_eq!(get("syntax_keyword").as_deref(), Some("#a626a4"));
+        assert_eq!(get("syntax_string"), None);
+        assert_eq!((get("color1").as_deref(), get("color9").as_deref()), (Some("#e45649"), Some("#ff6e64")));
+        assert_eq!(scheme.colors.last().unwrap().0, "color9");
+        assert_eq!(scheme.light, Some(true));
+        assert!(vscode("{\"colors\": {}}").is_err());
+    }
+
+    #[test]
+    fn alacritty_toml_yaml_and_gogh() {
+        let mut toml_src = String::from("[colors.primary]\nbackground = '#1d1f21'\nforeground = '0xc5c8c6'\n");
+        let mut yaml_src = String::from("colors:\n  primary:\n    background: '0x1d1f21'\n  normal:\n");
+        let mut gogh_src = String::from("name: 'Test'\nvariant: 'light'\nbackground: '#fafafa'\n");
+        toml_src.push_str("[colors.normal]\n");
+        for (i, name) in ANSI.iter().enumerate() {
+            let _ = writeln!(toml_src, "{name} = '#00000{i}'");
+            let _ = writeln!(yaml_src, "    {name}: '#00000{i}'");
+            let _ = writeln!(gogh_src, "color_{:02}: '#00000{i}'  # {name}", i + 1);
+        }
+
+        let mut flat = Vec::new();
+        flatten_toml("", &toml::Value::Table(toml_src.parse().unwrap()), &mut flat);
+        let scheme = alacritty(&flat).unwrap();
+        assert_eq!(scheme.get("fg").unwrap().to_hex(), "#c5c8c6");
+        assert_eq!(scheme.get("color4").unwrap().to_hex(), "#000004");
+
+        let scheme = alacritty(&yaml::flatten(&yaml_src)).unwrap();
+        assert_eq!(scheme.get("bg").unwrap().to_hex(), "#1d1f21");
+        assert_eq!(scheme.get("fg").unwrap().to_hex(), "#000007");
+
+        let scheme = gogh(&yaml::flatten(&gogh_src)).unwrap();
+        assert_eq!(scheme.get("bg").unwrap().to_hex(), "#fafafa");
+        assert_eq!(scheme.get("color7").unwrap().to_hex(), "#000007");
+        assert_eq!(scheme.light, Some(true));
+    }
+}
@@ -0,0 +1,77 @@
+//! Just enough YAML for terminal color schemes: nested block mappings of
+//! scalars. Sequences, flow collections and multi-line scalars are skipped.
+
+/// Every scalar in `src` under its dotted path (`colors.normal.black`).
+pub fn flatten(src: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    for line in src.lines() {
+        let line = strip_comment(line).trim_end();
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with("- ") || content == "---" || content == "..." {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        while parents.last().is_some_and(|(i, _)| *i >= indent) {
+            parents.pop();
+        }
+        let Some((key, value)) = content.split_once(':') else {
+            continue;
+        };
+        let key = unquote(key.trim());
+        let value = value.trim();
+        if value.is_empty() {
+            parents.push((indent, key.to_owned()));
+            continue;
+        }
+        let path: Vec<&str> = parents.iter().map(|(_, k)| k.as_str()).chain([key]).collect();
+        out.push((path.join("."), unquote(value).to_owned()));
+    }
+    out
+}
+
+/// Cut a `#` comment that starts outside quotes, at the line start or after
+/// whitespace (so `'#1e1e2e'` and `a#b` survive).
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if prev.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        prev = c;
+    }
+    line
+}
+
+fn unquote(s: &str) -> &str {
+    for q in ['\'', '"'] {
+        if let Some(inner) = s.strip_prefix(q).and_then(|s| s.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_mappings_and_comments() {
+        let src = "---\n# scheme\ncolors:\n  primary:\n    background: '#1e1e2e' # base\n    foreground: \"0xcdd6f4\"\n  \
+                   normal:\n    black: '#45475a'\nname: Dracula\nlist:\n  - skipped\n";
+        assert_eq!(
+            flatten(src),
+            [
+                ("colors.primary.background".to_owned(), "#1e1e2e".to_owned()),
+                ("colors.primary.foreground".to_owned(), "0xcdd6f4".to_owned()),
+                ("colors.normal.black".to_owned(), "#45475a".to_owned()),
+                ("name".to_owned(), "Dracula".to_owned()),
+            ]
+        );
+    }
+}
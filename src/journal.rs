@@ -0,0 +1,180 @@
+//! Append-only record of theme switches, for `oxidize history`.
+//!
+//! One tab-separated line per switch: time, previous theme, new theme,
+//! variant, generation, wallpaper, and the apply steps as `step=result`
+//! pairs separated by `; `. Empty fields are `-`.
+
+use anyhow::{Context, Result};
+use jiff::Timestamp;
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write as _},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub time: Timestamp,
+    pub from: Option<String>,
+    pub to: String,
+    pub variant: Option<String>,
+    pub generation: Option<u32>,
+    pub wallpaper: Option<PathBuf>,
+    /// `(step, result)`, e.g. `("wallpaper", "failed: no images")`.
+    pub steps: Vec<(String, String)>,
+}
+
+impl Entry {
+    pub fn new(from: Option<String>, to: &str) -> Self {
+        Self {
+            time: Timestamp::now(),
+            from,
+            to: to.to_owned(),
+            variant: None,
+            generation: None,
+            wallpaper: None,
+            steps: Vec::new(),
+        }
+    }
+
+    /// An entry for a switch from `current.theme` contents `from` to `to`.
+    pub fn from_records(from: Option<&str>, to: &str) -> Self {
+        let name = |record: &str| record.lines().next().map(str::trim).filter(|n| !n.is_empty()).map(str::to_owned);
+        let mut entry = Self::new(from.and_then(name), &name(to).unwrap_or_default());
+        entry.variant = to.lines().nth(1).map(str::trim).filter(|v| !v.is_empty()).map(str::to_owned);
+        entry
+    }
+
+    pub fn step(&mut self, step: &str, result: impl Into<String>) {
+        self.steps.push((step.to_owned(), result.into()));
+    }
+
+    fn to_line(&self) -> String {
+        let field = |s: Option<String>| s.map(|s| clean(&s)).filter(|s| !s.is_empty()).unwrap_or_else(|| "-".to_owned());
+        let steps: Vec<String> = self.steps.iter().map(|(k, v)| format!("{k}={}", clean(v).replace("; ", ", "))).collect();
+        [
+            self.time.to_string(),
+            field(self.from.clone()),
+            field(Some(self.to.clone())),
+            field(self.variant.clone()),
+            field(self.generation.map(|g| g.to_string())),
+            field(self.wallpaper.as_ref().map(|w| w.display().to_string())),
+            field(Some(steps.join("; "))),
+        ]
+        .join("\t")
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [time, from, to, variant, generation, wallpaper, steps] = fields.as_slice() else {
+            return None;
+        };
+        let opt = |s: &str| (s != "-").then(|| s.to_owned());
+        Some(Self {
+            time: time.parse().ok()?,
+            from: opt(from),
+            to: (*to).to_owned(),
+            variant: opt(variant),
+            generation: generation.parse().ok(),
+            wallpaper: opt(wallpaper).map(PathBuf::from),
+            steps: opt(steps)
+                .map(|s| {
+                    s.split("; ")
+                        .map(|p| p.split_once('=').map_or((p.to_owned(), String::new()), |(k, v)| (k.to_owned(), v.to_owned())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl std::fmt::Display for Entry {
+    /// `2026-10-13 14:02  nord -> latte (light)  gen 7  wall.png  gnome ok, ...`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = self.time.to_zoned(jiff::tz::TimeZone::system()).strftime("%Y-%m-%d %H:%M");
+        write!(f, "{time}  {} -> {}", self.from.as_deref().unwrap_or("?"), self.to)?;
+        if let Some(variant) = &self.variant {
+            write!(f, " ({variant})")?;
+        }
+        if let Some(generation) = self.generation {
+            write!(f, "  gen {generation}")?;
+        }
+        if let Some(name) = self.wallpaper.as_deref().and_then(Path::file_name) {
+            write!(f, "  {}", name.to_string_lossy())?;
+        }
+        let steps: Vec<String> = self.steps.iter().map(|(k, v)| format!("{k} {v}")).collect();
+        if !steps.is_empty() {
+            write!(f, "  {}", steps.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `--since` / `--at`: an RFC 3339 timestamp, or a local date or date and
+/// time (`2026-10-13`, `2026-10-13T14:00`). A bare date means its start.
+pub fn parse_when(when: &str) -> Result<Timestamp> {
+    if let Ok(ts) = when.parse::<Timestamp>() {
+        return Ok(ts);
+    }
+    let local = when
+        .parse::<jiff::civil::DateTime>()
+        .or_else(|_| when.parse::<jiff::civil::Date>().map(|d| d.to_datetime(jiff::civil::Time::midnight())))
+        .with_context(|| format!("'{when}' is not a date like 2026-10-13 or 2026-10-13T14:00"))?;
+    Ok(local.to_zoned(jiff::tz::TimeZone::system())?.timestamp())
+}
+
+/// Tabs and newlines would break the line format.
+fn clean(s: &str) -> String {
+    s.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Add `entry` to the journal at `path`.
+pub fn append(path: &Path, entry: &Entry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    // One write per line, so concurrent appends don't interleave.
+    file.write_all(format!("{}\n", entry.to_line()).as_bytes())
+        .with_context(|| format!("append to {}", path.display()))
+}
+
+/// Every entry in the journal, oldest first. Lines that don't parse are
+/// skipped; a missing journal is empty.
+pub fn read(path: &Path) -> Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(src) => Ok(src.lines().filter_map(Entry::parse).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("journal.tsv");
+        let mut entry = Entry::new(None, "nord");
+        entry.variant = Some("dark".into());
+        entry.generation = Some(4);
+        entry.wallpaper = Some("/walls/a b.png".into());
+        entry.step("gnome", "ok");
+        entry.step("wallpaper", "failed: awww\tnot running; retry");
+        append(&path, &entry).unwrap();
+        fs::write(&path, format!("{}garbage line\n", fs::read_to_string(&path).unwrap())).unwrap();
+        append(&path, &Entry::new(Some("nord".into()), "latte")).unwrap();
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].steps[1], ("wallpaper".to_owned(), "failed: awww not running, retry".to_owned()));
+        assert_eq!(entries[0].wallpaper, entry.wallpaper);
+        assert_eq!((entries[1].from.as_deref(), entries[1].to.as_str(), entries[1].steps.len()), (Some("nord"), "latte", 0));
+    }
+}
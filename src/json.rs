@@ -0,0 +1,466 @@
+//! Just enough JSON for color scheme files, VS Code settings and
+//! `set --plan`. Accepts the JSONC that VS Code uses: `//` and `/* */`
+//! comments and trailing commas.
+
+use anyhow::{Result, bail};
+use std::fmt::{self, Write as _};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Value>),
+    /// Members in file order.
+    Obj(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Obj(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Elements of an array; empty for anything else.
+    pub fn items(&self) -> &[Value] {
+        match self {
+            Value::Arr(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// An object from `(key, value)` pairs, in order.
+    pub fn obj<K: Into<String>>(members: impl IntoIterator<Item = (K, Value)>) -> Self {
+        Value::Obj(members.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// An array of strings.
+    pub fn strs<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        Value::Arr(items.into_iter().map(|s| Value::Str(s.into())).collect())
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        let pad = |out: &mut String, n: usize| out.extend(std::iter::repeat_n("  ", n));
+        match self {
+            Value::Null => out.push_str("null"),
+            Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Value::Num(n) if n.is_finite() => {
+                let _ = write!(out, "{n}");
+            }
+            Value::Num(_) => out.push_str("null"),
+            Value::Str(s) => quote(out, s),
+            Value::Arr(items) if items.is_empty() => out.push_str("[]"),
+            Value::Obj(members) if members.is_empty() => out.push_str("{}"),
+            Value::Arr(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    pad(out, indent + 1);
+                    item.write(out, indent + 1);
+                }
+                out.push('\n');
+                pad(out, indent);
+                out.push(']');
+            }
+            Value::Obj(members) => {
+                out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    pad(out, indent + 1);
+                    quote(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                pad(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<&std::path::Path> for Value {
+    fn from(p: &std::path::Path) -> Self {
+        Value::Str(p.display().to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Pretty-printed, two spaces per level.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        f.write_str(&out)
+    }
+}
+
+fn quote(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+pub fn parse(src: &str) -> Result<Value> {
+    let mut p = Parser { src: src.as_bytes(), pos: 0 };
+    let value = p.value()?;
+    p.skip_ws()?;
+    if p.pos < p.src.len() {
+        bail!("trailing characters at byte {}", p.pos);
+    }
+    Ok(value)
+}
+
+/// `src`, a JSONC object, with its top-level member `key` set to `value`.
+/// Everything else (comments, order, layout) is left as it was: an existing
+/// member has just its value replaced, a new one is appended after the last.
+pub fn set_member(src: &str, key: &str, value: &Value) -> Result<String> {
+    let mut member = String::new();
+    quote(&mut member, key);
+    if src.trim().is_empty() {
+        return Ok(format!("{{\n  {member}: {}\n}}\n", indented(value, "  ")));
+    }
+    parse(src)?;
+
+    let mut p = Parser { src: src.as_bytes(), pos: 0 };
+    p.expect(b'{')?;
+    let open = p.pos;
+    let mut last = None;
+    p.skip_ws()?;
+    if p.peek() == Some(b'}') {
+        p.pos += 1;
+    } else {
+        loop {
+            p.skip_ws()?;
+            let start = p.pos;
+            let name = p.string()?;
+            p.expect(b':')?;
+            p.skip_ws()?;
+            let value_start = p.pos;
+            p.value()?;
+            let indent = line_indent(src, start);
+            if name == key {
+                return Ok(format!("{}{}{}", &src[..value_start], indented(value, indent), &src[p.pos..]));
+            }
+            last = Some((p.pos, indent));
+            if !p.more(b'}')? {
+                break;
+            }
+        }
+    }
+    Ok(match last {
+        Some((end, indent)) => {
+            let indent = if indent.is_empty() { "  " } else { indent };
+            let new = format!("\n{indent}{member}: {}", indented(value, indent));
+            // After a comma or `//` comment that ends the last member's line.
+            let rest = &src[end..];
+            let after = rest.trim_start_matches([' ', '\t']);
+            let comma = after.starts_with(',');
+            let after = after.strip_prefix(',').unwrap_or(after).trim_start_matches([' ', '\t']);
+            let line_end = if after.starts_with("//") { after.find('\n') } else { after.starts_with('\n').then_some(0) };
+            match line_end {
+                Some(n) => {
+                    let at = end + (rest.len() - after.len()) + n;
+                    let sep = if comma { "" } else { "," };
+                    format!("{}{sep}{}{new}{}", &src[..end], &src[end..at], &src[at..])
+                }
+                None => format!("{},{new}{}", &src[..end], &src[end..]),
+            }
+        }
+        None => {
+            let close = p.pos - 1;
+            format!("{}\n  {member}: {}\n{}", &src[..open], indented(value, "  "), &src[close..])
+        }
+    })
+}
+
+/// The whitespace `pos` is indented by, if nothing else precedes it on its
+/// line.
+fn line_indent(src: &str, pos: usize) -> &str {
+    let line = src[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &src[line..pos];
+    if indent.bytes().all(|b| b == b' ' || b == b'\t') { indent } else { "" }
+}
+
+/// `value` written as a member indented by `indent`, nesting in steps of it.
+fn indented(value: &Value, indent: &str) -> String {
+    let text = value.to_string();
+    let mut lines = text.lines();
+    let mut out = lines.next().unwrap_or_default().to_owned();
+    for line in lines {
+        let body = line.trim_start_matches(' ');
+        let depth = (line.len() - body.len()) / 2;
+        out.push('\n');
+        out.push_str(&indent.repeat(depth + 1));
+        out.push_str(body);
+    }
+    out
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) -> Result<()> {
+        loop {
+            match (self.peek(), self.src.get(self.pos + 1)) {
+                (Some(b' ' | b'\t' | b'\n' | b'\r'), _) => self.pos += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while self.peek().is_some_and(|c| c != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    let Some(end) = self.src[self.pos + 2..].windows(2).position(|w| w == b"*/") else {
+                        bail!("unterminated comment at byte {}", self.pos);
+                    };
+                    self.pos += end + 4;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        self.skip_ws()?;
+        if self.peek() != Some(c) {
+            bail!("expected '{}' at byte {}", c as char, self.pos);
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// After an item: consume a `,` (and report `false` if `close` follows,
+    /// allowing a trailing comma) or the closing bracket itself.
+    fn more(&mut self, close: u8) -> Result<bool> {
+        self.skip_ws()?;
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                self.skip_ws()?;
+                if self.peek() == Some(close) {
+                    self.pos += 1;
+                    return Ok(false);
+                }
+                Ok(true)
+            }
+            Some(c) if c == close => {
+                self.pos += 1;
+                Ok(false)
+            }
+            _ => bail!("expected ',' or '{}' at byte {}", close as char, self.pos),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip_ws()?;
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_ws()?;
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Obj(members));
+                }
+                loop {
+                    self.skip_ws()?;
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    members.push((key, self.value()?));
+                    if !self.more(b'}')? {
+                        return Ok(Value::Obj(members));
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_ws()?;
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Arr(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    if !self.more(b']')? {
+                        return Ok(Value::Arr(items));
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(Value::Str),
+            Some(b't') => self.word("true", Value::Bool(true)),
+            Some(b'f') => self.word("false", Value::Bool(false)),
+            Some(b'n') => self.word("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| matches!(c, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                let text = std::str::from_utf8(&self.src[start..self.pos])?;
+                match text.parse() {
+                    Ok(n) => Ok(Value::Num(n)),
+                    Err(_) => bail!("bad number '{text}' at byte {start}"),
+                }
+            }
+            Some(c) => bail!("unexpected '{}' at byte {}", c as char, self.pos),
+            None => bail!("unexpected end of input"),
+        }
+    }
+
+    fn word(&mut self, word: &str, value: Value) -> Result<Value> {
+        if !self.src[self.pos..].starts_with(word.as_bytes()) {
+            bail!("unexpected token at byte {}", self.pos);
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        if self.peek() != Some(b'"') {
+            bail!("expected a string at byte {}", self.pos);
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while self.peek().is_some_and(|c| c != b'"' && c != b'\\') {
+                self.pos += 1;
+            }
+            out.push_str(std::str::from_utf8(&self.src[start..self.pos])?);
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    let esc = self.src.get(self.pos + 1).copied();
+                    self.pos += 2;
+                    match esc {
+                        Some(b'n') => out.push('\n'),
+                        Some(b't') => out.push('\t'),
+                        Some(b'r') => out.push('\r'),
+                        Some(b'b') => out.push('\u{8}'),
+                        Some(b'f') => out.push('\u{c}'),
+                        Some(b'u') => {
+                            let hex = self.src.get(self.pos..self.pos + 4).and_then(|h| std::str::from_utf8(h).ok());
+                            let code = hex.and_then(|h| u32::from_str_radix(h, 16).ok());
+                            out.push(code.and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                            self.pos += 4;
+                        }
+                        Some(c) => out.push(c as char),
+                        None => bail!("unterminated string"),
+                    }
+                }
+                _ => bail!("unterminated string"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jsonc() {
+        let v = parse(
+            r##"{
+                // a comment
+                "name": "x\"y", /* another */
+                "colors": { "editor.background": "#1e1e2e", },
+                "list": [1, -2.5e1, true, null],
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(v.get("name").and_then(Value::as_str), Some("x\"y"));
+        assert_eq!(v.get("colors").and_then(|c| c.get("editor.background")).and_then(Value::as_str), Some("#1e1e2e"));
+        assert_eq!(
+            v.get("list"),
+            Some(&Value::Arr(vec![Value::Num(1.0), Value::Num(-25.0), Value::Bool(true), Value::Null]))
+        );
+        assert!(parse("{\"a\": }").is_err());
+    }
+
+    #[test]
+    fn written_json_parses_back() {
+        let v = Value::obj([
+            ("name", Value::from("a \"b\"\\\n\u{1}")),
+            ("files", Value::strs(["x", "y"])),
+            ("empty", Value::Arr(Vec::new())),
+            ("none", Value::from(None::<&str>)),
+            ("n", Value::Num(2.5)),
+            ("nested", Value::obj([("ok", Value::Bool(true))])),
+        ]);
+        let text = v.to_string();
+        assert!(text.starts_with("{\n  \"name\": "), "{text}");
+        assert_eq!(parse(&text).unwrap(), v);
+    }
+
+    #[test]
+    fn set_member_keeps_the_rest() {
+        let src = "{\n    // theme\n    \"workbench.colorTheme\": \"Old\",\n    \"editor.fontSize\": 14, // big\n}\n";
+        let out = set_member(src, "workbench.colorTheme", &Value::from("New")).unwrap();
+        assert_eq!(out, src.replace("\"Old\"", "\"New\""));
+
+        let colors = Value::obj([("editor.background", Value::from("#000000"))]);
+        let out = set_member(&out, "workbench.colorCustomizations", &colors).unwrap();
+        assert!(
+            out.ends_with(
+                "14, // big\n    \"workbench.colorCustomizations\": {\n        \"editor.background\": \"#000000\"\n    }\n}\n"
+            ),
+            "{out}"
+        );
+        assert_eq!(parse(&out).unwrap().get("editor.fontSize"), Some(&Value::Num(14.0)));
+
+        assert_eq!(set_member("{}", "a", &Value::Bool(true)).unwrap(), "{\n  \"a\": true\n}");
+        assert_eq!(set_member("", "a", &Value::Null).unwrap(), "{\n  \"a\": null\n}\n");
+        assert!(set_member("[1]", "a", &Value::Null).is_err());
+    }
+}
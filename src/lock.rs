@@ -0,0 +1,89 @@
+//! Run serialization and switch debouncing.
+//!
+//! Every `set` writes a ticket to `generated/.pending` before waiting on the
+//! exclusive lock. Whoever holds the lock can then tell whether a newer
+//! switch has been requested in the meantime ("superseded") and skip work
+//! that the newer run will redo anyway.
+
+use crate::log;
+use anyhow::{Context, Result, bail};
+use rustix::{
+    fs::{FlockOperation, flock},
+    io::Errno,
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tempfile::Builder;
+
+/// Prefix of the temp files tickets are written through, in the same dir.
+pub const PENDING_TMP: &str = ".pending.tmp";
+
+/// An exclusive `flock` held until dropped. `set`, `rollback`, `reload`
+/// and `wallpaper` all take it, so they never interleave.
+pub struct Lock {
+    _file: File,
+}
+
+impl Lock {
+    /// Take the lock at `path`. If another run holds it, block until it is
+    /// released when `wait` is set, and fail right away otherwise.
+    pub fn acquire(path: &Path, wait: bool) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("open lock {}", path.display()))?;
+        match flock(&file, FlockOperation::NonBlockingLockExclusive) {
+            Ok(()) => {}
+            Err(Errno::WOULDBLOCK) if wait => {
+                log::info!("waiting for another oxidize run to finish");
+                flock(&file, FlockOperation::LockExclusive).with_context(|| format!("lock {}", path.display()))?;
+            }
+            Err(Errno::WOULDBLOCK) => bail!("another oxidize run is in progress ({} is locked)", path.display()),
+            Err(e) => return Err(e).with_context(|| format!("lock {}", path.display())),
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// This invocation's place in the queue of requested switches.
+pub struct Ticket {
+    id: String,
+    pending: PathBuf,
+}
+
+impl Ticket {
+    /// Record this run as the most recent switch request.
+    pub fn issue(pending: &Path) -> Result<Self> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        let id = format!("{}.{nanos}", std::process::id());
+
+        let dir = pending.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        // A temp file of our own: runs issue tickets before taking the lock.
+        let mut tmp = Builder::new()
+            .prefix(PENDING_TMP)
+            .tempfile_in(dir)
+            .with_context(|| format!("create temp file in {}", dir.display()))?;
+        tmp.write_all(id.as_bytes()).with_context(|| format!("write {}", tmp.path().display()))?;
+        tmp.persist(pending).with_context(|| format!("write {}", pending.display()))?;
+
+        Ok(Self {
+            id,
+            pending: pending.to_owned(),
+        })
+    }
+
+    /// True once a newer run has issued its own ticket.
+    pub fn superseded(&self) -> bool {
+        fs::read_to_string(&self.pending).is_ok_and(|latest| latest.trim() != self.id)
+    }
+}
@@ -0,0 +1,73 @@
+//! Leveled diagnostics on stderr, controlled by `-v`/`-vv`/`--quiet`.
+//!
+//! Errors are never filtered here — they propagate to `main` and are always
+//! printed. Everything else goes through the macros below.
+
+use std::{
+    process::Command,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub const QUIET: u8 = 0;
+pub const NORMAL: u8 = 1;
+pub const VERBOSE: u8 = 2;
+pub const TRACE: u8 = 3;
+
+static LEVEL: AtomicU8 = AtomicU8::new(NORMAL);
+
+/// Set the global level from the CLI flags. `--quiet` wins over `-v`.
+pub fn init(verbose: u8, quiet: bool) {
+    let level = if quiet { QUIET } else { NORMAL.saturating_add(verbose).min(TRACE) };
+    LEVEL.store(level, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn enabled(level: u8) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level
+}
+
+/// Print the exact command line about to be spawned (at `-vv`).
+pub fn command(cmd: &Command) {
+    if !enabled(TRACE) {
+        return;
+    }
+    let mut line = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        line.push(' ');
+        line.push_str(&arg.to_string_lossy());
+    }
+    eprintln!("+ {line}");
+}
+
+/// Non-fatal problem; shown unless `--quiet`.
+macro_rules! warn_ {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::NORMAL) {
+            eprintln!(
+                "{} {}",
+                $crate::term::paint($crate::term::stderr_color(), "33", "warn:"),
+                format_args!($($arg)*)
+            );
+        }
+    };
+}
+
+/// Phase-level progress; shown at `-v`.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::VERBOSE) {
+            eprintln!("{}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Fine-grained detail; shown at `-vv`.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::TRACE) {
+            eprintln!("  {}", format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use {debug, info, warn_ as warn};
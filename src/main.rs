@@ -1,131 +1,976 @@
 //! `oxidize-theme` — atomic theme switcher for Wayland desktops.
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result, bail};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::time::Duration;
 
 mod apply;
+mod busy;
+mod clipboard;
+mod color;
+mod completions;
+mod config;
+mod contrast;
 mod ctx;
+mod cvd;
+mod export;
+mod external;
+mod gc;
+mod generation;
+mod host;
+mod import;
+mod json;
+mod journal;
+mod lock;
+mod log;
+mod meta;
+mod overlay;
+mod plan;
+mod preview;
 mod render;
+mod scheme;
+mod schema;
+mod session;
+mod term;
 mod theme;
 mod transaction;
+mod undo;
 mod util;
+mod validate;
 
+use config::Config;
 use ctx::Ctx;
-use theme::Theme;
+use theme::{Theme, Variant};
 use transaction::Transaction;
 
 #[derive(Parser)]
 #[command(name = "oxidize", about = "Atomic Wayland theme switcher")]
 struct Cli {
+    /// Explain each phase (-v) and print spawned commands (-vv)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Print nothing but errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// When to use ANSI colors (`auto` honors NO_COLOR and non-terminals)
+    #[arg(long, global = true, value_enum, default_value_t = term::ColorChoice::Auto)]
+    color: term::ColorChoice,
+
+    /// Fail instead of waiting when another run is switching or reloading
+    #[arg(long, global = true)]
+    no_wait: bool,
+
     #[command(subcommand)]
     cmd: Cmd,
 }
 
 #[derive(Subcommand)]
 enum Cmd {
-    Set {
-        theme: String,
+    Set(SetArgs),
+
+    /// Reload apps without changing the theme
+    Reload,
+
+    /// Apply GNOME color-scheme and gtk-theme for the current theme
+    Gnome {
         #[arg(long)]
-        no_apply: bool,
+        no_icons: bool,
+        /// Put back the settings from before oxidize first changed them
+        #[arg(long, conflicts_with = "no_icons")]
+        restore: bool,
+    },
+
+    /// Cycle to the next wallpaper for the current theme, or pin one
+    Wallpaper {
+        #[command(subcommand)]
+        action: Option<WallpaperAction>,
+    },
+
+    /// Show the current theme and its metadata
+    Status,
+
+    /// List installed themes (`*` marks the current one)
+    List {
+        /// Show light/dark and a swatch bar of each theme's colors
         #[arg(long)]
-        no_gnome: bool,
+        detailed: bool,
+    },
+
+    /// Show a theme's palette (the current theme if none is named)
+    Preview {
+        theme: Option<String>,
+        /// Write a PNG swatch grid to FILE instead of printing
+        #[arg(long, value_name = "FILE")]
+        png: Option<std::path::PathBuf>,
+        /// Show the colors as seen with a color vision deficiency
+        #[arg(long, value_enum)]
+        cvd: Option<color::Cvd>,
+    },
+
+    /// Check that themes load (all themes if none are named)
+    Validate {
+        themes: Vec<String>,
+        /// Also expand every template and list keys each theme cannot satisfy
         #[arg(long)]
-        no_icons: bool,
+        against_templates: bool,
+    },
+
+    /// Report undefined and unused variables and colliding template outputs
+    Lint,
+
+    /// Print a variable of the current theme (`bg` also finds `palette_bg`)
+    Get {
+        key: String,
+        /// Copy the value to the Wayland clipboard instead of printing it
         #[arg(long)]
-        no_reload: bool,
+        copy: bool,
+    },
+
+    /// Time the render pipeline stages over the installed templates (jinja ones excluded)
+    Bench {
+        /// Theme to render (defaults to the current theme)
+        theme: Option<String>,
+        #[arg(short = 'n', long, default_value_t = 100)]
+        iterations: u32,
+    },
+
+    /// Export the current palette for tools outside the template engine
+    Export {
+        #[arg(value_enum)]
+        format: export::Format,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Create a theme from another tool's color scheme
+    Import {
+        #[arg(value_enum)]
+        format: import::Format,
+        /// Name of the new theme
+        name: String,
+        /// Scheme file (defaults to the tool's usual location, if it has one)
+        path: Option<std::path::PathBuf>,
+        /// Overwrite the palette of an existing theme
         #[arg(long)]
-        no_wallpaper: bool,
+        force: bool,
     },
 
-    /// Reload apps without changing the theme
-    Reload,
+    /// Create a light/dark theme from one seed color
+    Generate {
+        name: String,
+        /// `#rrggbb`, or a GNOME accent color name (blue, teal, green, ...)
+        #[arg(long)]
+        seed: String,
+        /// Overwrite the palettes of an existing theme
+        #[arg(long)]
+        force: bool,
+    },
 
-    /// Apply GNOME color-scheme and gtk-theme for the current theme
-    Gnome {
+    /// Show past theme switches, oldest first
+    History {
+        /// Show only the last N switches
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Only switches at or after WHEN (`2026-10-13`, `2026-10-13T14:00`)
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+        /// Show only the switch that was in effect at WHEN
+        #[arg(long, value_name = "WHEN", conflicts_with = "since")]
+        at: Option<String>,
+    },
+
+    /// List generations of the live tree (`*` marks the live one)
+    Generations,
+
+    /// Point the live tree back at an earlier generation and reload apps
+    Rollback {
+        /// Generation number (default: the one before the live one)
+        #[arg(long, value_name = "N")]
+        to: Option<u32>,
+        /// Only switch the live tree; run no apply steps
+        #[arg(long)]
+        no_apply: bool,
+        /// Leave the GNOME icon theme alone
+        #[arg(long)]
+        no_icons: bool,
+    },
+
+    /// Undo the last switch: the live tree, current theme, wallpaper and
+    /// GNOME settings go back to how they were before it
+    Undo {
+        /// Restore files and links only; run no apply steps
+        #[arg(long)]
+        no_apply: bool,
+        /// Leave the GNOME icon theme alone
         #[arg(long)]
         no_icons: bool,
     },
 
-    /// Cycle to the next wallpaper for the current theme
-    Wallpaper,
+    /// Remove staging dirs left by crashed runs and prune old generations
+    Gc,
+
+    /// Print shell completions (shell detected from $SHELL if omitted)
+    Completions {
+        #[arg(value_enum)]
+        shell: Option<clap_complete::Shell>,
+        /// Write them into the shell's per-user completion directory
+        #[arg(long)]
+        install: bool,
+    },
+}
+
+#[derive(clap::Args)]
+struct SetArgs {
+    /// Theme name, or an `[alias]` from oxidize.toml (default: the theme
+    /// in this machine's `hosts/<hostname>/host.toml`)
+    theme: Option<String>,
+    /// Variant of a theme that has colors-light.toml / colors-dark.toml
+    /// (default: the `[variant]` schedule, then the theme's own default)
+    #[arg(long, value_enum)]
+    variant: Option<Variant>,
+    #[arg(long)]
+    no_apply: bool,
+    #[arg(long)]
+    no_gnome: bool,
+    /// Don't apply the KDE Plasma color scheme
+    #[arg(long)]
+    no_kde: bool,
+    #[arg(long)]
+    no_icons: bool,
+    #[arg(long)]
+    no_reload: bool,
+    #[arg(long)]
+    no_wallpaper: bool,
+    /// Don't link rofi/wofi/fuzzel themes into their config dirs
+    #[arg(long)]
+    no_launcher: bool,
+    /// Run no scripts from hooks/pre-set.d and hooks/post-set.d
+    #[arg(long)]
+    no_hooks: bool,
+    /// Warn about processes holding files open in the old live tree
+    #[arg(long)]
+    check_open: bool,
+    /// Wait up to SECS for those processes to close their files
+    #[arg(long, value_name = "SECS")]
+    wait_open: Option<u64>,
+    /// Apply a named preset from oxidize.toml
+    #[arg(long)]
+    preset: Option<String>,
+    /// Read back apply results and fail if any step didn't take
+    #[arg(long, conflicts_with = "minimal")]
+    verify: bool,
+    /// Render, commit, and update the background link, but run no
+    /// external commands and signal no processes
+    #[arg(long)]
+    minimal: bool,
+    /// Wait MS before starting; if another switch is requested meanwhile,
+    /// this one is dropped
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    debounce: u64,
+    /// Show what would change and ask before committing and applying
+    #[arg(long)]
+    confirm: bool,
+    /// Print everything the switch would do as JSON, and do none of it
+    #[arg(long, conflicts_with = "confirm")]
+    plan: bool,
+    /// Abort if any template references an undefined variable or a
+    /// `[contrast]` pair is below the required level
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Subcommand)]
+enum WallpaperAction {
+    /// Cycle to the next wallpaper (default)
+    Next,
+    /// Cycle to the previous wallpaper
+    Prev,
+    /// Pin a specific image
+    Set { path: std::path::PathBuf },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    log::init(cli.verbose, cli.quiet);
+    term::init(cli.color);
     let ctx = Ctx::new().context("initialise context")?;
+    let config = Config::load(&ctx.config_file).context("load config")?;
+    let wait = !cli.no_wait;
 
     match cli.cmd {
-        Cmd::Set {
-            theme,
-            no_apply,
-            no_gnome,
-            no_icons,
-            no_reload,
-            no_wallpaper,
-        } => cmd_set(
-            &ctx,
-            &theme,
-            apply::ApplyFlags {
-                no_apply,
-                no_gnome,
-                no_icons,
-                no_reload,
-                no_wallpaper,
-            },
-        ),
+        Cmd::Set(args) => cmd_set(&ctx, &config, args, wait),
 
         Cmd::Reload => {
-            apply::reload::run(&ctx);
+            let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+            apply::reload::run(&ctx, &config.waybar);
             Ok(())
         }
 
-        Cmd::Gnome { no_icons } => {
-            let theme = current_theme(&ctx)?;
+        Cmd::Gnome { restore: true, .. } => {
+            let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+            let baseline = undo::load_baseline(&ctx)?.context("no original GNOME settings were recorded")?;
+            for (key, value) in &baseline.gsettings {
+                apply::gnome::gsettings_set(apply::gnome::SCHEMA, key, value);
+            }
+            Ok(())
+        }
+
+        Cmd::Gnome { no_icons, .. } => {
+            let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+            let theme = current_theme(&ctx, &config)?;
+            undo::keep_baseline(&ctx).context("record original GNOME settings")?;
             apply::gnome::run(&theme, no_icons);
             Ok(())
         }
 
-        Cmd::Wallpaper => {
-            let theme = current_theme(&ctx)?;
-            apply::wallpaper::run(&ctx, &theme)
+        Cmd::Wallpaper { action } => {
+            let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+            match action.unwrap_or(WallpaperAction::Next) {
+                WallpaperAction::Next => {
+                    let theme = current_theme(&ctx, &config)?;
+                    apply::wallpaper::cycle(&ctx, &theme, apply::wallpaper::Direction::Next)
+                }
+                WallpaperAction::Prev => {
+                    let theme = current_theme(&ctx, &config)?;
+                    apply::wallpaper::cycle(&ctx, &theme, apply::wallpaper::Direction::Prev)
+                }
+                WallpaperAction::Set { path } => apply::wallpaper::set(&ctx, &path),
+            }
+        }
+
+        Cmd::Status => cmd_status(&ctx, &config),
+
+        Cmd::History { count, since, at } => {
+            let entries = journal::read(&ctx.journal_file)?;
+            let shown: Vec<&journal::Entry> = match (since, at) {
+                (_, Some(at)) => {
+                    let at = journal::parse_when(&at)?;
+                    entries.iter().rev().find(|e| e.time <= at).into_iter().collect()
+                }
+                (Some(since), None) => {
+                    let since = journal::parse_when(&since)?;
+                    entries.iter().filter(|e| e.time >= since).collect()
+                }
+                (None, None) => entries.iter().collect(),
+            };
+            for entry in &shown[shown.len().saturating_sub(count)..] {
+                println!("{entry}");
+            }
+            Ok(())
+        }
+
+        Cmd::Generations => {
+            let live = generation::current(&ctx);
+            for g in generation::list(&ctx) {
+                let marker = if Some(g.number) == live { '*' } else { ' ' };
+                let created = g
+                    .created
+                    .and_then(|t| jiff::Timestamp::try_from(t).ok())
+                    .map(|t| t.to_zoned(jiff::tz::TimeZone::system()).strftime("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!("{marker} {:>4}  {created}  {}", g.number, g.theme().unwrap_or_else(|| "?".to_owned()));
+            }
+            Ok(())
+        }
+
+        Cmd::Rollback { to, no_apply, no_icons } => {
+            let mut flags = apply::ApplyFlags { no_apply, no_icons, ..Default::default() };
+            flags.detect_desktop();
+            cmd_rollback(&ctx, &config, to, flags, wait)
+        }
+
+        Cmd::Gc => {
+            let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+            let report = gc::run(&ctx, config.keep_generations.unwrap_or(generation::DEFAULT_KEEP));
+            println!(
+                "removed {} staging dir(s), {} generation(s), {} leftover file(s)",
+                report.stages, report.generations, report.leftovers
+            );
+            Ok(())
+        }
+
+        Cmd::Undo { no_apply, no_icons } => {
+            let mut flags = apply::ApplyFlags { no_apply, no_icons, ..Default::default() };
+            flags.detect_desktop();
+            cmd_undo(&ctx, &config, flags, wait)
+        }
+
+        Cmd::List { detailed } => cmd_list(&ctx, detailed),
+
+        Cmd::Preview { theme, png, cvd } => {
+            let theme = match theme {
+                Some(name) => load_theme(&ctx, &config, &name, None)?,
+                None => current_theme(&ctx, &config)?,
+            };
+            let vars = match cvd {
+                Some(kind) => cvd::simulate(&theme.vars, kind),
+                None => theme.vars.clone(),
+            };
+            match png {
+                Some(path) => preview::write_png(&vars, &path),
+                None => {
+                    preview::print(&vars);
+                    for warning in cvd::check(&config.cvd, &theme.vars) {
+                        log::warn!("{warning}");
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        Cmd::Validate { themes, against_templates } => {
+            let checks = validate::Checks {
+                against_templates,
+                contrast: config.contrast.clone(),
+                cvd: config.cvd.clone(),
+                schema: schema::Schema::load(&ctx.schema_file)?,
+            };
+            let failed = validate::run(&ctx, &themes, checks, |name| load_theme(&ctx, &config, name, None))?;
+            anyhow::ensure!(failed == 0, "{failed} theme(s) failed validation");
+            Ok(())
+        }
+
+        Cmd::Lint => {
+            let themes = validate::all_themes(&ctx)?
+                .iter()
+                .map(|name| load_theme(&ctx, &config, name, None))
+                .collect::<Result<Vec<_>>>()?;
+            let findings = render::lint::run(&ctx, &themes)?;
+            anyhow::ensure!(findings == 0, "{findings} lint finding(s)");
+            Ok(())
+        }
+
+        Cmd::Get { key, copy } => {
+            let theme = current_theme(&ctx, &config)?;
+            let (name, value) = render::engine::resolve_key(&theme.vars, &key)
+                .with_context(|| format!("no variable '{key}' in theme '{}'", theme.name))?;
+            if copy {
+                clipboard::copy(value)?;
+                log::info!("copied {name} = {value}");
+            } else {
+                println!("{value}");
+            }
+            Ok(())
         }
+
+        Cmd::Bench { theme, iterations } => render::bench::run(&ctx, iterations, || match &theme {
+            Some(name) => load_theme(&ctx, &config, name, None),
+            None => current_theme(&ctx, &config),
+        }),
+
+        Cmd::Export { format, output } => {
+            let theme = current_theme(&ctx, &config)?;
+            let rendered = export::render(format, &theme.vars);
+            match output {
+                Some(path) => std::fs::write(&path, rendered).with_context(|| format!("write {}", path.display())),
+                None => {
+                    print!("{rendered}");
+                    Ok(())
+                }
+            }
+        }
+
+        Cmd::Import { format, name, path, force } => {
+            let path = path
+                .or_else(|| format.default_path())
+                .context("this format has no default location; pass the scheme file")?;
+            let scheme = import::read(format, &path)?;
+            import::write(&ctx.data_dir, &name, &scheme, &path.display().to_string(), force)?;
+            log::info!("created theme '{name}' from {}", path.display());
+            Ok(())
+        }
+
+        Cmd::Generate { name, seed, force } => {
+            let seed = scheme::parse_seed(&seed)?;
+            scheme::write(&ctx.data_dir, &name, seed, force)?;
+            log::info!("created theme '{name}' from {}", seed.to_hex());
+            Ok(())
+        }
+
+        Cmd::Completions { shell, install } => completions::run(Cli::command(), shell, install),
     }
 }
 
-fn cmd_set(ctx: &Ctx, theme_name: &str, flags: apply::ApplyFlags) -> Result<()> {
-    let theme = Theme::load(&ctx.data_dir, theme_name).context("load theme")?;
+fn cmd_set(ctx: &Ctx, config: &Config, args: SetArgs, wait: bool) -> Result<()> {
+    let mut flags = apply::ApplyFlags {
+        no_apply: args.no_apply,
+        no_gnome: args.no_gnome,
+        no_kde: args.no_kde,
+        no_icons: args.no_icons,
+        no_reload: args.no_reload,
+        no_wallpaper: args.no_wallpaper,
+        no_launcher: args.no_launcher,
+        no_hooks: args.no_hooks,
+        minimal: args.minimal,
+    };
+    if let Some(name) = &args.preset {
+        let preset = config
+            .presets
+            .get(name)
+            .with_context(|| format!("unknown preset '{name}'"))?;
+        for step in &preset.skip {
+            flags.skip(step).with_context(|| format!("preset '{name}'"))?;
+        }
+    }
+    flags.detect_desktop();
+    let name = match args.theme {
+        Some(name) => name,
+        None => host::default_theme(&ctx.host_dir)?
+            .with_context(|| format!("no theme given and {} sets none", ctx.host_dir.join("host.toml").display()))?,
+    };
+    let open_policy = match (args.wait_open, args.check_open) {
+        (Some(secs), _) => busy::Policy::Wait(Duration::from_secs(secs)),
+        (None, true) => busy::Policy::Warn,
+        (None, false) => busy::Policy::Ignore,
+    };
+
+    // Debounce: queue behind any running switch, and bow out if a newer
+    // request arrives before we get to go. A plan never supersedes a switch.
+    let ticket = (!args.plan)
+        .then(|| lock::Ticket::issue(&ctx.pending_file))
+        .transpose()
+        .context("record switch request")?;
+    let superseded = || ticket.as_ref().is_some_and(lock::Ticket::superseded);
+    std::thread::sleep(Duration::from_millis(args.debounce));
+    let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+    if superseded() {
+        log::info!("superseded by a newer switch; skipping '{name}'");
+        return Ok(());
+    }
+    let keep = config.keep_generations.unwrap_or(generation::DEFAULT_KEEP);
+    if !args.plan {
+        let report = gc::run(ctx, keep);
+        if report.stages > 0 {
+            log::info!("removed {} staging dir(s) left by an earlier run", report.stages);
+        }
+    }
+
+    let variant = args.variant.or_else(|| config.scheduled_variant(jiff::Zoned::now().time()));
+    let theme = load_theme(ctx, config, &name, variant).context("load theme")?;
+    log::info!("loaded theme '{}' ({} variables)", theme.name, theme.vars.len());
+    let output_themes = config
+        .outputs
+        .iter()
+        .map(|(output, o)| {
+            load_theme(ctx, config, &o.theme, variant)
+                .map(|t| (output.clone(), t))
+                .with_context(|| format!("load theme '{}' for output {output}", o.theme))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let low_contrast: Vec<String> = std::iter::once(&theme)
+        .chain(output_themes.iter().map(|(_, t)| t))
+        .flat_map(|t| contrast::check(&config.contrast, &t.vars).into_iter().map(|c| format!("{}: {c}", t.name)))
+        .collect();
+    if args.strict && !low_contrast.is_empty() {
+        bail!("insufficient contrast (strict mode):\n  {}", low_contrast.join("\n  "));
+    }
+    for c in &low_contrast {
+        log::warn!("{c}");
+    }
+
+    let hook_timeout = config.hook_timeout.unwrap_or(apply::hooks::DEFAULT_TIMEOUT);
+    let run_hooks = !flags.no_hooks && !flags.minimal;
+    if run_hooks && !args.plan {
+        apply::hooks::run(ctx, apply::hooks::Stage::PreSet, &theme, hook_timeout).context("switch aborted")?;
+    }
 
     // Stage → commit (atomic rename).
     let txn = Transaction::begin(ctx).context("begin transaction")?;
-    render::render_all(ctx, &theme.root, txn.stage(), &theme.vars).context("render templates")?;
+    log::debug!("staging into {}", txn.stage().display());
+    log::info!("rendering templates");
+    let render_opts = render::Options { strict: args.strict };
+    let mut externals =
+        render::render_all(ctx, &theme, txn.stage(), &ctx.live_dir, render_opts).context("render templates")?;
+    log::info!("staging assets");
     stage_assets(&theme, txn.stage()).context("stage assets")?;
-    txn.commit().context("commit transaction")?;
-
+    export::stage(&theme.vars, txn.stage()).context("stage palette exports")?;
+    externals.extend(apply::vscode::stage(ctx, &theme, &config.vscode, txn.stage()).context("stage VS Code settings")?);
+    externals.extend(apply::firefox::stage(ctx, config.firefox, txn.stage()).context("stage Firefox profile files")?);
+    for (output, out_theme) in &output_themes {
+        log::info!("rendering '{}' for output {output}", out_theme.name);
+        let dir = txn.stage().join("outputs").join(output);
+        let prev = ctx.live_dir.join("outputs").join(output);
+        let ignored = render::render_all(ctx, out_theme, &dir, &prev, render_opts)
+            .with_context(|| format!("render templates for output {output}"))?;
+        for ext in ignored {
+            log::debug!("output {output}: not linking {} (global theme only)", ext.target.display());
+        }
+        stage_assets(out_theme, &dir).with_context(|| format!("stage assets for output {output}"))?;
+    }
+    busy::guard(&ctx.live_dir, open_policy);
+    let changes = txn.changes();
+    log::info!("{} file(s) changed", changes.len());
+    for change in &changes {
+        log::debug!("{change}");
+    }
+    if args.plan {
+        let plan = plan::Plan {
+            theme: &theme,
+            changes: &changes,
+            externals: &externals,
+            flags: &flags,
+            compositor: config.compositor,
+            waybar: &config.waybar,
+            stage: txn.stage(),
+            neovim_command: config.neovim_command(),
+            emacs: config.emacs.as_ref(),
+            kde: &config.kde,
+            hooks: run_hooks,
+        };
+        println!("{}", plan.to_json(ctx));
+        return Ok(());
+    }
+    if args.confirm && !confirm_switch(&theme, &changes, &flags)? {
+        log::info!("switch cancelled; nothing was changed");
+        return Ok(());
+    }
     // Persist theme name outside the atomic tree (intentional).
-    std::fs::write(&ctx.current_theme_file, format!("{}\n", theme.name))
-        .context("write current.theme")?;
+    // Name on the first line; the variant, if any, on the second.
+    let record = match theme.variant {
+        Some(v) => format!("{}\n{}\n", theme.name, v.as_str()),
+        None => format!("{}\n", theme.name),
+    };
+    let snapshot = undo::Snapshot::capture(ctx, !flags.no_apply && !flags.minimal && !flags.no_gnome);
+    log::info!("committing {}", ctx.live_dir.display());
+    let number = txn.commit(&record, keep, !config.no_fsync).context("commit transaction")?;
+    log::debug!("live is now generation {number}");
+    // Right after the swap and still under the lock, so current.theme never
+    // names a theme whose tree isn't live.
+    util::write_atomic(&ctx.current_theme_file, &record, !config.no_fsync).context("write current.theme")?;
+    undo::save(ctx, number, &snapshot).context("record state for undo")?;
+    render::link_externals(ctx, &externals);
+    external::record(ctx, number, &externals).context("record files outside the live tree")?;
+    external::write_all(ctx, &externals).context("write files outside the live tree")?;
 
-    if flags.no_apply {
-        return Ok(());
+    let mut entry = journal::Entry::from_records(snapshot.current_theme.as_deref(), &record);
+    entry.generation = Some(number);
+    let result = (|| {
+        if flags.no_apply {
+            log::info!("skipping apply steps (--no-apply)");
+            entry.step("apply", "skipped");
+            return Ok(());
+        }
+
+        if superseded() {
+            log::info!("superseded by a newer switch; leaving apply steps to it");
+            entry.step("apply", "superseded");
+            return Ok(());
+        }
+
+        // Launchers read their theme on every start, so linking is all they need.
+        if !flags.no_launcher {
+            apply::launcher::run(ctx);
+            entry.step("launcher", "ok");
+        }
+
+        if flags.minimal {
+            if !flags.no_wallpaper {
+                log::info!("updating background link (--minimal)");
+                apply::wallpaper::advance(ctx, &theme, apply::wallpaper::Direction::Next)
+                    .context("update background link")?;
+                entry.step("wallpaper", "linked");
+            }
+            return Ok(());
+        }
+
+        let watch = args.verify.then(|| apply::verify::Watch::before(ctx));
+
+        apply_all(ctx, config, &theme, &flags, &changes, &mut entry)?;
+        if !flags.no_wallpaper {
+            log::info!("applying wallpaper");
+            let result = if output_themes.is_empty() {
+                apply::wallpaper::run(ctx, &theme)
+            } else {
+                apply::wallpaper::run_outputs(ctx, &theme, &output_themes)
+            };
+            match result {
+                Ok(()) => entry.step("wallpaper", "ok"),
+                Err(e) => {
+                    log::warn!("wallpaper apply failed: {e:#}");
+                    entry.step("wallpaper", format!("failed: {e:#}"));
+                }
+            }
+        }
+        if run_hooks {
+            log::info!("running post-set hooks");
+            match apply::hooks::run(ctx, apply::hooks::Stage::PostSet, &theme, hook_timeout) {
+                Ok(()) => entry.step("hooks", "ok"),
+                Err(e) => {
+                    log::warn!("{e:#}");
+                    entry.step("hooks", format!("failed: {e:#}"));
+                }
+            }
+        }
+
+        if let Some(watch) = watch {
+            log::info!("verifying apply steps");
+            let problems = watch.check(ctx, &theme, &flags);
+            for p in &problems {
+                log::warn!("{p}");
+            }
+            entry.step("verify", if problems.is_empty() { "ok".to_owned() } else { problems.join(", ") });
+            anyhow::ensure!(problems.is_empty(), "{} apply check(s) failed", problems.len());
+        }
+
+        Ok(())
+    })();
+
+    entry.wallpaper = std::fs::read_link(&ctx.background_link).ok();
+    if let Err(e) = journal::append(&ctx.journal_file, &entry) {
+        log::warn!("{e:#}");
     }
+    result
+}
 
-    // Apply steps are best-effort: warn on failure, never abort.
+/// The desktop and app steps `set`, `rollback` and `undo` share, for `theme`
+/// once its tree is live. `changes` are the files the switch changed.
+///
+/// Each step is best-effort: it warns on failure and never aborts.
+fn apply_all(
+    ctx: &Ctx,
+    config: &Config,
+    theme: &Theme,
+    flags: &apply::ApplyFlags,
+    changes: &[transaction::Change],
+    entry: &mut journal::Entry,
+) -> Result<()> {
     if !flags.no_gnome {
-        apply::gnome::run(&theme, flags.no_icons);
+        log::info!("applying GNOME settings");
+        undo::keep_baseline(ctx).context("record original GNOME settings")?;
+        apply::gnome::run(theme, flags.no_icons);
+        apply::gtk::run(ctx, theme);
+        entry.step("gnome", "ok");
+    }
+    if !flags.no_kde {
+        log::info!("applying KDE color scheme");
+        apply::kde::run(ctx, theme, &config.kde);
+        entry.step("kde", "ok");
     }
     if !flags.no_reload {
-        apply::reload::run(ctx);
+        log::info!("reloading apps");
+        apply::reload::run(ctx, &config.waybar);
+        apply::compositor::run(theme, &config.compositor);
+        apply::tmux::run(ctx, theme);
+        apply::fish::run(ctx, theme);
+        apply::neovim::run(config.neovim_command());
+        apply::emacs::run(ctx, theme, config.emacs.as_ref());
+        apply::qutebrowser::run(ctx);
+        apply::xresources::run(ctx);
+        if apply::bat::changed(changes) {
+            apply::bat::run(ctx);
+        }
+        entry.step("reload", "ok");
+    }
+    Ok(())
+}
+
+/// Print the staged changes and the GNOME settings the switch would touch,
+/// then ask whether to go ahead.
+fn confirm_switch(theme: &Theme, changes: &[transaction::Change], flags: &apply::ApplyFlags) -> Result<bool> {
+    println!("Switching to '{}':", theme.name);
+    if changes.is_empty() {
+        println!("  no file changes");
     }
-    if !flags.no_wallpaper {
-        if let Err(e) = apply::wallpaper::run(ctx, &theme) {
-            eprintln!("warn: wallpaper apply failed: {e:#}");
+    for change in changes {
+        println!("  {change}");
+    }
+    if !flags.no_apply && !flags.minimal && !flags.no_gnome {
+        for (key, current, new) in apply::gnome::pending(theme, flags.no_icons) {
+            println!("  gsettings {key}: {current} -> {new}");
         }
     }
+    term::confirm("Apply?").context("read confirmation")
+}
+
+fn cmd_rollback(ctx: &Ctx, config: &Config, to: Option<u32>, flags: apply::ApplyFlags, wait: bool) -> Result<()> {
+    let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+    let gens = generation::list(ctx);
+    let live = generation::current(ctx);
+    let target = match to {
+        Some(n) => gens.iter().find(|g| g.number == n),
+        None => gens.iter().rev().find(|g| live.is_none_or(|l| g.number < l)),
+    };
+    let Some(target) = target else {
+        match to {
+            Some(n) => bail!("generation {n} does not exist (see `oxidize generations`)"),
+            None => bail!("no generation older than the live one"),
+        }
+    };
+    let record = target.record.clone().context("generation has no theme record")?;
 
+    log::info!("rolling back to generation {}", target.number);
+    let changes = transaction::diff(&ctx.live_dir, &target.path);
+    switch_generation(ctx, live, target.number, !config.no_fsync)?;
+    let previous = std::fs::read_to_string(&ctx.current_theme_file).ok();
+    util::write_atomic(&ctx.current_theme_file, &record, !config.no_fsync).context("write current.theme")?;
+
+    let mut entry = journal::Entry::from_records(previous.as_deref(), &record);
+    entry.generation = Some(target.number);
+    entry.step("rollback", "ok");
+    if flags.no_apply {
+        entry.step("apply", "skipped");
+    } else {
+        let theme = current_theme(ctx, config)?;
+        apply_all(ctx, config, &theme, &flags, &changes, &mut entry)?;
+    }
+    entry.wallpaper = std::fs::read_link(&ctx.background_link).ok();
+    if let Err(e) = journal::append(&ctx.journal_file, &entry) {
+        log::warn!("{e:#}");
+    }
     Ok(())
 }
 
+/// Point `live` at generation `number` and copy its external files out. If
+/// the copies fail, `live` goes back to `from`.
+fn switch_generation(ctx: &Ctx, from: Option<u32>, number: u32, fsync: bool) -> Result<()> {
+    generation::point_live(ctx, number)?;
+    if let Err(e) = external::restore_generation(ctx, number) {
+        if let Some(from) = from {
+            generation::point_live(ctx, from)?;
+        }
+        return Err(e).context("restore files outside the live tree (switch undone)");
+    }
+    if fsync {
+        util::fsync_dir(&ctx.generated_dir)?;
+    }
+    util::symlink_force(&ctx.live_dir, &ctx.current_link).context("update current symlink")
+}
+
+fn cmd_undo(ctx: &Ctx, config: &Config, flags: apply::ApplyFlags, wait: bool) -> Result<()> {
+    let _lock = lock::Lock::acquire(&ctx.lock_file, wait)?;
+    let live = generation::current(ctx).context("nothing to undo: no switch has been made yet")?;
+    let snapshot = undo::load(ctx, live)?
+        .with_context(|| format!("nothing to undo: no state was recorded before generation {live}"))?;
+    let previous = snapshot
+        .generation
+        .with_context(|| format!("nothing to undo: generation {live} was the first one"))?;
+
+    log::info!("undoing generation {live}, back to {previous}");
+    let from = std::fs::read_to_string(&ctx.current_theme_file).ok();
+    let changes = transaction::diff(&ctx.live_dir, &ctx.generated_dir.join(format!("gen-{previous}")));
+    switch_generation(ctx, Some(live), previous, !config.no_fsync)?;
+    undo::restore_current_theme(ctx, &snapshot, !config.no_fsync)?;
+    apply::wallpaper::restore(ctx, snapshot.background.as_deref(), !flags.no_apply)?;
+
+    let mut entry = journal::Entry::from_records(from.as_deref(), snapshot.current_theme.as_deref().unwrap_or_default());
+    entry.generation = Some(previous);
+    entry.step("undo", "ok");
+    if flags.no_apply {
+        entry.step("apply", "skipped");
+    } else {
+        let theme = current_theme(ctx, config)?;
+        // The GNOME settings go back to the recorded values rather than the
+        // theme's, after the GTK detour so apps reload the stylesheets.
+        if !flags.no_gnome {
+            apply::gtk::run(ctx, &theme);
+        }
+        let restore = snapshot.gsettings.iter().filter(|(key, _)| !(flags.no_icons && key == "icon-theme"));
+        for (key, value) in restore {
+            apply::gnome::gsettings_set(apply::gnome::SCHEMA, key, value);
+        }
+        if !snapshot.gsettings.is_empty() {
+            entry.step("gnome", "ok");
+        }
+        apply_all(ctx, config, &theme, &apply::ApplyFlags { no_gnome: true, ..flags }, &changes, &mut entry)?;
+    }
+    entry.wallpaper = snapshot.background.clone();
+    if let Err(e) = journal::append(&ctx.journal_file, &entry) {
+        log::warn!("{e:#}");
+    }
+    Ok(())
+}
+
+fn cmd_list(ctx: &Ctx, detailed: bool) -> Result<()> {
+    let current = std::fs::read_to_string(&ctx.current_theme_file).unwrap_or_default();
+    let current = current.lines().next().unwrap_or_default().trim();
+
+    let names = validate::all_themes(ctx)?;
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+
+    for name in &names {
+        let marker = if name == current { '*' } else { ' ' };
+        if !detailed {
+            println!("{marker} {name}");
+            continue;
+        }
+        match Theme::load(&ctx.theme_dirs, name, None) {
+            Ok(theme) => {
+                let glyph = if theme.is_light { '☀' } else { '☾' };
+                let bar = swatch_bar(&theme);
+                let mut notes = Vec::new();
+                notes.extend(theme.display_name.clone());
+                if !theme.tags.is_empty() {
+                    notes.push(format!("[{}]", theme.tags.join(", ")));
+                }
+                notes.extend(theme.parent.as_ref().map(|p| format!("(extends {p})")));
+                let notes = if notes.is_empty() { String::new() } else { format!("  {}", notes.join(" ")) };
+                println!("{marker} {glyph} {name:width$}  {bar}{notes}");
+            }
+            Err(e) => println!("{marker} ? {name:width$}  (error: {e:#})"),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_status(ctx: &Ctx, config: &Config) -> Result<()> {
+    let theme = current_theme(ctx, config)?;
+    let variant = if theme.is_light { "light" } else { "dark" };
+    let variant = match theme.variant {
+        Some(_) => format!("{variant} (of {})", Theme::variants(&theme.root).iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ")),
+        None => variant.to_owned(),
+    };
+    let mut rows = vec![("theme", theme.name.clone())];
+    rows.extend(theme.display_name.clone().map(|n| ("name", n)));
+    rows.push(("variant", variant));
+    rows.extend(theme.author.clone().map(|a| ("author", a)));
+    rows.extend(theme.description.clone().map(|d| ("description", d)));
+    if !theme.tags.is_empty() {
+        rows.push(("tags", theme.tags.join(", ")));
+    }
+    let aliases = config.aliases_of(&theme.name);
+    if !aliases.is_empty() {
+        rows.push(("aliases", aliases.join(", ")));
+    }
+    rows.extend(theme.parent.clone().map(|p| ("extends", p)));
+    rows.extend(theme.icon_theme.clone().map(|i| ("icons", i)));
+    rows.extend(theme.backgrounds_dir.as_ref().map(|d| ("wallpapers", d.display().to_string())));
+    if !theme.overlays.is_empty() {
+        rows.push(("overlays", theme.overlays.join(", ")));
+    }
+    for (label, value) in rows {
+        println!("{:<12} {value}", format!("{label}:"));
+    }
+    Ok(())
+}
+
+/// The theme's main colors (bg, fg, accent first) as a row of swatches.
+fn swatch_bar(theme: &Theme) -> String {
+    const MAX: usize = 12;
+    let mut colors: Vec<color::Rgb> = ["bg", "fg", "accent", "secondary"]
+        .into_iter()
+        .filter_map(|role| render::engine::resolve_key(&theme.vars, role))
+        .filter_map(|(_, v)| color::Rgb::parse(v))
+        .collect();
+
+    let mut rest: Vec<(&String, &String)> = theme.vars.iter().filter(|(_, v)| v.starts_with('#')).collect();
+    rest.sort();
+    for c in rest.into_iter().filter_map(|(_, v)| color::Rgb::parse(v)) {
+        if colors.len() >= MAX {
+            break;
+        }
+        if !colors.contains(&c) {
+            colors.push(c);
+        }
+    }
+
+    let sep = if term::stdout_color() { "" } else { " " };
+    colors.into_iter().map(term::swatch).collect::<Vec<_>>().join(sep)
+}
+
 /// Read the current theme name from disk and load it.
-fn current_theme(ctx: &Ctx) -> Result<Theme> {
+fn current_theme(ctx: &Ctx, config: &Config) -> Result<Theme> {
     let raw = match std::fs::read_to_string(&ctx.current_theme_file) {
         Ok(s) => s,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
@@ -133,24 +978,68 @@ fn current_theme(ctx: &Ctx) -> Result<Theme> {
             return Err(e).with_context(|| format!("read {}", ctx.current_theme_file.display()));
         }
     };
-    let name = raw.trim();
+    // Without current.theme, the live generation still knows what it holds.
+    let raw = match raw.trim() {
+        "" => generation::current(ctx)
+            .and_then(|live| std::fs::read_to_string(generation::sidecar(&ctx.generated_dir, live, "theme")).ok())
+            .unwrap_or_default(),
+        _ => raw,
+    };
+    let mut lines = raw.lines().map(str::trim);
+    let name = lines.next().unwrap_or_default();
+    let variant = lines.next().and_then(Variant::parse);
+
+    // A machine that has never switched falls back to its host default.
+    let name = match name {
+        "" => host::default_theme(&ctx.host_dir)?.with_context(|| {
+            format!("current theme is not set ({})", ctx.current_theme_file.display())
+        })?,
+        name => name.to_owned(),
+    };
 
-    anyhow::ensure!(
-        !name.is_empty(),
-        "current theme is not set ({})",
-        ctx.current_theme_file.display()
-    );
+    load_theme(ctx, config, &name, variant).context("load current theme")
+}
 
-    Theme::load(&ctx.data_dir, name).context("load current theme")
+/// Load a theme (or the one an alias names), merge this host's variables
+/// and any seasonal overlays active today, and add the derived and built-in
+/// variables.
+fn load_theme(ctx: &Ctx, config: &Config, name: &str, variant: Option<Variant>) -> Result<Theme> {
+    let name = config.resolve_alias(name);
+    let mut theme = Theme::load(&ctx.theme_dirs, name, variant)?;
+    host::apply(&ctx.host_dir, &mut theme).context("apply host overrides")?;
+    theme.overlays = overlay::apply(&ctx.overlays_dir, &mut theme.vars, overlay::today()).context("apply overlays")?;
+    if !theme.overlays.is_empty() {
+        log::info!("overlays active today: {}", theme.overlays.join(", "));
+    }
+    // The theme's own roles must resolve; config defaults only where they can.
+    render::rules::apply_roles(&theme.roles, &mut theme.vars, true).with_context(|| format!("theme '{name}'"))?;
+    let defaults = config.roles.iter().filter(|(r, _)| !theme.roles.contains_key(*r));
+    render::rules::apply_roles(&defaults.map(|(r, s)| (r.clone(), s.clone())).collect(), &mut theme.vars, false)?;
+    render::rules::apply(&config.derive, &mut theme.vars).with_context(|| format!("theme '{name}'"))?;
+    render::engine::derive_composite(&mut theme.vars);
+    if let Some(upper) = config.upper_hex {
+        render::engine::apply_hex_case(&mut theme.vars, upper);
+    }
+    meta::insert(ctx, &mut theme);
+    meta::insert_env(&config.env_allow, &mut theme.vars);
+    Ok(theme)
 }
 
 /// Symlink per-theme assets (marker files, backgrounds) into the stage dir.
 fn stage_assets(theme: &Theme, stage: &std::path::Path) -> Result<()> {
-    for name in ["light.mode", "icons.theme"] {
+    // Marker files reflect the loaded theme, which may come from theme.toml
+    // or a parent rather than the theme's own marker files.
+    let markers = [
+        ("light.mode", theme.is_light.then(String::new)),
+        ("icons.theme", theme.icon_theme.as_ref().map(|i| format!("{i}\n"))),
+    ];
+    for (name, content) in markers {
+        let Some(content) = content else { continue };
         let src = theme.root.join(name);
-        if src.is_file() {
-            util::symlink_force(&src, &stage.join(name))
-                .with_context(|| format!("symlink {name}"))?;
+        if src.is_file() && std::fs::read_to_string(&src).is_ok_and(|s| s.trim() == content.trim()) {
+            util::symlink_force(&src, &stage.join(name)).with_context(|| format!("symlink {name}"))?;
+        } else {
+            std::fs::write(stage.join(name), content).with_context(|| format!("write {name}"))?;
         }
     }
 
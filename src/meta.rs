@@ -0,0 +1,39 @@
+//! Built-in variables describing the switch itself rather than the palette.
+
+use crate::{ctx::Ctx, theme::Theme};
+use std::{collections::HashMap, fs};
+
+/// Add `theme_name`, `theme_is_light`, `hostname`, `generated_at`,
+/// `live_dir`, and `background_link` to the theme's variables. Values the
+/// theme defines itself are kept.
+pub fn insert(ctx: &Ctx, theme: &mut Theme) {
+    let meta = [
+        ("theme_name", theme.name.clone()),
+        ("theme_is_light", theme.is_light.to_string()),
+        ("hostname", hostname()),
+        ("generated_at", jiff::Zoned::now().strftime("%Y-%m-%dT%H:%M:%S%:z").to_string()),
+        ("live_dir", ctx.live_dir.display().to_string()),
+        ("background_link", ctx.background_link.display().to_string()),
+    ];
+    for (key, value) in meta {
+        theme.vars.entry(key.to_owned()).or_insert(value);
+    }
+}
+
+pub fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok())
+        .map(|h| h.trim().to_owned())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_owned())
+}
+
+/// Add `env.NAME` for every allowlisted environment variable that is set.
+pub fn insert_env(allow: &[String], vars: &mut HashMap<String, String>) {
+    for name in allow {
+        if let Ok(value) = std::env::var(name) {
+            vars.insert(format!("env.{name}"), value);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Date-ranged overlays merged onto the active theme's variables.
+//!
+//! Each `overlays/*.toml` file is a regular color table. It is active either
+//! during the month named by its file stem (`december.toml`) or during the
+//! inclusive `[when] from = "MM-DD"` / `to = "MM-DD"` range it declares.
+//! Ranges may wrap the year end (`from = "12-20"`, `to = "01-06"`).
+
+use crate::render::engine::build_vars;
+use anyhow::{Context, Result, bail};
+use std::{collections::HashMap, fs, path::Path};
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december",
+];
+
+/// A calendar day without a year, ordered by `(month, day)`.
+pub type MonthDay = (u8, u8);
+
+/// Today's date in the local time zone.
+pub fn today() -> MonthDay {
+    let now = jiff::Zoned::now();
+    (now.month() as u8, now.day() as u8)
+}
+
+/// Merge every overlay in `dir` that is active on `today` into `vars`.
+///
+/// Overlays are applied in file-name order, so later files win. Returns the
+/// names of the overlays that were applied.
+pub fn apply(dir: &Path, vars: &mut HashMap<String, String>, today: MonthDay) -> Result<Vec<String>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|x| x.to_str()) == Some("toml"))
+        .collect();
+    files.sort();
+
+    let mut applied = Vec::new();
+    for path in files {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_owned();
+        let src = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let mut table: toml::Value =
+            toml::from_str(&src).with_context(|| format!("parse {}", path.display()))?;
+
+        let when = table.as_table_mut().and_then(|t| t.remove("when"));
+        let range = match when {
+            Some(w) => parse_range(&w).with_context(|| format!("overlay '{name}'"))?,
+            None => match month_range(&name) {
+                Some(r) => r,
+                // Not a month name and no explicit range — never active.
+                None => continue,
+            },
+        };
+
+        if in_range(today, range) {
+            vars.extend(build_vars(&table));
+            applied.push(name);
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Whole-month range for a file stem like `december`.
+fn month_range(stem: &str) -> Option<(MonthDay, MonthDay)> {
+    let idx = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(stem))?;
+    let month = idx as u8 + 1;
+    Some(((month, 1), (month, 31)))
+}
+
+fn parse_range(when: &toml::Value) -> Result<(MonthDay, MonthDay)> {
+    let field = |k: &str| -> Result<MonthDay> {
+        let raw = when
+            .get(k)
+            .and_then(|v| v.as_str())
+            .with_context(|| format!("[when] is missing '{k}'"))?;
+        parse_month_day(raw).with_context(|| format!("invalid [when] {k} = \"{raw}\""))
+    };
+    Ok((field("from")?, field("to")?))
+}
+
+/// Parse `MM-DD`.
+fn parse_month_day(s: &str) -> Result<MonthDay> {
+    let Some((m, d)) = s.split_once('-') else {
+        bail!("expected MM-DD");
+    };
+    let (m, d): (u8, u8) = (m.trim().parse()?, d.trim().parse()?);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        bail!("month or day out of range");
+    }
+    Ok((m, d))
+}
+
+/// Inclusive range check; `from > to` wraps around the year end.
+fn in_range(day: MonthDay, (from, to): (MonthDay, MonthDay)) -> bool {
+    if from <= to {
+        from <= day && day <= to
+    } else {
+        day >= from || day <= to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn month_names_and_ranges() {
+        assert_eq!(month_range("December"), Some(((12, 1), (12, 31))));
+        assert_eq!(month_range("march"), Some(((3, 1), (3, 31))));
+        assert_eq!(month_range("xmas"), None);
+
+        assert_eq!(parse_month_day("12-20").unwrap(), (12, 20));
+        assert_eq!(parse_month_day(" 1-5").unwrap(), (1, 5));
+        for bad in ["1220", "13-01", "12-32", "12-x"] {
+            assert!(parse_month_day(bad).is_err(), "{bad}");
+        }
+
+        let holidays = ((12, 20), (1, 5));
+        assert!(in_range((12, 24), holidays));
+        assert!(in_range((1, 5), holidays));
+        assert!(!in_range((1, 6), holidays));
+        assert!(!in_range((12, 19), holidays));
+        assert!(in_range((6, 15), ((6, 1), (6, 30))));
+        assert!(!in_range((7, 1), ((6, 1), (6, 30))));
+    }
+
+    #[test]
+    fn applies_active_overlays_in_name_order() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("december.toml"), "[palette]\naccent = \"#ff0000\"\n").unwrap();
+        fs::write(
+            dir.path().join("holidays.toml"),
+            "[when]\nfrom = \"12-20\"\nto = \"01-05\"\n[palette]\naccent = \"#00ff00\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.toml"), "[palette]\naccent = \"#0000ff\"\n").unwrap();
+
+        let mut vars = HashMap::new();
+        assert_eq!(apply(dir.path(), &mut vars, (12, 24)).unwrap(), ["december", "holidays"]);
+        assert_eq!(vars["palette_accent"], "#00ff00");
+
+        let mut vars = HashMap::new();
+        assert_eq!(apply(dir.path(), &mut vars, (1, 2)).unwrap(), ["holidays"]);
+        assert!(apply(dir.path(), &mut HashMap::new(), (7, 1)).unwrap().is_empty());
+    }
+}
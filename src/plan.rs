@@ -0,0 +1,175 @@
+//! `set --plan` — describe everything a switch would do as JSON, without
+//! doing any of it.
+
+use crate::{
+    apply::{self, ApplyFlags, reload::Action},
+    ctx::Ctx,
+    generation,
+    json::Value,
+    render::External,
+    theme::Theme,
+    transaction::Change,
+};
+use std::path::Path;
+
+pub struct Plan<'a> {
+    pub theme: &'a Theme,
+    /// The staged tree compared against `live/`.
+    pub changes: &'a [Change],
+    pub externals: &'a [External],
+    pub flags: &'a ApplyFlags,
+    pub compositor: apply::compositor::Settings,
+    pub waybar: &'a apply::waybar::Settings,
+    /// The staged tree, for what depends on the new files.
+    pub stage: &'a Path,
+    pub neovim_command: &'a str,
+    pub emacs: Option<&'a apply::emacs::Source>,
+    pub kde: &'a apply::kde::Settings,
+    /// Whether hooks would run.
+    pub hooks: bool,
+}
+
+impl Plan<'_> {
+    pub fn to_json(&self, ctx: &Ctx) -> Value {
+        let flags = self.flags;
+        let apply = !flags.no_apply;
+        let spawn = apply && !flags.minimal;
+        let next = generation::list(ctx).last().map_or(1, |g| g.number + 1);
+        let path = |p: &Path| Value::from(p);
+
+        let mut files: Vec<Value> = self
+            .changes
+            .iter()
+            .map(|change| {
+                let (kind, rel) = match change {
+                    Change::Added(rel) => ("added", rel),
+                    Change::Modified(rel) => ("modified", rel),
+                    Change::Removed(rel) => ("removed", rel),
+                };
+                Value::obj([("path", path(&ctx.live_dir.join(rel))), ("change", kind.into())])
+            })
+            .collect();
+        let record_change = if ctx.current_theme_file.exists() { "modified" } else { "added" };
+        files.push(Value::obj([("path", path(&ctx.current_theme_file)), ("change", record_change.into())]));
+        for ext in self.externals.iter().filter(|e| e.copy) {
+            files.push(Value::obj([
+                ("path", path(&ext.target)),
+                ("change", "copied".into()),
+                ("source", path(&ctx.current_link.join(&ext.out))),
+            ]));
+        }
+
+        let link = |at: &Path, target: &Path| Value::obj([("path", path(at)), ("target", path(target))]);
+        let mut symlinks = vec![
+            link(&ctx.live_dir, Path::new(&format!("gen-{next}"))),
+            link(&ctx.current_link, &ctx.live_dir),
+        ];
+        symlinks.extend(self.externals.iter().filter(|e| !e.copy).map(|e| link(&e.target, &ctx.current_link.join(&e.out))));
+        if apply && !flags.no_launcher {
+            symlinks.extend(apply::launcher::links(ctx, self.stage).iter().map(|l| link(&l.path, &l.target)));
+        }
+        let wallpaper = (apply && !flags.no_wallpaper).then(|| apply::wallpaper::peek(ctx, self.theme)).flatten();
+        if let Some(image) = &wallpaper {
+            symlinks.push(link(&ctx.background_link, image));
+            if ctx.shared_background_link != ctx.background_link {
+                symlinks.push(link(&ctx.shared_background_link, image));
+            }
+        }
+
+        let gsettings: Vec<Value> = if spawn && !flags.no_gnome {
+            apply::gnome::pending(self.theme, flags.no_icons)
+                .into_iter()
+                .map(|(key, from, to)| {
+                    Value::obj([
+                        ("schema", apply::gnome::SCHEMA.into()),
+                        ("key", key.into()),
+                        ("from", from.into()),
+                        ("to", to.into()),
+                    ])
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut signals = Vec::new();
+        let mut commands = Vec::new();
+        if spawn && !flags.no_gnome {
+            commands.extend(apply::gtk::commands(self.theme, self.stage).into_iter().map(Value::strs));
+        }
+        if spawn && !flags.no_kde {
+            commands.extend(apply::kde::commands(self.theme, self.kde, self.stage).into_iter().map(Value::strs));
+        }
+        if spawn && !flags.no_reload {
+            let pids = |pids: &[u32]| Value::Arr(pids.iter().map(|&p| Value::Num(p.into())).collect());
+            for action in apply::reload::actions(ctx, self.waybar) {
+                match action {
+                    Action::Signal { process, signal } => signals.push(Value::obj([
+                        ("process", process.into()),
+                        ("signal", signal.into()),
+                        ("session", ctx.session.clone().into()),
+                    ])),
+                    Action::SignalPids { process, signal, pids: p } => signals.push(Value::obj([
+                        ("process", process.into()),
+                        ("signal", signal.into()),
+                        ("pids", pids(&p)),
+                    ])),
+                    Action::RestartWaybar(instance) => {
+                        signals.push(Value::obj([
+                            ("process", "waybar".into()),
+                            ("signal", "SIGTERM".into()),
+                            ("pids", pids(&[instance.pid])),
+                        ]));
+                        commands.push(Value::strs(instance.argv));
+                    }
+                    Action::Spawn(args) => commands.push(Value::strs(args)),
+                }
+            }
+            commands.extend(apply::compositor::commands(self.theme, &self.compositor).into_iter().map(Value::strs));
+            commands.extend(apply::tmux::command(ctx, self.theme, self.stage).map(Value::strs));
+            commands.extend(apply::fish::command(ctx, self.theme, self.stage).map(Value::strs));
+            commands.extend(apply::neovim::commands(self.neovim_command).into_iter().map(Value::strs));
+            commands.extend(apply::emacs::command(ctx, self.theme, self.emacs, self.stage).map(Value::strs));
+            commands.extend(apply::qutebrowser::command(ctx, self.stage).map(Value::strs));
+            commands.extend(apply::xresources::command(ctx, self.stage).map(Value::strs));
+            if apply::bat::changed(self.changes) {
+                commands.extend(apply::bat::command().map(Value::strs));
+            }
+        }
+        let wallpaper_command = (spawn && wallpaper.is_some()).then(|| apply::wallpaper::command(ctx));
+        if let Some(args) = &wallpaper_command {
+            commands.push(Value::strs(args.clone()));
+        }
+
+        // Post-set hooks run with the apply steps.
+        let hooks = |stage, runs: bool| {
+            let scripts = if self.hooks && runs { apply::hooks::list(ctx, stage) } else { Vec::new() };
+            Value::Arr(scripts.iter().map(|p| path(p)).collect())
+        };
+
+        Value::obj([
+            ("theme", self.theme.name.clone().into()),
+            ("variant", self.theme.variant.map(|v| v.as_str()).into()),
+            ("generation", Value::Num(next.into())),
+            ("files", Value::Arr(files)),
+            ("symlinks", Value::Arr(symlinks)),
+            ("gsettings", Value::Arr(gsettings)),
+            ("signals", Value::Arr(signals)),
+            ("commands", Value::Arr(commands)),
+            (
+                "wallpaper",
+                Value::obj([
+                    ("image", wallpaper.as_deref().into()),
+                    ("command", wallpaper_command.map_or(Value::Null, Value::strs)),
+                ]),
+            ),
+            (
+                "hooks",
+                Value::obj([
+                    ("pre-set", hooks(apply::hooks::Stage::PreSet, true)),
+                    ("post-set", hooks(apply::hooks::Stage::PostSet, apply)),
+                ]),
+            ),
+        ])
+    }
+}
@@ -0,0 +1,134 @@
+//! `oxidize preview` — a theme's palette as terminal swatches or a PNG grid.
+
+use crate::{color::Rgb, render::engine::resolve_key, term};
+use anyhow::{Context, Result};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Swatches per row in the PNG.
+const COLUMNS: usize = 8;
+/// Swatch size and the gap around it, in pixels.
+const CELL: (usize, usize) = (96, 64);
+const GAP: usize = 8;
+
+/// Every hex color of the theme, sorted by name. Per-app overrides are left
+/// out: they repeat the global names.
+pub fn colors(vars: &HashMap<String, String>) -> Vec<(&str, Rgb)> {
+    let mut colors: Vec<(&str, Rgb)> = vars
+        .iter()
+        .filter(|(k, v)| v.starts_with('#') && !k.starts_with("app_") && !k.starts_with("overrides_"))
+        .filter_map(|(k, v)| Some((k.as_str(), Rgb::parse(v)?)))
+        .collect();
+    colors.sort_by_key(|(k, _)| *k);
+    colors
+}
+
+/// One line per color: swatch, hex and name.
+pub fn print(vars: &HashMap<String, String>) {
+    for (name, color) in colors(vars) {
+        if term::stdout_color() {
+            println!("{} {} {name}", term::swatch(color), color.to_hex());
+        } else {
+            println!("{} {name}", color.to_hex());
+        }
+    }
+}
+
+/// Write the palette as a grid of swatches on the theme's background.
+pub fn write_png(vars: &HashMap<String, String>, path: &Path) -> Result<()> {
+    let colors = colors(vars);
+    let bg = resolve_key(vars, "bg").and_then(|(_, v)| Rgb::parse(v)).unwrap_or(Rgb::BLACK);
+    let rows = colors.len().div_ceil(COLUMNS).max(1);
+    let cols = colors.len().clamp(1, COLUMNS);
+    let width = GAP + cols * (CELL.0 + GAP);
+    let height = GAP + rows * (CELL.1 + GAP);
+
+    let mut pixels = vec![bg; width * height];
+    for (i, (_, color)) in colors.iter().enumerate() {
+        let (x0, y0) = (GAP + (i % COLUMNS) * (CELL.0 + GAP), GAP + (i / COLUMNS) * (CELL.1 + GAP));
+        for y in y0..y0 + CELL.1 {
+            pixels[y * width + x0..y * width + x0 + CELL.0].fill(*color);
+        }
+    }
+
+    fs::write(path, encode_png(width, height, &pixels)).with_context(|| format!("write {}", path.display()))
+}
+
+/// An 8-bit RGB PNG. The image data goes into stored (uncompressed) deflate
+/// blocks, which keeps the encoder dependency-free.
+fn encode_png(width: usize, height: usize, pixels: &[Rgb]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(height * (1 + width * 3));
+    for row in pixels.chunks(width) {
+        raw.push(0); // filter: none
+        raw.extend(row.iter().flat_map(|c| [c.r, c.g, c.b]));
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(0xffff);
+    let last = blocks.len() - 1;
+    for (i, block) in blocks.enumerate() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(i == last));
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend((width as u32).to_be_bytes());
+    ihdr.extend((height as u32).to_be_bytes());
+    ihdr.extend([8, 2, 0, 0, 0]); // 8-bit, truecolor, deflate, no filter, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", ihdr.as_slice()), (b"IDAT", &zlib), (b"IEND", &[])] {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        let crc = crc32(&png[start..]);
+        png.extend(crc.to_be_bytes());
+    }
+    png
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_framing_and_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        let png = encode_png(2, 1, &[Rgb::BLACK, Rgb::WHITE]);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x02\0\0\0\x01"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        // One stored block holding the filter byte and both pixels.
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap() + 4;
+        assert_eq!(&png[idat..idat + 14], b"\x78\x01\x01\x07\x00\xf8\xff\x00\x00\x00\x00\xff\xff\xff");
+    }
+}
@@ -0,0 +1,113 @@
+//! `oxidize bench` — time each stage of the render pipeline separately.
+
+use super::{engine, frontmatter, parser::parse};
+use crate::{ctx::Ctx, theme::Theme};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Run every stage `iterations` times against the real template set and
+/// print a per-stage breakdown. `load` loads the theme the way `set` does,
+/// and is what the vars stage times.
+///
+/// Jinja templates render through minijinja rather than the built-in
+/// parser, so they are left out and only counted.
+pub fn run(ctx: &Ctx, iterations: u32, load: impl Fn() -> Result<Theme>) -> Result<()> {
+    let iterations = iterations.max(1);
+    let theme = load()?;
+
+    let mut all: Vec<PathBuf> = ctx.templates_dirs.iter().flat_map(|d| engine::templates_in(d)).collect();
+    if ctx.user_templates_dir.is_dir() {
+        all.extend(engine::templates_in(&ctx.user_templates_dir));
+    }
+    let (mut templates, mut sources) = (Vec::new(), Vec::new());
+    let mut jinja = 0;
+    for path in all {
+        let src = fs::read_to_string(&path).with_context(|| format!("read template {}", path.display()))?;
+        let (fm, body) = frontmatter::split(&src).with_context(|| path.display().to_string())?;
+        if engine::is_jinja(&path, &fm) {
+            jinja += 1;
+            continue;
+        }
+        sources.push(body.to_owned());
+        templates.push(path);
+    }
+    let bytes: usize = sources.iter().map(String::len).sum();
+
+    let scratch = tempfile::tempdir().context("create scratch dir")?;
+
+    let vars = try_time(iterations, || {
+        black_box(load()?);
+        Ok(())
+    })?;
+
+    let parse_t = time(iterations, || {
+        for src in &sources {
+            black_box(parse(src));
+        }
+    });
+
+    let parsed: Vec<_> = sources.iter().map(|s| parse(s)).collect();
+    let expand_t = time(iterations, || {
+        for segs in &parsed {
+            black_box(engine::expand_segments(segs, &theme.vars, &mut Vec::new()));
+        }
+    });
+
+    let rendered: Vec<String> = parsed
+        .iter()
+        .map(|s| engine::expand_segments(s, &theme.vars, &mut Vec::new()))
+        .collect();
+    let io_t = time(iterations, || {
+        for (i, (path, out)) in templates.iter().zip(&rendered).enumerate() {
+            black_box(fs::read(path).ok());
+            fs::write(scratch.path().join(i.to_string()), out).ok();
+        }
+    });
+
+    println!(
+        "{} templates ({} KiB), theme '{}', {iterations} iteration(s)",
+        templates.len(),
+        bytes / 1024,
+        theme.name
+    );
+    if jinja > 0 {
+        println!("{jinja} jinja template(s) not included");
+    }
+    println!("{:<10} {:>12} {:>12}", "stage", "per run", "total");
+    for (stage, total) in [("vars", vars), ("parse", parse_t), ("expand", expand_t), ("io", io_t)] {
+        println!("{stage:<10} {:>12} {:>12}", fmt(total / iterations), fmt(total));
+    }
+    let sum = vars + parse_t + expand_t + io_t;
+    println!("{:<10} {:>12} {:>12}", "sum", fmt(sum / iterations), fmt(sum));
+    Ok(())
+}
+
+fn time(iterations: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed()
+}
+
+fn try_time(iterations: u32, mut f: impl FnMut() -> Result<()>) -> Result<Duration> {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f()?;
+    }
+    Ok(start.elapsed())
+}
+
+fn fmt(d: Duration) -> String {
+    let us = d.as_secs_f64() * 1e6;
+    if us < 1000.0 {
+        format!("{us:.1} µs")
+    } else {
+        format!("{:.2} ms", us / 1000.0)
+    }
+}
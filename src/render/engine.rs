@@ -1,23 +1,88 @@
 //! Template rendering engine and TOML variable builder.
 
-use super::parser::{parse, Segment};
+use super::{
+    filters,
+    frontmatter::{self, Dest, FrontMatter},
+    jinja,
+    parser::{parse, Arg, Cmp, Cond, Expr, Segment},
+};
+use crate::{color::Rgb, log};
 use anyhow::{bail, Context, Result};
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     fs,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
 };
+use rayon::prelude::*;
 use walkdir::WalkDir;
 
 // Variable building
 pub fn build_vars_from_colors(colors_file: &Path) -> Result<HashMap<String, String>> {
+    build_vars_from_files(&[colors_file.to_path_buf()])
+}
+
+/// Deep-merge several palette files, later ones winning, and build the
+/// variables from the result.
+pub fn build_vars_from_files(files: &[PathBuf]) -> Result<HashMap<String, String>> {
+    Ok(build_vars(&read_layers(files)?))
+}
+
+/// Parse and deep-merge `files` in order.
+pub fn read_layers(files: &[PathBuf]) -> Result<toml::Value> {
+    let mut table = toml::Value::Table(toml::Table::new());
+    for file in files {
+        merge_tables(&mut table, read_colors(file)?);
+    }
+    Ok(table)
+}
+
+/// `dir/colors.d/*.toml`, sorted by file name.
+pub fn palette_dropins(dir: &Path) -> Result<Vec<PathBuf>> {
+    let dropins = dir.join("colors.d");
+    if !dropins.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(&dropins)
+        .with_context(|| format!("read {}", dropins.display()))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|x| x == "toml"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Parse a palette file without flattening it.
+pub fn read_colors(colors_file: &Path) -> Result<toml::Value> {
     let src = fs::read_to_string(colors_file)
         .with_context(|| format!("read {}", colors_file.display()))?;
+    toml::from_str(&src).with_context(|| format!("parse {}", colors_file.display()))
+}
 
-    let table: toml::Value = toml::from_str(&src).context("parse colors.toml")?;
+/// Deep-merge `over` into `base`: tables merge key by key, anything else
+/// (including arrays) is replaced.
+pub fn merge_tables(base: &mut toml::Value, over: toml::Value) {
+    match (base, over) {
+        (toml::Value::Table(base), toml::Value::Table(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_tables(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
 
+/// Flatten a parsed TOML table and append the derived color keys.
+pub fn build_vars(table: &toml::Value) -> HashMap<String, String> {
     let mut vars = HashMap::new();
-    flatten("", &table, &mut vars);
+    flatten("", table, &mut vars);
 
     // Collect derived keys separately to avoid a borrow conflict on `vars`.
     let derived: Vec<(String, String)> = vars
@@ -27,11 +92,132 @@ pub fn build_vars_from_colors(colors_file: &Path) -> Result<HashMap<String, Stri
         .collect();
 
     vars.extend(derived);
-    Ok(vars)
+    vars
+}
+
+/// Derive keys that combine several colors. Runs once on the final variable
+/// map (after overlays), so overrides feed into the composites.
+pub fn derive_composite(vars: &mut HashMap<String, String>) {
+    derive_terminal_colors(vars);
+    derive_wezterm_palettes(vars);
+    derive_hypr_gradients(vars);
+}
+
+/// For every table that defines the eight normal terminal colors
+/// (`palette_color0`–`palette_color7`), fill in missing bright variants
+/// `color8`–`color15` (OKLCH lightness +12) and dim ones `color0_dim`–
+/// `color7_dim` (lightness −12).
+fn derive_terminal_colors(vars: &mut HashMap<String, String>) {
+    let prefixes: HashSet<&str> = vars.keys().filter_map(|k| k.strip_suffix("color0")).collect();
+    let mut derived = Vec::new();
+    for prefix in prefixes {
+        let Some(normal) = (0..8)
+            .map(|i| vars.get(&format!("{prefix}color{i}")).and_then(|v| Rgb::parse(v)))
+            .collect::<Option<Vec<Rgb>>>()
+        else {
+            continue;
+        };
+        for (i, color) in normal.into_iter().enumerate() {
+            for (key, value) in [
+                (format!("{prefix}color{}", i + 8), color.lighten(12.0)),
+                (format!("{prefix}color{i}_dim"), color.darken(12.0)),
+            ] {
+                if !vars.contains_key(&key) {
+                    derived.push((key, value.to_hex()));
+                }
+            }
+        }
+    }
+    for (key, value) in derived {
+        vars.extend(derive_color_keys(&key, &value));
+        vars.insert(key, value);
+    }
+}
+
+/// Rewrite every hex color value, and its `_strip` / `_0x` / `_alpha_hex`
+/// notations, in upper or lower case. Also sets `hex_case`, which color
+/// filters follow for their output.
+pub fn apply_hex_case(vars: &mut HashMap<String, String>, upper: bool) {
+    let colors: Vec<String> = vars
+        .iter()
+        .filter(|(_, v)| v.starts_with('#') && Rgb::parse_alpha(v).is_some())
+        .map(|(k, _)| k.clone())
+        .collect();
+    for key in colors {
+        for name in [key.clone(), format!("{key}_strip"), format!("{key}_0x"), format!("{key}_alpha_hex")] {
+            if let Some(v) = vars.get_mut(&name) {
+                let (prefix, digits) = v.split_at(if v.starts_with("0x") { 2 } else { 0 });
+                *v = format!("{prefix}{}", if upper { digits.to_uppercase() } else { digits.to_lowercase() });
+            }
+        }
+    }
+    vars.insert("hex_case".to_owned(), if upper { "upper" } else { "lower" }.to_owned());
+}
+
+/// For every table with all sixteen terminal colors, add `wezterm_ansi`
+/// (`color0`–`color7`) and `wezterm_brights` (`color8`–`color15`) as TOML
+/// arrays, the form WezTerm's scheme files use.
+fn derive_wezterm_palettes(vars: &mut HashMap<String, String>) {
+    let prefixes: Vec<String> = vars.keys().filter_map(|k| k.strip_suffix("color15")).map(str::to_owned).collect();
+    for prefix in prefixes {
+        let array = |range: std::ops::Range<usize>| {
+            let colors = range
+                .map(|i| vars.get(&format!("{prefix}color{i}")).map(|c| format!("\"{c}\"")))
+                .collect::<Option<Vec<String>>>()?;
+            Some(format!("[{}]", colors.join(", ")))
+        };
+        let (Some(ansi), Some(brights)) = (array(0..8), array(8..16)) else {
+            continue;
+        };
+        vars.entry(format!("{prefix}wezterm_ansi")).or_insert(ansi);
+        vars.entry(format!("{prefix}wezterm_brights")).or_insert(brights);
+    }
+}
+
+/// Produce `hypr_gradient_active` / `hypr_gradient_inactive` in Hyprland's
+/// `rgba(rrggbbaa) rgba(rrggbbaa) <angle>deg` syntax from the accent and
+/// secondary colors. Themes may set `hypr_gradient_angle` (default 45) or
+/// define either gradient themselves to opt out.
+fn derive_hypr_gradients(vars: &mut HashMap<String, String>) {
+    let Some(accent) = find_color(vars, "accent") else {
+        return;
+    };
+    let secondary = find_color(vars, "secondary").unwrap_or_else(|| accent.lighten(15.0));
+    let angle = vars
+        .get("hypr_gradient_angle")
+        .map_or("45".to_owned(), |a| a.trim_end_matches("deg").to_owned());
+
+    let gradient = |alpha: &str| {
+        let a = accent.to_hex();
+        let b = secondary.to_hex();
+        format!("rgba({}{alpha}) rgba({}{alpha}) {angle}deg", &a[1..], &b[1..])
+    };
+    let active = gradient("ff");
+    let inactive = gradient("55");
+    vars.entry("hypr_gradient_active".to_owned()).or_insert(active);
+    vars.entry("hypr_gradient_inactive".to_owned()).or_insert(inactive);
+}
+
+/// Find a color by role name; see [`resolve_key`].
+fn find_color(vars: &HashMap<String, String>, name: &str) -> Option<Rgb> {
+    resolve_key(vars, name).and_then(|(_, v)| Rgb::parse(v))
+}
+
+/// Look up `name` itself, else the shortest key ending in `_name`
+/// (so `bg` finds `palette_bg`).
+pub fn resolve_key<'v>(vars: &'v HashMap<String, String>, name: &str) -> Option<(&'v str, &'v str)> {
+    if let Some((k, v)) = vars.get_key_value(name) {
+        return Some((k, v));
+    }
+    let suffix = format!("_{name}");
+    vars.iter()
+        .filter(|(k, _)| k.ends_with(&suffix))
+        .min_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then(a.cmp(b)))
+        .map(|(k, v)| (k.as_str(), v.as_str()))
 }
 
 /// Flatten a TOML value into `prefix_key = string` pairs.
-fn flatten(prefix: &str, value: &toml::Value, out: &mut HashMap<String, String>) {
+pub(super) fn flatten(prefix: &str, value: &toml::Value, out: &mut HashMap<String, String>) {
     match value {
         toml::Value::Table(map) => {
             for (k, v) in map {
@@ -55,149 +241,667 @@ fn flatten(prefix: &str, value: &toml::Value, out: &mut HashMap<String, String>)
         toml::Value::Boolean(b) => {
             out.insert(prefix.to_owned(), b.to_string());
         }
-        // Arrays and datetimes are not used in color files — silently ignore.
-        _ => {}
+        // Arrays become `prefix_0`, `prefix_1`, … plus `prefix_len`, which
+        // `{% for %}` iterates over.
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(&format!("{prefix}_{i}"), item, out);
+            }
+            out.insert(format!("{prefix}_len"), items.len().to_string());
+        }
+        // Datetimes are not used in color files — silently ignore.
+        toml::Value::Datetime(_) => {}
     }
 }
 
-/// Produce `<key>_strip` and `<key>_rgb` entries from a `#rrggbb` value.
-fn derive_color_keys(key: &str, hex: &str) -> impl Iterator<Item = (String, String)> {
-    let bare = hex.trim_start_matches('#');
-    let rgb = hex_to_rgb(bare).map(|r| (format!("{key}_rgb"), r));
-    let strip = (format!("{key}_strip"), bare.to_owned());
-    std::iter::once(strip).chain(rgb)
-}
+/// Produce the derived notations of a `#rrggbb` value: `<key>_strip`, and
+/// for valid colors `_rgb`, `_rgb_func`, `_0x`, `_r` / `_g` / `_b`, the HSL
+/// components `_h` / `_s` / `_l`, and HSV `_hsv_h` / `_hsv_s` / `_hsv_v`
+/// (degrees and percent, rounded to integers), and OKLCH `_oklch_l`
+/// (percent, one decimal) / `_oklch_c` (three decimals) / `_oklch_h`.
+///
+/// Shorthand `#rgb` and alpha forms `#rgba` / `#rrggbbaa` are normalized:
+/// the result then includes `<key>` itself rewritten to `#rrggbb`, plus
+/// `_alpha` (0–1) and `_alpha_hex` when there was an alpha channel.
+pub(super) fn derive_color_keys(key: &str, hex: &str) -> Vec<(String, String)> {
+    let mut bare = hex.trim_start_matches('#');
+    let Some((color, alpha)) = Rgb::parse_alpha(bare) else {
+        return vec![(format!("{key}_strip"), bare.to_owned())];
+    };
 
-/// Convert a bare 6-character hex string to `"r,g,b"`.
-fn hex_to_rgb(hex: &str) -> Option<String> {
-    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
-        return None;
+    let mut out = Vec::new();
+    let normalized = color.to_hex();
+    if bare.len() != 6 {
+        bare = &normalized[1..];
+        out.push((key.to_owned(), normalized.clone()));
     }
-    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-    Some(format!("{r},{g},{b}"))
+    if let Some(a) = alpha {
+        let decimal = format!("{:.3}", f64::from(a) / 255.0);
+        out.push((format!("{key}_alpha"), decimal.trim_end_matches('0').trim_end_matches('.').to_owned()));
+        out.push((format!("{key}_alpha_hex"), format!("{a:02x}")));
+    }
+
+    let Rgb { r, g, b } = color;
+    let (h, s, l) = color.to_hsl();
+    let (_, hsv_s, v) = color.to_hsv();
+    let (ok_l, ok_c, ok_h) = color.to_oklch();
+    let int = |x: f64| format!("{}", x.round() as i64);
+    out.extend([
+        (format!("{key}_strip"), bare.to_owned()),
+        (format!("{key}_rgb"), format!("{r},{g},{b}")),
+        (format!("{key}_rgb_func"), format!("rgb({r}, {g}, {b})")),
+        (format!("{key}_0x"), format!("0x{bare}")),
+        (format!("{key}_r"), r.to_string()),
+        (format!("{key}_g"), g.to_string()),
+        (format!("{key}_b"), b.to_string()),
+        (format!("{key}_h"), int(h)),
+        (format!("{key}_s"), int(s)),
+        (format!("{key}_l"), int(l)),
+        (format!("{key}_hsv_h"), int(h)),
+        (format!("{key}_hsv_s"), int(hsv_s)),
+        (format!("{key}_hsv_v"), int(v)),
+        (format!("{key}_oklch_l"), format!("{ok_l:.1}")),
+        (format!("{key}_oklch_c"), format!("{ok_c:.3}")),
+        (format!("{key}_oklch_h"), int(ok_h)),
+        (format!("{key}_ansi256"), color.to_ansi256().to_string()),
+        (format!("{key}_lum"), int(color.luminance() * 100.0)),
+        (format!("{key}_brightness"), int(color.brightness() * 100.0)),
+    ]);
+    out
 }
 
 // Template rendering
-pub fn render_all(
-    templates_dir: &Path,
-    user_templates_dir: &Path,
-    theme_files_dir: &Path,
-    out_dir: &Path,
-    vars: &HashMap<String, String>,
-) -> Result<()> {
-    if !templates_dir.is_dir() {
-        bail!("templates directory not found: {}", templates_dir.display());
+
+/// Knobs for [`render_all`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Fail instead of leaving unresolved `{{ ... }}` tokens in the output.
+    pub strict: bool,
+}
+
+/// One output file of a render, and where its content comes from.
+#[derive(Debug, Clone)]
+pub enum Job {
+    /// Expand the template at `tpl`; if `target` is set, the output is also
+    /// linked from (or with `copy`, written to) that absolute path.
+    Render {
+        tpl: PathBuf,
+        out: PathBuf,
+        target: Option<PathBuf>,
+        copy: bool,
+        /// Namespace whose `app.<name>` keys shadow the global ones.
+        app: Option<String>,
+    },
+    /// Copy a theme file or template-dir asset verbatim.
+    Copy { src: PathBuf, out: PathBuf },
+}
+
+/// A rendered file that also appears at a fixed path outside the live tree.
+#[derive(Debug, Clone)]
+pub struct External {
+    /// Path relative to the live tree.
+    pub out: PathBuf,
+    /// Absolute path the link or copy is created at.
+    pub target: PathBuf,
+    /// Write a real file (with backup) instead of a symlink.
+    pub copy: bool,
+}
+
+/// A theme's own files, copied into the output verbatim.
+#[derive(Debug, Clone, Copy)]
+pub struct ThemeFiles<'a> {
+    pub dir: &'a Path,
+    /// The theme's wallpapers, which are staged separately and left out.
+    pub backgrounds: Option<&'a Path>,
+}
+
+/// Resolve which source produces each output file:
+/// user templates, then verbatim theme files, then shared templates (from
+/// each existing dir of `search` in turn).
+/// Non-`.tpl` files in any template dir are copied as static assets,
+/// after the templates of the same dir.
+pub fn plan(search: &[PathBuf], user_templates_dir: &Path, theme_files: ThemeFiles) -> Result<Vec<Job>> {
+    let templates_dirs: Vec<&PathBuf> = search.iter().filter(|d| d.is_dir()).collect();
+    if templates_dirs.is_empty() {
+        let names: Vec<String> = search.iter().map(|d| d.display().to_string()).collect();
+        bail!("templates directory not found: {}", names.join(", "));
     }
-    fs::create_dir_all(out_dir).context("create output directory")?;
 
     // a hashset bag with claimed items that will not generate with
     // the template enginge.
     let mut claimed: HashSet<PathBuf> = HashSet::new();
+    let mut jobs = Vec::new();
 
     if user_templates_dir.is_dir() {
         for tpl in templates_in(user_templates_dir) {
-            let rel = tpl.strip_prefix(user_templates_dir)?.to_path_buf();
-            render_one(&tpl, &rel, vars, out_dir)?;
-            claimed.insert(rel.with_extension("")); // key = output path (no .tpl)
+            let job = render_job(tpl, user_templates_dir)?; // key = output path (no .tpl)
+            if claimed.insert(job_out(&job).to_owned()) {
+                jobs.push(job);
+            }
+        }
+        for src in assets_in(user_templates_dir) {
+            let out = src.strip_prefix(user_templates_dir)?.to_path_buf();
+            if claimed.insert(out.clone()) {
+                jobs.push(Job::Copy { src, out });
+            }
+        }
+    }
+
+    if theme_files.dir.is_dir() {
+        for src in theme_files_in(theme_files) {
+            let out = src.strip_prefix(theme_files.dir)?.to_path_buf(); // already no extension
+            if claimed.insert(out.clone()) {
+                jobs.push(Job::Copy { src, out });
+            }
+        }
+    }
+
+    for templates_dir in templates_dirs {
+        for tpl in templates_in(templates_dir) {
+            let job = render_job(tpl, templates_dir)?; // compare against output path
+            if claimed.insert(job_out(&job).to_owned()) {
+                jobs.push(job);
+            }
+        }
+        for src in assets_in(templates_dir) {
+            let out = src.strip_prefix(templates_dir)?.to_path_buf();
+            if claimed.insert(out.clone()) {
+                jobs.push(Job::Copy { src, out });
+            }
         }
     }
 
-    if theme_files_dir.is_dir() {
-        for src in theme_files_in(theme_files_dir) {
-            let rel = src.strip_prefix(theme_files_dir)?.to_path_buf();
-            if !claimed.contains(&rel) {
-                let out_path = out_dir.join(&rel);
-                if let Some(parent) = out_path.parent() {
-                    fs::create_dir_all(parent)?;
+    Ok(jobs)
+}
+
+/// Build the job for `tpl`, honoring its `#! out` and `#! app` headers. A
+/// template in a subdirectory belongs to the app named after its top-level
+/// directory unless the header says otherwise.
+pub(super) fn render_job(tpl: PathBuf, root: &Path) -> Result<Job> {
+    let mirrored = tpl.strip_prefix(root)?.with_extension("");
+    let src = fs::read_to_string(&tpl).with_context(|| format!("read template {}", tpl.display()))?;
+    let (fm, _) = frontmatter::split(&src).with_context(|| tpl.display().to_string())?;
+    let (out, target) = match fm.dest(&mirrored).with_context(|| tpl.display().to_string())? {
+        Dest::Live(out) => (out, None),
+        Dest::External(target) => (mirrored, Some(target)),
+    };
+    let app = fm.app.or_else(|| {
+        let mut parts = tpl.strip_prefix(root).ok()?.components();
+        let top = parts.next()?.as_os_str().to_str()?.to_owned();
+        parts.next().map(|_| top)
+    });
+    Ok(Job::Render { tpl, out, target, copy: fm.copy, app })
+}
+
+fn job_out(job: &Job) -> &Path {
+    match job {
+        Job::Render { out, .. } | Job::Copy { out, .. } => out,
+    }
+}
+
+pub fn render_all(
+    templates_dirs: &[PathBuf],
+    user_templates_dir: &Path,
+    theme_files: ThemeFiles,
+    out_dir: &Path,
+    prev_dir: Option<&Path>,
+    vars: &HashMap<String, String>,
+    opts: Options,
+) -> Result<Vec<External>> {
+    let jobs = plan(templates_dirs, user_templates_dir, theme_files)?;
+    fs::create_dir_all(out_dir).context("create output directory")?;
+
+    // Every job is independent; run them in parallel and collect all errors.
+    let results: Vec<Result<Done>> = jobs
+        .into_par_iter()
+        .map(|job| run_job(job, vars, out_dir, prev_dir, opts))
+        .collect();
+
+    // Templates that left tokens unresolved, with the offending keys.
+    let mut unresolved: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    let mut externals = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in results {
+        match result {
+            Ok(Done::Rendered { tpl, missing, external }) => {
+                if !missing.is_empty() {
+                    unresolved.push((tpl, missing));
                 }
-                fs::copy(&src, &out_path)?;
-                claimed.insert(rel); // already no extension, key = output path
+                externals.extend(external);
             }
+            Ok(Done::Copied) => {}
+            Err(e) => errors.push(format!("  {e:#}")),
         }
     }
+    if !errors.is_empty() {
+        bail!("{} output file(s) failed:\n{}", errors.len(), errors.join("\n"));
+    }
 
-    for tpl in templates_in(templates_dir) {
-        let rel = tpl.strip_prefix(templates_dir)?.to_path_buf();
-        if !claimed.contains(&rel.with_extension("")) {
-            // compare against output path
-            render_one(&tpl, &rel, vars, out_dir)?;
+    for (tpl, missing) in &unresolved {
+        log::debug!("{}: unresolved {}", tpl.display(), missing.join(", "));
+    }
+    if opts.strict && !unresolved.is_empty() {
+        let list: Vec<String> = unresolved
+            .iter()
+            .map(|(tpl, missing)| format!("  {}: {}", tpl.display(), missing.join(", ")))
+            .collect();
+        bail!("undefined variables (strict mode):\n{}", list.join("\n"));
+    }
+
+    Ok(externals)
+}
+
+/// Result of one [`Job`].
+enum Done {
+    Rendered {
+        tpl: PathBuf,
+        missing: Vec<String>,
+        external: Option<External>,
+    },
+    Copied,
+}
+
+/// `prev_dir` is the tree being replaced: outputs identical to their
+/// counterpart there are hard-linked instead of written again.
+fn run_job(
+    job: Job,
+    vars: &HashMap<String, String>,
+    out_dir: &Path,
+    prev_dir: Option<&Path>,
+    opts: Options,
+) -> Result<Done> {
+    match job {
+        Job::Render { tpl, out, target, copy, app } => {
+            let vars = scoped(vars, app.as_deref());
+            let missing = render_one(&tpl, &out, &vars, out_dir, prev_dir, opts)?;
+            let external = target.map(|target| External { out, target, copy });
+            Ok(Done::Rendered { tpl, missing, external })
+        }
+        Job::Copy { src, out } => {
+            let out_path = out_dir.join(&out);
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent).with_context(|| format!("create output subdir {}", parent.display()))?;
+            }
+            let prev = prev_dir.map(|d| d.join(&out));
+            let reused = prev.is_some_and(|prev| {
+                fs::metadata(&src).is_ok_and(|meta| {
+                    fs::read(&src).is_ok_and(|bytes| unchanged(&prev, &bytes, meta.permissions().mode() & 0o7777))
+                }) && fs::hard_link(&prev, &out_path).is_ok()
+            });
+            if !reused {
+                fs::copy(&src, &out_path).with_context(|| format!("copy {}", src.display()))?;
+            }
+            Ok(Done::Copied)
         }
     }
+}
 
-    Ok(())
+/// Expand every template of a plan in memory and report, per output path,
+/// the keys that would be left unresolved. Nothing is written.
+pub fn dry_run(jobs: &[Job], vars: &HashMap<String, String>) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    let mut unresolved = Vec::new();
+    for job in jobs {
+        if let Job::Render { tpl, out, app, .. } = job {
+            let src = fs::read_to_string(tpl).with_context(|| format!("read template {}", tpl.display()))?;
+            let (fm, body) = frontmatter::split(&src).with_context(|| tpl.display().to_string())?;
+            if is_jinja(tpl, &fm) {
+                continue; // minijinja reports undefined keys only when rendering strictly
+            }
+            let mut missing = Vec::new();
+            expand_segments(&parse(body), &scoped(vars, app.as_deref()), &mut missing);
+            if !missing.is_empty() {
+                unresolved.push((out.clone(), missing));
+            }
+        }
+    }
+    Ok(unresolved)
 }
 
-/// Render a single template file to `out_dir / rel` (minus `.tpl` extension).
+/// Render a single template file to `out_dir / out`.
+///
+/// Returns the keys that could not be resolved. Jinja templates never
+/// report any: in strict mode an undefined key fails the render instead.
 fn render_one(
     tpl_path: &Path,
-    rel: &Path,
+    out: &Path,
     vars: &HashMap<String, String>,
     out_dir: &Path,
-) -> Result<()> {
+    prev_dir: Option<&Path>,
+    opts: Options,
+) -> Result<Vec<String>> {
     let src = fs::read_to_string(tpl_path)
         .with_context(|| format!("read template {}", tpl_path.display()))?;
 
-    let rendered = expand(&src, vars);
+    let (fm, body) = frontmatter::split(&src).with_context(|| tpl_path.display().to_string())?;
+
+    let mut missing = Vec::new();
+    let rendered = if is_jinja(tpl_path, &fm) {
+        jinja::render(body, vars, opts.strict).with_context(|| tpl_path.display().to_string())?
+    } else {
+        expand_segments(&parse(body), vars, &mut missing)
+    };
 
-    let out_path = out_dir.join(&rel.with_extension("")); // strip .tpl
+    let out_path = out_dir.join(out);
 
     if let Some(parent) = out_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("create output subdir {}", parent.display()))?;
     }
-    fs::write(&out_path, rendered).with_context(|| format!("write {}", out_path.display()))
+    // Keep e.g. the executable bit of templated scripts.
+    let mode = match fm.mode {
+        Some(mode) => mode,
+        None => fs::metadata(tpl_path)
+            .with_context(|| format!("stat {}", tpl_path.display()))?
+            .permissions()
+            .mode()
+            & 0o7777,
+    };
+
+    if let Some(prev) = prev_dir.map(|d| d.join(out))
+        && unchanged(&prev, rendered.as_bytes(), mode)
+        && fs::hard_link(&prev, &out_path).is_ok()
+    {
+        return Ok(missing);
+    }
+
+    fs::write(&out_path, rendered).with_context(|| format!("write {}", out_path.display()))?;
+    fs::set_permissions(&out_path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("chmod {}", out_path.display()))?;
+    Ok(missing)
 }
 
-/// Expand `{{ key }}` tokens in `src` using `vars`.
+/// True if `prev` is a regular file with exactly `content` and `mode`.
+fn unchanged(prev: &Path, content: &[u8], mode: u32) -> bool {
+    fs::symlink_metadata(prev).is_ok_and(|meta| {
+        meta.is_file()
+            && meta.len() == content.len() as u64
+            && meta.permissions().mode() & 0o7777 == mode
+            && fs::read(prev).is_ok_and(|old| old == content)
+    })
+}
+
+/// Expand `{{ key | filter }}` tokens and `{% for %}` blocks in `src`.
 ///
-/// Unknown keys are left as `{{ key }}` so partial renders are inspectable.
-/// The output buffer is pre-sized with a single pass to avoid reallocations.
+/// Unknown keys and failing filters leave the original token in place so
+/// partial renders are inspectable.
+#[cfg(test)]
 fn expand(src: &str, vars: &HashMap<String, String>) -> String {
-    let segments = parse(src);
+    expand_segments(&parse(src), vars, &mut Vec::new())
+}
 
-    let capacity: usize = segments
+/// Evaluate already-parsed segments, recording every key that could not be
+/// resolved (deduplicated) in `missing`.
+pub(super) fn expand_segments(
+    segments: &[Segment<'_>],
+    vars: &HashMap<String, String>,
+    missing: &mut Vec<String>,
+) -> String {
+    let literal: usize = segments
         .iter()
         .map(|s| match s {
             Segment::Lit(t) => t.len(),
-            Segment::Var(k) => vars.get(*k).map_or(k.len() + 6, String::len),
+            _ => 0,
         })
         .sum();
+    let mut out = String::with_capacity(literal * 5 / 4);
+    let mut macros = Macros::new();
+    collect_macros(segments, &mut macros);
+    render_into(segments, vars, &macros, &mut out, missing);
+    out
+}
 
-    let mut out = String::with_capacity(capacity);
+/// Macro name → (parameters, body).
+type Macros<'s, 'a> = HashMap<&'a str, (&'s [&'a str], &'s [Segment<'a>])>;
 
-    for seg in &segments {
+/// Every `{% macro %}` in the template, wherever it is defined. A later
+/// definition of the same name wins.
+fn collect_macros<'s, 'a>(segments: &'s [Segment<'a>], macros: &mut Macros<'s, 'a>) {
+    for seg in segments {
+        match seg {
+            Segment::Macro { name, params, body } => {
+                macros.insert(name, (params, body));
+            }
+            Segment::For { body, .. } => collect_macros(body, macros),
+            Segment::If { then, otherwise, .. } => {
+                collect_macros(then, macros);
+                collect_macros(otherwise, macros);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_into(
+    segments: &[Segment<'_>],
+    vars: &HashMap<String, String>,
+    macros: &Macros<'_, '_>,
+    out: &mut String,
+    missing: &mut Vec<String>,
+) {
+    for seg in segments {
         match seg {
             Segment::Lit(t) => out.push_str(t),
-            Segment::Var(k) => match vars.get(*k) {
-                Some(v) => out.push_str(v),
-                None => {
-                    out.push_str("{{ ");
-                    out.push_str(k);
-                    out.push_str(" }}");
+            Segment::Macro { .. } => {}
+            Segment::Call { raw, name, args } => {
+                let Some((params, body)) = macros.get(name) else {
+                    note_missing(missing, name);
+                    out.push_str(raw);
+                    continue;
+                };
+                if args.len() > params.len() {
+                    log::warn!("{raw}: {name}() takes {} argument(s)", params.len());
+                    out.push_str(raw);
+                    continue;
+                }
+                // A macro can't call itself: there would be no way to stop.
+                let mut inner = macros.clone();
+                inner.remove(name);
+                render_into(body, &macro_scope(vars, params, args), &inner, out, missing);
+            }
+            Segment::Var(e) => match eval(e, vars) {
+                Ok(Some(v)) => out.push_str(&v),
+                Ok(None) => {
+                    note_missing(missing, e.key);
+                    out.push_str(e.raw);
+                }
+                Err(err) => {
+                    log::warn!("{}: {err:#}", e.raw);
+                    note_missing(missing, &format!("{} ({err})", e.key));
+                    out.push_str(e.raw);
                 }
             },
+            Segment::For { var, list, body } => {
+                let Some(len) = list_len(vars, list) else {
+                    log::warn!("{{% for {var} in {list} %}}: '{list}' is not a list");
+                    note_missing(missing, list);
+                    continue;
+                };
+                for i in 0..len {
+                    render_into(body, &loop_scope(vars, var, list, i, len), macros, out, missing);
+                }
+            }
+            Segment::If { cond, then, otherwise } => {
+                let branch = if test(cond, vars) { then } else { otherwise };
+                render_into(branch, vars, macros, out, missing);
+            }
         }
     }
+}
 
-    out
+/// Evaluate an `if` condition. Undefined variables are falsy and compare
+/// unequal to everything; values that both parse as numbers compare
+/// numerically, anything else as strings.
+fn test(cond: &Cond<'_>, vars: &HashMap<String, String>) -> bool {
+    let operand = |arg: &Arg<'_>| -> Option<String> {
+        match arg {
+            Arg::Num(n) => Some(n.to_string()),
+            Arg::Str(s) => Some((*s).to_owned()),
+            Arg::Var(k) => vars.get(*k).cloned(),
+        }
+    };
+
+    let result = match &cond.cmp {
+        None => operand(&cond.lhs).is_some_and(|v| !matches!(v.as_str(), "" | "false" | "0")),
+        Some((cmp, rhs)) => match (operand(&cond.lhs), operand(rhs)) {
+            (Some(a), Some(b)) => {
+                let ord = match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+                    (Ok(x), Ok(y)) => x.partial_cmp(&y),
+                    _ => Some(a.cmp(&b)),
+                };
+                ord.is_some_and(|o| match cmp {
+                    Cmp::Eq => o.is_eq(),
+                    Cmp::Ne => o.is_ne(),
+                    Cmp::Lt => o.is_lt(),
+                    Cmp::Le => o.is_le(),
+                    Cmp::Gt => o.is_gt(),
+                    Cmp::Ge => o.is_ge(),
+                })
+            }
+            _ => *cmp == Cmp::Ne,
+        },
+    };
+    result != cond.negate
+}
+
+fn note_missing(missing: &mut Vec<String>, key: &str) {
+    if !missing.iter().any(|m| m == key) {
+        missing.push(key.to_owned());
+    }
+}
+
+/// Tables in colors.toml whose `<table>.<app>` keys apply only to that app.
+/// Later entries win over earlier ones.
+pub(super) const APP_TABLES: [&str; 2] = ["app", "overrides"];
+
+/// `vars` with every `app_<app>_<key>` / `overrides_<app>_<key>` (from
+/// `[app.<app>]` / `[overrides.<app>]` in colors.toml) also bound as `<key>`,
+/// shadowing the global value.
+pub(super) fn scoped<'v>(vars: &'v HashMap<String, String>, app: Option<&str>) -> Cow<'v, HashMap<String, String>> {
+    let Some(app) = app else {
+        return Cow::Borrowed(vars);
+    };
+    let mut overrides: Vec<(String, String)> = Vec::new();
+    for table in APP_TABLES {
+        let prefix = format!("{table}_{app}_");
+        overrides.extend(vars.iter().filter_map(|(k, v)| Some((k.strip_prefix(&prefix)?.to_owned(), v.clone()))));
+    }
+    if overrides.is_empty() {
+        return Cow::Borrowed(vars);
+    }
+    let mut scope = vars.clone();
+    scope.extend(overrides);
+    Cow::Owned(scope)
+}
+
+/// Length of a flattened TOML array (`<list>_len`).
+fn list_len(vars: &HashMap<String, String>, list: &str) -> Option<usize> {
+    vars.get(&format!("{list}_len"))?.parse().ok()
+}
+
+/// `vars` plus aliases binding `var` (and every `var_<suffix>`) to element
+/// `i` of `list`, and `loop.index` (1-based) / `loop.index0`.
+fn loop_scope(vars: &HashMap<String, String>, var: &str, list: &str, i: usize, len: usize) -> HashMap<String, String> {
+    let prefix = format!("{list}_{i}");
+    let mut scope = vars.clone();
+    for (k, v) in vars {
+        if let Some(rest) = k.strip_prefix(&prefix)
+            && (rest.is_empty() || rest.starts_with('_'))
+        {
+            scope.insert(format!("{var}{rest}"), v.clone());
+        }
+    }
+    scope.insert("loop.index".to_owned(), (i + 1).to_string());
+    scope.insert("loop.index0".to_owned(), i.to_string());
+    scope.insert("loop.first".to_owned(), (i == 0).to_string());
+    scope.insert("loop.last".to_owned(), (i + 1 == len).to_string());
+    scope
 }
 
-/// Walk `dir` and yield paths of all `*.tpl` files.
-fn templates_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
+/// `vars` plus each macro parameter bound to its argument. A variable
+/// argument also binds its derived keys (`color_rgb` for `palette_accent_rgb`);
+/// a literal color gets them computed. Parameters without an argument stay
+/// undefined.
+fn macro_scope(vars: &HashMap<String, String>, params: &[&str], args: &[Arg<'_>]) -> HashMap<String, String> {
+    let mut scope = vars.clone();
+    for param in params {
+        scope.remove(*param);
+    }
+    for (param, arg) in params.iter().zip(args) {
+        match arg {
+            Arg::Var(key) => {
+                for (k, v) in vars {
+                    if let Some(rest) = k.strip_prefix(key)
+                        && (rest.is_empty() || rest.starts_with('_'))
+                    {
+                        scope.insert(format!("{param}{rest}"), v.clone());
+                    }
+                }
+            }
+            Arg::Str(s) => {
+                if s.starts_with('#') {
+                    scope.extend(derive_color_keys(param, s));
+                }
+                scope.entry((*param).to_owned()).or_insert_with(|| (*s).to_owned());
+            }
+            Arg::Num(n) => {
+                scope.insert((*param).to_owned(), n.to_string());
+            }
+        }
+    }
+    scope
+}
+
+/// Look up `expr.key` and run it through its filters.
+///
+/// An undefined value skips ordinary filters until a `default(...)` supplies
+/// one. Returns `Ok(None)` when the result is still undefined.
+fn eval<'v>(expr: &Expr<'_>, vars: &'v HashMap<String, String>) -> Result<Option<Cow<'v, str>>> {
+    let mut value = vars.get(expr.key).map(|v| Cow::Borrowed(v.as_str()));
+    for filter in &expr.filters {
+        value = match value {
+            None if filter.name == "default" => Some(Cow::Owned(filters::default(filter, vars)?)),
+            None => None,
+            Some(v) if filter.name == "default" => Some(v),
+            Some(v) => Some(Cow::Owned(filters::apply(filter, &v, vars)?)),
+        };
+    }
+    Ok(value)
+}
+
+/// Whether `tpl` goes through the minijinja backend: `#! engine` decides,
+/// otherwise the `.j2` extension does.
+pub(super) fn is_jinja(tpl: &Path, fm: &FrontMatter) -> bool {
+    fm.jinja.unwrap_or_else(|| tpl.extension().is_some_and(|x| x == "j2"))
+}
+
+fn is_template(path: &Path) -> bool {
+    matches!(path.extension().and_then(|x| x.to_str()), Some("tpl" | "j2"))
+}
+
+/// Walk `dir` and yield paths of all `*.tpl` and `*.j2` files.
+pub(super) fn templates_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
     WalkDir::new(dir)
         .into_iter()
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.file_type().is_file() && e.path().extension().and_then(|x| x.to_str()) == Some("tpl")
-        })
+        .filter(|e| e.file_type().is_file() && is_template(e.path()))
         .map(|e| e.into_path())
 }
 
-fn theme_files_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
+/// Walk `dir` and yield every non-template file, skipping hidden entries.
+pub(super) fn assets_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
     WalkDir::new(dir)
         .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && !is_template(e.path()))
+        .map(|e| e.into_path())
+}
+
+fn theme_files_in(files: ThemeFiles) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(files.dir)
+        .into_iter()
+        .filter_entry(move |e| files.backgrounds != Some(e.path()))
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file() && !is_theme_metadata(e.path()))
         .map(|e| e.into_path())
@@ -205,6 +909,175 @@ fn theme_files_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
 
 fn is_theme_metadata(path: &Path) -> bool {
     path.components().any(|c| {
-        matches!(c.as_os_str().to_str(), Some("colors.toml" | "light.mode" | "icons.theme" | "backgrounds"))
+        matches!(c.as_os_str().to_str(), Some(
+                "colors.toml"
+                    | "colors-light.toml"
+                    | "colors-dark.toml"
+                    | "colors.d"
+                    | "theme.toml"
+                    | "light.mode"
+                    | "icons.theme"
+            ))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn unknown_key_is_left_in_place() {
+        assert_eq!(expand("a={{ nope }}", &vars(&[])), "a={{ nope }}");
+    }
+
+    #[test]
+    fn filters_chain() {
+        let v = vars(&[("bg", "#808080")]);
+        assert_eq!(expand("{{ bg | lighten(100) | darken(100) }}", &v), "#000000");
+    }
+
+    #[test]
+    fn on_picks_the_higher_contrast() {
+        let v = vars(&[("yellow", "#f9e2af"), ("navy", "#1e1e2e"), ("fg", "#cdd6f4")]);
+        assert_eq!(expand("{{ yellow | on }}", &v), "#000000");
+        assert_eq!(expand("{{ navy | on }}", &v), "#ffffff");
+        assert_eq!(expand("{{ yellow | on(navy, fg) }}", &v), "#1e1e2e");
+    }
+
+    #[test]
+    fn if_branches_on_thresholds() {
+        let v = vars(&[("bg_lum", "12"), ("name", "dark"), ("flag", "false")]);
+        assert_eq!(expand("{% if bg_lum < 50 %}light-text{% else %}dark-text{% endif %}", &v), "light-text");
+        assert_eq!(expand("{% if name == \"dark\" %}d{% endif %}", &v), "d");
+        assert_eq!(expand("{% if flag %}y{% else %}n{% endif %}", &v), "n");
+        assert_eq!(expand("{% if not nope %}y{% endif %}{% if nope != 1 %}z{% endif %}", &v), "yz");
+    }
+
+    #[test]
+    fn arithmetic_filters() {
+        let v = vars(&[("font_size", "11"), ("scale", "1.5")]);
+        assert_eq!(expand("{{ font_size | mul(1.25) | round }}", &v), "14");
+        assert_eq!(expand("{{ font_size | mul(scale) }}", &v), "16.5");
+        assert_eq!(expand("{{ font_size | div(3) | round(2) }}", &v), "3.67");
+        assert_eq!(expand("{{ font_size | sub(12) | ceil }}", &v), "-1");
+        assert_eq!(expand("{{ font_size | div(0) }}", &v), "{{ font_size | div(0) }}");
+    }
+
+    #[test]
+    fn for_loop_binds_element_and_derived_keys() {
+        let table: toml::Value = toml::from_str("pal = [\"#000000\", \"#ffffff\"]").unwrap();
+        let v = build_vars(&table);
+        let out = expand("{% for c in pal %}\n{{ loop.index0 }}={{ c_strip }}\n{% endfor %}\n", &v);
+        assert_eq!(out, "0=000000\n1=ffffff\n");
+    }
+
+    #[test]
+    fn derived_color_keys() {
+        let table: toml::Value = toml::from_str("bg = \"#1e1e2e\"").unwrap();
+        let v = build_vars(&table);
+        assert_eq!(v["bg_strip"], "1e1e2e");
+        assert_eq!(v["bg_rgb"], "30,30,46");
+        assert_eq!(v["bg_rgb_func"], "rgb(30, 30, 46)");
+        assert_eq!(v["bg_0x"], "0x1e1e2e");
+        assert_eq!((v["bg_r"].as_str(), v["bg_g"].as_str(), v["bg_b"].as_str()), ("30", "30", "46"));
+        assert_eq!((v["bg_h"].as_str(), v["bg_s"].as_str(), v["bg_l"].as_str()), ("240", "21", "15"));
+        assert_eq!(v["bg_hsv_v"], "18");
+        assert_eq!(v["bg_ansi256"], "235");
+        assert_eq!((v["bg_lum"].as_str(), v["bg_brightness"].as_str()), ("1", "12"));
+        assert_eq!((v["bg_oklch_l"].as_str(), v["bg_oklch_c"].as_str(), v["bg_oklch_h"].as_str()), ("24.3", "0.030", "284"));
+    }
+
+    #[test]
+    fn short_and_alpha_hex_are_normalized() {
+        let table: toml::Value = toml::from_str("a = \"#f80\"\nb = \"#1e1e2ecc\"").unwrap();
+        let v = build_vars(&table);
+        assert_eq!((v["a"].as_str(), v["a_strip"].as_str()), ("#ff8800", "ff8800"));
+        assert!(!v.contains_key("a_alpha"));
+        assert_eq!((v["b"].as_str(), v["b_rgb"].as_str()), ("#1e1e2e", "30,30,46"));
+        assert_eq!((v["b_alpha"].as_str(), v["b_alpha_hex"].as_str()), ("0.8", "cc"));
+    }
+
+    #[test]
+    fn hex_case_applies_to_values_and_filters() {
+        let table: toml::Value = toml::from_str("bg = \"#1e1e2e\"\nname = \"abc\"").unwrap();
+        let mut v = build_vars(&table);
+        apply_hex_case(&mut v, true);
+        assert_eq!((v["bg"].as_str(), v["bg_strip"].as_str(), v["bg_0x"].as_str()), ("#1E1E2E", "1E1E2E", "0x1E1E2E"));
+        assert_eq!(v["name"], "abc");
+        assert_eq!(expand("{{ bg | lighten(0) }} {{ bg | lower }}", &v), "#1E1E2E #1e1e2e");
+    }
+
+    #[test]
+    fn missing_bright_and_dim_colors_are_derived() {
+        let mut src = String::from("[palette]\n");
+        for i in 0..8 {
+            src.push_str(&format!("color{i} = \"#{:02x}4040\"\n", 0x40 + i * 16));
+        }
+        src.push_str("color9 = \"#ff0000\"\n[term]\ncolor0 = \"#000000\"\n");
+        let mut vars = build_vars(&toml::from_str(&src).unwrap());
+        derive_composite(&mut vars);
+
+        assert_eq!(vars["palette_color9"], "#ff0000");
+        let [normal, bright, dim] =
+            ["palette_color0", "palette_color8", "palette_color0_dim"].map(|k| Rgb::parse(&vars[k]).unwrap().to_oklch().0);
+        assert!(bright > normal && dim < normal, "{normal} {bright} {dim}");
+        assert!(vars.contains_key("palette_color15_rgb"));
+        assert!(!vars.contains_key("term_color8"));
+        assert!(vars["palette_wezterm_ansi"].starts_with("[\"#404040\", \"#504040\""));
+        assert!(vars["palette_wezterm_brights"].contains("\"#ff0000\""));
+        assert!(!vars.contains_key("term_wezterm_ansi"));
+    }
+
+    #[test]
+    fn app_keys_shadow_globals() {
+        let table: toml::Value = toml::from_str("bg = \"#000000\"\n[app.kitty]\nbg = \"#111111\"").unwrap();
+        let v = build_vars(&table);
+        assert_eq!(expand("{{ bg }} {{ bg_rgb }}", &scoped(&v, Some("kitty"))), "#111111 17,17,17");
+        assert_eq!(expand("{{ bg }}", &scoped(&v, Some("foot"))), "#000000");
+        assert_eq!(expand("{{ bg }}", &scoped(&v, None)), "#000000");
+
+        let table: toml::Value =
+            toml::from_str("bg = \"#000000\"\n[app.kitty]\nbg = \"#111111\"\n[overrides.kitty]\nbg = \"#222222\"").unwrap();
+        assert_eq!(expand("{{ bg }}", &scoped(&build_vars(&table), Some("kitty"))), "#222222");
+    }
+
+    #[test]
+    fn macros_bind_arguments() {
+        let v = vars(&[("accent", "#ff0000"), ("accent_rgb", "255,0,0")]);
+        let tpl = "{% macro ws(n, color) %}\n#ws{{ n }} { color: {{ color }}; rgb: {{ color_rgb }}; }\n{% endmacro %}\n\
+                   {{ ws(1, accent) }}{{ ws(2, \"#00f\") }}{{ nope(1) }}";
+        assert_eq!(
+            expand(tpl, &v),
+            "#ws1 { color: #ff0000; rgb: 255,0,0; }\n#ws2 { color: #0000ff; rgb: 0,0,255; }\n{{ nope(1) }}"
+        );
+        assert_eq!(expand("{% macro m() %}{{ m() }}{% endmacro %}{{ m() }}", &v), "{{ m() }}");
+    }
+
+    #[test]
+    fn default_fills_undefined_only() {
+        let v = vars(&[("bg", "#1e1e2e"), ("fg", "#cdd6f4")]);
+        assert_eq!(expand("{{ nope | default(\"#000000\") }}", &v), "#000000");
+        assert_eq!(expand("{{ nope | default(fg) }}", &v), "#cdd6f4");
+        assert_eq!(expand("{{ bg | default(fg) }}", &v), "#1e1e2e");
+    }
+
+    #[test]
+    fn theme_files_skip_metadata_and_wallpapers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (templates, theme) = (tmp.path().join("templates"), tmp.path().join("theme"));
+        for dir in [templates.clone(), theme.join("walls"), theme.join("kitty")] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for file in ["colors.toml", "theme.toml", "walls/a.png", "kitty/theme.conf"] {
+            fs::write(theme.join(file), "").unwrap();
+        }
+        let files = ThemeFiles { dir: &theme, backgrounds: Some(&theme.join("./walls")) };
+        let jobs = plan(&[templates], &tmp.path().join("user"), files).unwrap();
+        let outs: Vec<_> = jobs.iter().map(job_out).collect();
+        assert_eq!(outs, [Path::new("kitty/theme.conf")]);
+    }
+}
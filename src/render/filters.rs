@@ -0,0 +1,211 @@
+//! Filters applied to variable values: `{{ key | name(args) }}`.
+
+use super::parser::{Arg, Filter};
+use crate::color::Rgb;
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+
+/// Apply `filter` to `input`, resolving bare-identifier args from `vars`.
+pub fn apply(filter: &Filter<'_>, input: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let args = Args { filter, vars };
+
+    match filter.name {
+        "lighten" => {
+            let amount = args.num(0)?;
+            args.arity(1)?;
+            Ok(args.hex(color(input)?.lighten(amount)))
+        }
+        "darken" => {
+            let amount = args.num(0)?;
+            args.arity(1)?;
+            Ok(args.hex(color(input)?.darken(amount)))
+        }
+        "saturate" => {
+            let amount = args.num(0)?;
+            args.arity(1)?;
+            Ok(args.hex(color(input)?.saturate(amount)))
+        }
+        "desaturate" => {
+            let amount = args.num(0)?;
+            args.arity(1)?;
+            Ok(args.hex(color(input)?.desaturate(amount)))
+        }
+        "rotate_hue" => {
+            let degrees = args.num(0)?;
+            args.arity(1)?;
+            Ok(args.hex(color(input)?.rotate_hue(degrees)))
+        }
+        "mix" => {
+            let other = args.color(0)?;
+            let ratio = args.opt_num(1)?.unwrap_or(0.5);
+            args.arity(2)?;
+            Ok(args.hex(color(input)?.mix(other, ratio)))
+        }
+        "alpha" => {
+            let alpha = args.num(0)?;
+            let format = args.opt_word(1)?.unwrap_or("rgba");
+            args.arity(2)?;
+            if !(0.0..=1.0).contains(&alpha) {
+                bail!("alpha(): {alpha} is outside 0..1");
+            }
+            let c = color(input)?;
+            let a8 = (alpha * 255.0).round() as u8;
+            match format {
+                "rgba" => Ok(format!("rgba({},{},{},{alpha})", c.r, c.g, c.b)),
+                "hex" => Ok(args.cased(format!("{}{a8:02x}", c.to_hex()))),
+                "hypr" => Ok(format!("rgba({})", args.cased(format!("{}{a8:02x}", &c.to_hex()[1..])))),
+                other => bail!("alpha(): unknown format '{other}' (expected rgba, hex, or hypr)"),
+            }
+        }
+        "on" => {
+            let (dark, light) = match filter.args.len() {
+                0 => (Rgb::BLACK, Rgb::WHITE),
+                2 => (args.color(0)?, args.color(1)?),
+                _ => bail!("on() takes no arguments or two colors"),
+            };
+            let bg = color(input)?;
+            let pick = if bg.contrast(dark) >= bg.contrast(light) { dark } else { light };
+            Ok(args.hex(pick))
+        }
+        "upper" | "lower" => {
+            args.arity(0)?;
+            Ok(if filter.name == "upper" { input.to_uppercase() } else { input.to_lowercase() })
+        }
+        "add" | "sub" | "mul" | "div" => {
+            let operand = args.num(0)?;
+            args.arity(1)?;
+            let n = number(input)?;
+            let result = match filter.name {
+                "add" => n + operand,
+                "sub" => n - operand,
+                "mul" => n * operand,
+                _ if operand == 0.0 => bail!("div(): division by zero"),
+                _ => n / operand,
+            };
+            Ok(format_number(result))
+        }
+        "round" => {
+            let places = args.opt_num(0)?.unwrap_or(0.0);
+            args.arity(1)?;
+            let scale = 10f64.powi(places as i32);
+            Ok(format_number((number(input)? * scale).round() / scale))
+        }
+        "floor" | "ceil" => {
+            args.arity(0)?;
+            let n = number(input)?;
+            Ok(format_number(if filter.name == "floor" { n.floor() } else { n.ceil() }))
+        }
+        other => bail!("unknown filter '{other}'"),
+    }
+}
+
+/// Value of `default(x)` for an undefined input: a literal, or another
+/// variable's value when `x` is a bare identifier.
+pub fn default(filter: &Filter<'_>, vars: &HashMap<String, String>) -> Result<String> {
+    let args = Args { filter, vars };
+    args.arity(1)?;
+    match args.get(0)? {
+        Arg::Str(s) => Ok((*s).to_owned()),
+        Arg::Num(n) => Ok(n.to_string()),
+        Arg::Var(k) => args.var(k).map(str::to_owned),
+    }
+}
+
+fn number(value: &str) -> Result<f64> {
+    value.trim().parse().with_context(|| format!("'{value}' is not a number"))
+}
+
+/// Whole numbers print without a fractional part (`15`, not `15.0`).
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        let s = format!("{n:.6}");
+        s.trim_end_matches('0').trim_end_matches('.').to_owned()
+    }
+}
+
+fn color(value: &str) -> Result<Rgb> {
+    Rgb::parse(value).with_context(|| format!("'{value}' is not a #rrggbb color"))
+}
+
+/// Typed access to a filter's arguments.
+struct Args<'f, 'a> {
+    filter: &'f Filter<'a>,
+    vars: &'f HashMap<String, String>,
+}
+
+impl Args<'_, '_> {
+    fn arity(&self, max: usize) -> Result<()> {
+        if self.filter.args.len() > max {
+            bail!("{}() takes at most {max} argument(s)", self.filter.name);
+        }
+        Ok(())
+    }
+
+    fn get(&self, i: usize) -> Result<&Arg<'_>> {
+        self.filter
+            .args
+            .get(i)
+            .with_context(|| format!("{}() is missing argument {}", self.filter.name, i + 1))
+    }
+
+    fn num(&self, i: usize) -> Result<f64> {
+        match self.get(i)? {
+            Arg::Num(n) => Ok(*n),
+            Arg::Str(s) => s.parse().with_context(|| format!("{}(): '{s}' is not a number", self.filter.name)),
+            Arg::Var(k) => {
+                let v = self.var(k)?;
+                v.parse().with_context(|| format!("{}(): {k} = '{v}' is not a number", self.filter.name))
+            }
+        }
+    }
+
+    fn opt_num(&self, i: usize) -> Result<Option<f64>> {
+        if i < self.filter.args.len() {
+            self.num(i).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// A color: a `#hex` literal or the name of a color variable.
+    fn color(&self, i: usize) -> Result<Rgb> {
+        let value = match self.get(i)? {
+            Arg::Str(s) => s,
+            Arg::Var(k) => self.var(k)?,
+            Arg::Num(n) => bail!("{}(): expected a color, got {n}", self.filter.name),
+        };
+        color(value).with_context(|| format!("{}() argument {}", self.filter.name, i + 1))
+    }
+
+    /// An optional option-like argument: a quoted string or a bare word
+    /// (bare words are taken literally here, not looked up as variables).
+    fn opt_word(&self, i: usize) -> Result<Option<&str>> {
+        match self.filter.args.get(i) {
+            None => Ok(None),
+            Some(Arg::Str(s) | Arg::Var(s)) => Ok(Some(s)),
+            Some(Arg::Num(n)) => bail!("{}(): expected a word, got {n}", self.filter.name),
+        }
+    }
+
+    /// `color` as `#rrggbb`, in the case set by `[colors] hex_case`.
+    fn hex(&self, color: Rgb) -> String {
+        self.cased(color.to_hex())
+    }
+
+    fn cased(&self, hex: String) -> String {
+        if self.vars.get("hex_case").is_some_and(|c| c == "upper") {
+            hex.to_uppercase()
+        } else {
+            hex
+        }
+    }
+
+    fn var(&self, key: &str) -> Result<&str> {
+        self.vars
+            .get(key)
+            .map(String::as_str)
+            .with_context(|| format!("{}(): undefined variable '{key}'", self.filter.name))
+    }
+}
@@ -0,0 +1,157 @@
+//! Optional `#!` header at the top of a template.
+//!
+//! ```text
+//! #! out = "~/.config/foo/theme.conf"
+//! [theme]
+//! bg = {{ palette_bg }}
+//! ```
+//!
+//! Every leading `#! key = value` line holds one TOML pair; the header ends
+//! at the first line that isn't one, so a shebang like `#!/bin/sh` is left
+//! alone. Header lines are not part of the rendered output.
+
+use crate::util;
+use anyhow::{Context, Result, bail};
+use std::path::{Component, Path, PathBuf};
+
+/// Directives from a template header.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    /// Output path: relative to the live tree, or absolute / `~/`-prefixed
+    /// for a file that must live outside it.
+    pub out: Option<String>,
+    /// Permission bits for the output, e.g. `mode = 0o755`. Without it the
+    /// template's own mode bits are copied.
+    pub mode: Option<u32>,
+    /// Write a real file at an outside `out` instead of a symlink.
+    pub copy: bool,
+    /// Render with the minijinja backend (`engine = "jinja"`) instead of the
+    /// built-in one. `.j2` templates default to it.
+    pub jinja: Option<bool>,
+    /// Variable namespace (`app = "kitty"`): keys from `[app.kitty]` in
+    /// colors.toml shadow the global ones. Defaults to the template's
+    /// top-level directory.
+    pub app: Option<String>,
+}
+
+/// Where a template's output goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dest {
+    /// A path inside the live tree.
+    Live(PathBuf),
+    /// Rendered into the live tree at the mirrored path, and linked (or,
+    /// with `copy`, written) to a fixed absolute path after commit.
+    External(PathBuf),
+}
+
+/// Split `src` into its header and the template body that follows it.
+pub fn split(src: &str) -> Result<(FrontMatter, &str)> {
+    let mut header = String::new();
+    let mut body = src;
+    while let Some(line) = body.strip_prefix("#!") {
+        let (line, rest) = line.split_once('\n').unwrap_or((line, ""));
+        if !is_directive(line) {
+            break;
+        }
+        header.push_str(line.trim());
+        header.push('\n');
+        body = rest;
+    }
+    if header.is_empty() {
+        return Ok((FrontMatter::default(), src));
+    }
+
+    let table: toml::Table = header.parse().context("parse #! header")?;
+    let mut fm = FrontMatter::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "out" => {
+                let out = value.as_str().context("#! out must be a string")?;
+                fm.out = Some(out.to_owned());
+            }
+            "mode" => {
+                let mode = value.as_integer().context("#! mode must be an integer like 0o644")?;
+                match u32::try_from(mode) {
+                    Ok(mode) if mode <= 0o7777 => fm.mode = Some(mode),
+                    _ => bail!("#! mode = {mode:#o} is not a valid permission mode"),
+                }
+            }
+            "copy" => fm.copy = value.as_bool().context("#! copy must be true or false")?,
+            "app" => {
+                let app = value.as_str().context("#! app must be a string")?;
+                fm.app = Some(app.to_owned());
+            }
+            "engine" => match value.as_str() {
+                Some("jinja") => fm.jinja = Some(true),
+                Some("builtin") => fm.jinja = Some(false),
+                _ => bail!("#! engine must be \"builtin\" or \"jinja\""),
+            },
+            other => bail!("unknown #! directive '{other}'"),
+        }
+    }
+    Ok((fm, body))
+}
+
+/// `key = ...` with a bare TOML key, as opposed to an interpreter path.
+fn is_directive(line: &str) -> bool {
+    let Some((key, _)) = line.split_once('=') else {
+        return false;
+    };
+    let key = key.trim();
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl FrontMatter {
+    /// Resolve `out` against the default `mirrored` output path.
+    pub fn dest(&self, mirrored: &Path) -> Result<Dest> {
+        let Some(out) = &self.out else {
+            if self.copy {
+                bail!("#! copy needs an absolute #! out");
+            }
+            return Ok(Dest::Live(mirrored.to_owned()));
+        };
+        let path = util::expand_home(out);
+        if path.is_absolute() {
+            return Ok(Dest::External(path));
+        }
+        if self.copy {
+            bail!("#! copy needs an absolute #! out, not \"{out}\"");
+        }
+        if path.components().any(|c| !matches!(c, Component::Normal(_))) {
+            bail!("#! out = \"{out}\" must stay inside the live tree");
+        }
+        Ok(Dest::Live(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_is_stripped_from_body() {
+        let (fm, body) = split("#! out = \"foo/bar.conf\"\n#! mode = 0o755\nbg = 1\n").unwrap();
+        assert_eq!(fm.out.as_deref(), Some("foo/bar.conf"));
+        assert_eq!(fm.mode, Some(0o755));
+        assert_eq!(body, "bg = 1\n");
+        assert_eq!(fm.dest(Path::new("x")).unwrap(), Dest::Live("foo/bar.conf".into()));
+    }
+
+    #[test]
+    fn no_header_leaves_source_alone() {
+        let (fm, body) = split("# comment\n#! mode = 1\n").unwrap();
+        assert_eq!(fm, FrontMatter::default());
+        assert_eq!(body, "# comment\n#! mode = 1\n");
+
+        let (_, body) = split("#! mode = 0o755\n#!/usr/bin/env -S a=b sh\n").unwrap();
+        assert_eq!(body, "#!/usr/bin/env -S a=b sh\n");
+    }
+
+    #[test]
+    fn rejects_unknown_directives_and_escapes() {
+        assert!(split("#! colour = \"x\"\n").is_err());
+        assert!(split("#! mode = 0o17777\n").is_err());
+        let (fm, _) = split("#! out = \"../evil\"\n").unwrap();
+        assert!(fm.dest(Path::new("x")).is_err());
+    }
+}
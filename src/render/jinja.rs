@@ -0,0 +1,66 @@
+//! Optional minijinja backend for templates that outgrow the built-in parser.
+//!
+//! Selected per template by a `.j2` extension or `#! engine = "jinja"`. The
+//! flattened variables are the template context, and the built-in color
+//! filters are available under the same names.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+#[cfg(feature = "jinja")]
+pub fn render(src: &str, vars: &HashMap<String, String>, strict: bool) -> Result<String> {
+    use super::{
+        filters,
+        parser::{Arg, Filter},
+    };
+    use minijinja::{Environment, Error, ErrorKind, UndefinedBehavior, value::Rest, value::Value};
+
+    const FILTERS: &[&str] = &[
+        "lighten", "darken", "saturate", "desaturate", "rotate_hue", "mix", "alpha", "on", "add", "sub", "mul",
+        "div", "round", "floor", "ceil",
+    ];
+
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.set_undefined_behavior(if strict {
+        UndefinedBehavior::Strict
+    } else {
+        UndefinedBehavior::Lenient
+    });
+    for &name in FILTERS {
+        env.add_filter(name, move |input: String, args: Rest<Value>| -> Result<String, Error> {
+            let owned: Vec<Option<String>> = args.iter().map(|a| a.as_str().map(str::to_owned)).collect();
+            let args = args
+                .iter()
+                .zip(&owned)
+                .map(|(value, s)| match s {
+                    Some(s) => Arg::Str(s),
+                    None => Arg::Num(f64::try_from(value.clone()).unwrap_or(f64::NAN)),
+                })
+                .collect();
+            filters::apply(&Filter { name, args }, &input, &HashMap::new())
+                .map_err(|e| Error::new(ErrorKind::InvalidOperation, format!("{e:#}")))
+        });
+    }
+
+    Ok(env.render_str(src, vars)?)
+}
+
+#[cfg(not(feature = "jinja"))]
+pub fn render(_src: &str, _vars: &HashMap<String, String>, _strict: bool) -> Result<String> {
+    anyhow::bail!("this template needs the jinja engine; rebuild with `--features jinja`")
+}
+
+#[cfg(all(test, feature = "jinja"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_filters_and_strict_mode() {
+        let vars = HashMap::from([("bg".to_owned(), "#000000".to_owned())]);
+        let out = render("{{ bg | mix(\"#ffffff\", 0.5) }}{% if bg %}!{% endif %}\n", &vars, false).unwrap();
+        assert_eq!(out, "#636363!\n");
+        assert_eq!(render("{{ nope }}", &vars, false).unwrap(), "");
+        assert!(render("{{ nope }}", &vars, true).is_err());
+    }
+}
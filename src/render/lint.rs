@@ -0,0 +1,205 @@
+//! `oxidize lint` — cross-check the template collection against the themes.
+//!
+//! Reports keys that templates reference but no theme defines, theme keys no
+//! template references, and sources in the same directory that would write
+//! the same output file.
+
+use super::{
+    engine::{self, Job},
+    frontmatter,
+    parser::{parse, Arg, Cond, Segment},
+};
+use crate::{ctx::Ctx, term, theme::Theme};
+use anyhow::{Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Keys seen in one template.
+#[derive(Default)]
+struct Refs {
+    /// Referenced without a fallback: must be defined somewhere.
+    required: BTreeSet<String>,
+    /// Referenced at all (also via `default(...)`, conditions, loops).
+    used: BTreeSet<String>,
+}
+
+/// Print every finding and return how many there were.
+pub fn run(ctx: &Ctx, themes: &[Theme]) -> Result<usize> {
+    let color = term::stdout_color();
+    let mut findings = 0;
+    let mut report = |kind: &str, what: String| {
+        findings += 1;
+        println!("{} {what}", term::paint(color, "33", &format!("{kind:<9}")));
+    };
+
+    let mut templates: Vec<PathBuf> = Vec::new();
+    for dir in std::iter::once(&ctx.user_templates_dir).chain(&ctx.templates_dirs) {
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut outputs: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        let mut targets: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for tpl in engine::templates_in(dir) {
+            match engine::render_job(tpl.clone(), dir) {
+                Ok(Job::Render { out, target, .. }) => {
+                    outputs.entry(out).or_default().push(tpl.clone());
+                    if let Some(target) = target {
+                        targets.entry(target).or_default().push(tpl.clone());
+                    }
+                }
+                Ok(Job::Copy { .. }) => {}
+                Err(e) => report("error", format!("{e:#}")),
+            }
+            templates.push(tpl);
+        }
+        for src in engine::assets_in(dir) {
+            let out = src.strip_prefix(dir)?.to_path_buf();
+            outputs.entry(out).or_default().push(src);
+        }
+        for (out, sources) in outputs.iter().chain(&targets).filter(|(_, s)| s.len() > 1) {
+            let names: Vec<_> = sources.iter().map(|s| rel(dir, s)).collect();
+            report("collision", format!("{} <- {}", out.display(), names.join(", ")));
+        }
+    }
+
+    let mut used = BTreeSet::new();
+    let mut undefined: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for tpl in &templates {
+        let src = fs::read_to_string(tpl).with_context(|| format!("read template {}", tpl.display()))?;
+        let Ok((fm, body)) = frontmatter::split(&src) else {
+            continue; // already reported above
+        };
+        if engine::is_jinja(tpl, &fm) {
+            continue;
+        }
+        let mut refs = Refs::default();
+        collect(&parse(body), &[], &mut refs);
+        for key in &refs.required {
+            if !themes.iter().any(|t| t.vars.contains_key(key)) {
+                undefined.entry(key.clone()).or_default().push(tpl.display().to_string());
+            }
+        }
+        used.extend(refs.used);
+    }
+    for (key, tpls) in undefined {
+        report("undefined", format!("{key} (in {})", tpls.join(", ")));
+    }
+
+    // A variable a theme maps to a role is used wherever the role is.
+    for theme in themes {
+        let sources = theme.roles.values().filter_map(|s| engine::resolve_key(&theme.vars, s));
+        used.extend(sources.map(|(key, _)| key.to_owned()));
+    }
+
+    let mut unused: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for theme in themes {
+        for key in source_keys(&theme.root)? {
+            // `app_kitty_bg` and `overrides_kitty_bg` are used wherever `bg` is.
+            let bare = engine::APP_TABLES
+                .iter()
+                .find_map(|t| key.strip_prefix(t)?.strip_prefix('_')?.split_once('_'))
+                .map_or(key.as_str(), |(_, k)| k);
+            if !is_used(bare, &used) {
+                unused.entry(key).or_default().push(&theme.name);
+            }
+        }
+    }
+    for (key, names) in unused {
+        report("unused", format!("{key} (themes: {})", names.join(", ")));
+    }
+
+    Ok(findings)
+}
+
+/// Walk `segments`, skipping keys bound by enclosing `{% for %}` loops and
+/// macro parameters.
+fn collect(segments: &[Segment<'_>], bound: &[&str], refs: &mut Refs) {
+    let is_bound = |key: &str| {
+        key.starts_with("loop.")
+            || key.starts_with("env.")
+            || bound.iter().any(|v| key == *v || key.strip_prefix(v).is_some_and(|r| r.starts_with('_')))
+    };
+    let note = |key: &str, required: bool, refs: &mut Refs| {
+        if !is_bound(key) {
+            refs.used.insert(key.to_owned());
+            if required {
+                refs.required.insert(key.to_owned());
+            }
+        }
+    };
+
+    for seg in segments {
+        match seg {
+            Segment::Lit(_) => {}
+            Segment::Var(e) => {
+                let optional = e.filters.iter().any(|f| f.name == "default");
+                note(e.key, !optional, refs);
+                for arg in e.filters.iter().flat_map(|f| &f.args) {
+                    if let Arg::Var(k) = arg {
+                        note(k, false, refs);
+                    }
+                }
+            }
+            Segment::For { var, list, body } => {
+                note(list, true, refs);
+                let mut inner = bound.to_vec();
+                inner.push(var);
+                collect(body, &inner, refs);
+            }
+            Segment::If { cond, then, otherwise } => {
+                let Cond { lhs, cmp, .. } = cond;
+                for arg in std::iter::once(lhs).chain(cmp.as_ref().map(|(_, rhs)| rhs)) {
+                    if let Arg::Var(k) = arg {
+                        note(k, false, refs);
+                    }
+                }
+                collect(then, bound, refs);
+                collect(otherwise, bound, refs);
+            }
+            Segment::Macro { params, body, .. } => {
+                let mut inner = bound.to_vec();
+                inner.extend(params);
+                collect(body, &inner, refs);
+            }
+            Segment::Call { args, .. } => {
+                for arg in args {
+                    if let Arg::Var(k) = arg {
+                        note(k, true, refs);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A theme key counts as used if a template references it, one of its
+/// derived keys (`palette_bg_rgb`), or the array it belongs to.
+fn is_used(key: &str, used: &BTreeSet<String>) -> bool {
+    used.iter().any(|r| {
+        r == key
+            || r.strip_prefix(key).is_some_and(|rest| rest.starts_with('_'))
+            || key.strip_prefix(r.as_str()).is_some_and(|rest| rest.starts_with('_'))
+    })
+}
+
+/// Keys defined directly in a theme's palette files, without derived
+/// notations.
+fn source_keys(root: &Path) -> Result<Vec<String>> {
+    let mut files: Vec<PathBuf> = ["colors.toml", "colors-light.toml", "colors-dark.toml"]
+        .iter()
+        .map(|f| root.join(f))
+        .filter(|p| p.is_file())
+        .collect();
+    files.extend(engine::palette_dropins(root)?);
+    let table = engine::read_layers(&files)?;
+    let mut vars = HashMap::new();
+    engine::flatten("", &table, &mut vars);
+    Ok(vars.into_keys().collect())
+}
+
+fn rel(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir).unwrap_or(path).display().to_string()
+}
@@ -1,13 +1,45 @@
 //! Template rendering facade.
 
+pub mod bench;
 pub mod engine;
+pub mod filters;
+pub mod frontmatter;
+pub mod jinja;
+pub mod lint;
 pub mod parser;
+pub mod rules;
 
-use crate::ctx::Ctx;
+use crate::{ctx::Ctx, log, theme::Theme, util};
 use anyhow::Result;
-use std::{collections::HashMap, path::Path};
+use std::{fs, path::Path};
 
-/// Render all templates for a theme into `out_dir`.
-pub fn render_all(ctx: &Ctx, theme_root: &Path, out_dir: &Path, vars: &HashMap<String, String>) -> Result<()> {
-    engine::render_all(&ctx.templates_dir, &ctx.user_templates_dir, theme_root, out_dir, vars)
+pub use engine::{External, Options, ThemeFiles};
+
+/// Render all templates for a theme into `out_dir`, reusing unchanged files
+/// from `prev_dir` (the tree it will replace).
+///
+/// Returns the outputs that must also be linked from outside the live tree.
+pub fn render_all(ctx: &Ctx, theme: &Theme, out_dir: &Path, prev_dir: &Path, opts: Options) -> Result<Vec<External>> {
+    let prev_dir = prev_dir.is_dir().then_some(prev_dir);
+    let theme_files = ThemeFiles { dir: &theme.root, backgrounds: theme.backgrounds_dir.as_deref() };
+    engine::render_all(&ctx.templates_dirs, &ctx.user_templates_dir, theme_files, out_dir, prev_dir, &theme.vars, opts)
+}
+
+/// Point every linked external target at its file under `current/`
+/// (copies are handled by [`crate::external`]).
+///
+/// Links go through `current` so they stay valid across switches. A regular
+/// file already sitting at a target is never replaced.
+pub fn link_externals(ctx: &Ctx, externals: &[External]) {
+    for ext in externals.iter().filter(|e| !e.copy) {
+        if fs::symlink_metadata(&ext.target).is_ok_and(|m| !m.file_type().is_symlink()) {
+            log::warn!("{} exists and is not a symlink; leaving it alone", ext.target.display());
+            continue;
+        }
+        let src = ctx.current_link.join(&ext.out);
+        log::debug!("link {} -> {}", ext.target.display(), src.display());
+        if let Err(e) = util::symlink_force(&src, &ext.target) {
+            log::warn!("{e:#}");
+        }
+    }
 }
@@ -1,59 +1,380 @@
-//! Minimal `{{ key }}` template parser.
+//! Minimal `{{ key | filter(arg) }}` / `{% for %}` / `{% if %}` / `{% macro %}`
+//! template parser.
 
 /// A parsed segment of a template.
 #[derive(Debug, Clone)]
 pub enum Segment<'a> {
     /// Literal text to emit verbatim.
     Lit(&'a str),
-    /// Variable name (contents between `{{` and `}}`, trimmed).
+    /// Variable expression (contents between `{{` and `}}`).
+    Var(Expr<'a>),
+    /// `{% for var in list %}body{% endfor %}` over a flattened TOML array.
+    For {
+        var: &'a str,
+        list: &'a str,
+        body: Vec<Segment<'a>>,
+    },
+    /// `{% if cond %}then{% else %}otherwise{% endif %}`.
+    If {
+        cond: Cond<'a>,
+        then: Vec<Segment<'a>>,
+        otherwise: Vec<Segment<'a>>,
+    },
+    /// `{% macro name(param, ...) %}body{% endmacro %}`. Renders nothing
+    /// where it is defined.
+    Macro {
+        name: &'a str,
+        params: Vec<&'a str>,
+        body: Vec<Segment<'a>>,
+    },
+    /// `{{ name(arg, ...) }}`: expand the macro `name`.
+    Call { raw: &'a str, name: &'a str, args: Vec<Arg<'a>> },
+}
+
+/// An `if` condition: a truthiness test or a comparison of two operands.
+#[derive(Debug, Clone)]
+pub struct Cond<'a> {
+    pub negate: bool,
+    pub lhs: Arg<'a>,
+    pub cmp: Option<(Cmp, Arg<'a>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A variable lookup followed by zero or more filters.
+#[derive(Debug, Clone)]
+pub struct Expr<'a> {
+    /// The whole `{{ ... }}` token, emitted as-is when evaluation fails.
+    pub raw: &'a str,
+    pub key: &'a str,
+    pub filters: Vec<Filter<'a>>,
+}
+
+/// A filter application: `name` or `name(arg, ...)`.
+#[derive(Debug, Clone)]
+pub struct Filter<'a> {
+    pub name: &'a str,
+    pub args: Vec<Arg<'a>>,
+}
+
+/// A filter argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg<'a> {
+    Num(f64),
+    /// Quoted string, or a bare `#hex` literal.
+    Str(&'a str),
+    /// Bare identifier, resolved as a variable at render time.
     Var(&'a str),
 }
 
 /// Parse a template string into a sequence of [`Segment`]s.
 ///
 /// Returns borrowed slices into `input` — no allocations per segment.
+/// Unknown or unbalanced `{% ... %}` tags are kept as literal text. A single
+/// newline directly after a block tag is dropped so tags can sit on their
+/// own lines without leaving blank lines behind.
 pub fn parse(input: &str) -> Vec<Segment<'_>> {
+    // Open blocks, each with the segments of its enclosing scope.
+    let mut stack: Vec<(Block<'_>, Vec<Segment<'_>>)> = Vec::new();
     let mut segments = Vec::new();
     let mut rest = input;
 
     while !rest.is_empty() {
-        match rest.find("{{") {
+        let next = match (rest.find("{{"), rest.find("{%")) {
+            (Some(v), Some(t)) => Some(if t < v { (t, true) } else { (v, false) }),
+            (Some(v), None) => Some((v, false)),
+            (None, Some(t)) => Some((t, true)),
+            (None, None) => None,
+        };
+
+        match next {
             None => {
                 // No more tokens — everything remaining is a literal.
                 segments.push(Segment::Lit(rest));
                 break;
             }
-            Some(open) => {
-                // Emit text before `{{` as a literal (skip empty slices).
+            Some((open, is_tag)) => {
+                // Emit text before the token as a literal (skip empty slices).
                 if open > 0 {
                     segments.push(Segment::Lit(&rest[..open]));
                 }
                 let after_open = &rest[open + 2..];
+                let closer = if is_tag { "%}" } else { "}}" };
 
-                match after_open.find("}}") {
+                match after_open.find(closer) {
                     None => {
-                        // Unclosed `{{` — treat the rest as literal.
+                        // Unclosed token — treat the rest as literal.
                         segments.push(Segment::Lit(&rest[open..]));
                         break;
                     }
                     Some(close) => {
-                        let key = after_open[..close].trim();
-                        if key.is_empty() {
-                            // `{{ }}` — emit as literal.
-                            segments.push(Segment::Lit(&rest[open..open + 2 + close + 2]));
-                        } else {
-                            segments.push(Segment::Var(key));
-                        }
+                        let raw = &rest[open..open + 2 + close + 2];
+                        let body = &after_open[..close];
                         rest = &after_open[close + 2..];
+
+                        if !is_tag {
+                            if let Some(call) = parse_call(raw, body) {
+                                segments.push(call);
+                                continue;
+                            }
+                            match parse_expr(raw, body) {
+                                Some(expr) => segments.push(Segment::Var(expr)),
+                                // `{{ }}` or a malformed expression — emit as literal.
+                                None => segments.push(Segment::Lit(raw)),
+                            }
+                            continue;
+                        }
+
+                        match parse_tag(raw, body) {
+                            Some(Tag::Open(block)) => {
+                                stack.push((block, std::mem::take(&mut segments)));
+                            }
+                            Some(Tag::Else(raw)) if stack.last().is_some_and(|(b, _)| b.accepts_else()) => {
+                                let (block, _) = stack.last_mut().expect("checked above");
+                                block.start_else(raw, std::mem::take(&mut segments));
+                            }
+                            Some(Tag::Close(name)) if stack.last().is_some_and(|(b, _)| b.closed_by(name)) => {
+                                let (block, parent) = stack.pop().expect("checked above");
+                                let body = std::mem::replace(&mut segments, parent);
+                                segments.push(block.into_segment(body));
+                            }
+                            _ => {
+                                segments.push(Segment::Lit(raw));
+                                continue;
+                            }
+                        }
+                        rest = rest.strip_prefix('\n').unwrap_or(rest);
                     }
                 }
             }
         }
     }
 
+    // Unclosed blocks: keep the opening tag (and any `else`) as literal text.
+    while let Some((block, parent)) = stack.pop() {
+        let body = std::mem::replace(&mut segments, parent);
+        segments.push(Segment::Lit(block.raw()));
+        if let Block::If { then: Some((else_raw, then)), .. } = block {
+            segments.extend(then);
+            segments.push(Segment::Lit(else_raw));
+        }
+        segments.extend(body);
+    }
+
     segments
 }
 
+/// A block tag waiting for its closing tag.
+enum Block<'a> {
+    For { raw: &'a str, var: &'a str, list: &'a str },
+    /// `then` holds the `{% else %}` tag and the segments before it, once seen.
+    If {
+        raw: &'a str,
+        cond: Cond<'a>,
+        then: Option<(&'a str, Vec<Segment<'a>>)>,
+    },
+    Macro { raw: &'a str, name: &'a str, params: Vec<&'a str> },
+}
+
+impl<'a> Block<'a> {
+    fn raw(&self) -> &'a str {
+        match self {
+            Block::For { raw, .. } | Block::If { raw, .. } | Block::Macro { raw, .. } => raw,
+        }
+    }
+
+    fn closed_by(&self, tag: &str) -> bool {
+        match self {
+            Block::For { .. } => tag == "endfor",
+            Block::If { .. } => tag == "endif",
+            Block::Macro { .. } => tag == "endmacro",
+        }
+    }
+
+    fn accepts_else(&self) -> bool {
+        matches!(self, Block::If { then: None, .. })
+    }
+
+    fn start_else(&mut self, else_raw: &'a str, body: Vec<Segment<'a>>) {
+        if let Block::If { then, .. } = self {
+            *then = Some((else_raw, body));
+        }
+    }
+
+    fn into_segment(self, body: Vec<Segment<'a>>) -> Segment<'a> {
+        match self {
+            Block::For { var, list, .. } => Segment::For { var, list, body },
+            Block::If { cond, then: None, .. } => Segment::If {
+                cond,
+                then: body,
+                otherwise: Vec::new(),
+            },
+            Block::If { cond, then: Some((_, then)), .. } => Segment::If { cond, then, otherwise: body },
+            Block::Macro { name, params, .. } => Segment::Macro { name, params, body },
+        }
+    }
+}
+
+enum Tag<'a> {
+    Open(Block<'a>),
+    Else(&'a str),
+    Close(&'a str),
+}
+
+/// Parse the inside of `{% ... %}`.
+fn parse_tag<'a>(raw: &'a str, body: &'a str) -> Option<Tag<'a>> {
+    if let Some(sig) = body.trim().strip_prefix("macro ") {
+        let Filter { name, args } = parse_filter(sig)?;
+        let params = args
+            .into_iter()
+            .map(|a| match a {
+                Arg::Var(p) if is_ident(p) => Some(p),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        return Some(Tag::Open(Block::Macro { raw, name, params }));
+    }
+    let words: Vec<&str> = body.split_whitespace().collect();
+    match words.as_slice() {
+        ["for", var, "in", list] if is_ident(var) && is_ident(list) => {
+            Some(Tag::Open(Block::For { raw, var, list }))
+        }
+        ["if", cond @ ..] => parse_cond(cond).map(|cond| Tag::Open(Block::If { raw, cond, then: None })),
+        ["else"] => Some(Tag::Else(raw)),
+        [name @ ("endfor" | "endif" | "endmacro")] => Some(Tag::Close(name)),
+        _ => None,
+    }
+}
+
+/// `x`, `not x`, or `x <op> y`; operands are parsed like filter arguments.
+fn parse_cond<'a>(words: &[&'a str]) -> Option<Cond<'a>> {
+    let (negate, words) = match words {
+        ["not", rest @ ..] => (true, rest),
+        _ => (false, words),
+    };
+    match words {
+        [lhs] => Some(Cond {
+            negate,
+            lhs: parse_arg(lhs)?,
+            cmp: None,
+        }),
+        [lhs, op, rhs] => {
+            let cmp = match *op {
+                "==" => Cmp::Eq,
+                "!=" => Cmp::Ne,
+                "<" => Cmp::Lt,
+                "<=" => Cmp::Le,
+                ">" => Cmp::Gt,
+                ">=" => Cmp::Ge,
+                _ => return None,
+            };
+            Some(Cond {
+                negate,
+                lhs: parse_arg(lhs)?,
+                cmp: Some((cmp, parse_arg(rhs)?)),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Parse `key | filter | filter(arg, ...)`. Returns `None` if malformed.
+fn parse_expr<'a>(raw: &'a str, body: &'a str) -> Option<Expr<'a>> {
+    let mut parts = split_top_level(body, '|').into_iter();
+    let key = parts.next()?.trim();
+    if key.is_empty() {
+        return None;
+    }
+    let filters = parts.map(parse_filter).collect::<Option<Vec<_>>>()?;
+    Some(Expr { raw, key, filters })
+}
+
+/// `name(arg, ...)` with no filters: a macro call.
+fn parse_call<'a>(raw: &'a str, body: &'a str) -> Option<Segment<'a>> {
+    if !body.trim_end().ends_with(')') || split_top_level(body, '|').len() > 1 {
+        return None;
+    }
+    let Filter { name, args } = parse_filter(body)?;
+    Some(Segment::Call { raw, name, args })
+}
+
+/// Parse `name` or `name(arg, ...)`. Returns `None` if malformed.
+pub fn parse_filter(src: &str) -> Option<Filter<'_>> {
+    let src = src.trim();
+    let (name, args) = match src.find('(') {
+        None => (src, Vec::new()),
+        Some(open) => {
+            let inner = src[open + 1..].strip_suffix(')')?;
+            let args = if inner.trim().is_empty() {
+                Vec::new()
+            } else {
+                split_top_level(inner, ',')
+                    .into_iter()
+                    .map(parse_arg)
+                    .collect::<Option<Vec<_>>>()?
+            };
+            (src[..open].trim(), args)
+        }
+    };
+    is_ident(name).then_some(Filter { name, args })
+}
+
+fn parse_arg(src: &str) -> Option<Arg<'_>> {
+    let src = src.trim();
+    if src.is_empty() {
+        return None;
+    }
+    for quote in ['"', '\''] {
+        if let Some(inner) = src.strip_prefix(quote) {
+            return inner.strip_suffix(quote).map(Arg::Str);
+        }
+    }
+    if src.starts_with('#') {
+        return Some(Arg::Str(src));
+    }
+    if let Ok(n) = src.parse::<f64>() {
+        return Some(Arg::Num(n));
+    }
+    Some(Arg::Var(src))
+}
+
+/// Split on `sep`, ignoring separators inside quotes or parentheses.
+fn split_top_level(src: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0u32;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in src.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if c == sep && depth == 0 => {
+                parts.push(&src[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&src[start..]);
+    parts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,7 +384,11 @@ mod tests {
         segs.iter()
             .map(|s| match s {
                 Segment::Lit(t) => *t,
-                Segment::Var(k) => map.get(k).copied().unwrap_or("MISSING"),
+                Segment::Var(e) => map.get(e.key).copied().unwrap_or("MISSING"),
+                Segment::For { .. } => "FOR",
+                Segment::If { .. } => "IF",
+                Segment::Macro { .. } => "",
+                Segment::Call { .. } => "CALL",
             })
             .collect()
     }
@@ -97,4 +422,59 @@ mod tests {
         let segs = parse("{{  key  }}");
         assert_eq!(render(&segs, &[("key", "val")]), "val");
     }
+
+    #[test]
+    fn filters_and_args() {
+        let segs = parse("{{ bg | lighten(10) | mix(fg, \"a,b\") | upper }}");
+        let Segment::Var(e) = &segs[0] else { panic!("expected var") };
+        assert_eq!(e.key, "bg");
+        let names: Vec<_> = e.filters.iter().map(|f| f.name).collect();
+        assert_eq!(names, ["lighten", "mix", "upper"]);
+        assert_eq!(e.filters[0].args, [Arg::Num(10.0)]);
+        assert_eq!(e.filters[1].args, [Arg::Var("fg"), Arg::Str("a,b")]);
+    }
+
+    #[test]
+    fn for_block_nests_body() {
+        let segs = parse("a{% for c in pal %}\n[{{ c }}]{% endfor %}\nb");
+        assert_eq!(segs.len(), 3);
+        let Segment::For { var, list, body } = &segs[1] else { panic!("expected for") };
+        assert_eq!((*var, *list, body.len()), ("c", "pal", 3));
+        assert_eq!(render(&segs, &[]), "aFORb");
+    }
+
+    #[test]
+    fn if_else_splits_branches() {
+        let segs = parse("{% if bg_lum < 50 %}\ndark{% else %}\nlight{% endif %}\n");
+        assert_eq!(segs.len(), 1);
+        let Segment::If { cond, then, otherwise } = &segs[0] else { panic!("expected if") };
+        assert_eq!((cond.lhs.clone(), cond.cmp.clone()), (Arg::Var("bg_lum"), Some((Cmp::Lt, Arg::Num(50.0)))));
+        assert_eq!((render(then, &[]), render(otherwise, &[])), ("dark".to_owned(), "light".to_owned()));
+        assert_eq!(render(&parse("{% if a %}x{% else %}y"), &[]), "{% if a %}x{% else %}y");
+        assert_eq!(render(&parse("{% if a ~ b %}"), &[]), "{% if a ~ b %}");
+    }
+
+    #[test]
+    fn macro_definition_and_call() {
+        let segs = parse("{% macro button(color, n) %}\n.b{{ n }} { color: {{ color }}; }\n{% endmacro %}\n{{ button(accent, 1) }}");
+        assert_eq!(segs.len(), 2);
+        let Segment::Macro { name, params, body } = &segs[0] else { panic!("expected macro") };
+        assert_eq!((*name, params.as_slice(), body.len()), ("button", ["color", "n"].as_slice(), 5));
+        let Segment::Call { name, args, .. } = &segs[1] else { panic!("expected call") };
+        assert_eq!((*name, args.as_slice()), ("button", [Arg::Var("accent"), Arg::Num(1.0)].as_slice()));
+        assert_eq!(render(&parse("{% macro m(#x) %}{% endmacro %}"), &[]), "{% macro m(#x) %}{% endmacro %}");
+    }
+
+    #[test]
+    fn unbalanced_tags_are_literal() {
+        assert_eq!(render(&parse("{% endfor %}x"), &[]), "{% endfor %}x");
+        assert_eq!(render(&parse("{% for c in p %}\nx"), &[]), "{% for c in p %}x");
+        assert_eq!(render(&parse("{% nope %}"), &[]), "{% nope %}");
+    }
+
+    #[test]
+    fn malformed_filter_is_literal() {
+        let segs = parse("{{ bg | lighten(10 }}");
+        assert_eq!(render(&segs, &[("bg", "x")]), "{{ bg | lighten(10 }}");
+    }
 }
@@ -0,0 +1,170 @@
+//! User-defined derived variables from `[derive]` in `oxidize.toml`.
+//!
+//! ```toml
+//! [derive]
+//! surface1 = "mix(bg, fg, 0.08)"
+//! border = "lighten(bg, 15)"
+//! ```
+//!
+//! Each rule is a filter call whose first argument is the input. Variables
+//! are looked up like export keys (`bg` finds `palette_bg`), rules may build
+//! on other rules, and a theme that defines the key itself keeps its value.
+//!
+//! Also semantic roles from `[role]` (in `oxidize.toml` and `theme.toml`),
+//! which bind `role_<name>` to one of the theme's own variables.
+
+use super::{
+    engine::{derive_color_keys, resolve_key},
+    filters,
+    parser::{parse_filter, Arg, Filter},
+};
+use crate::log;
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+
+/// Evaluate `rules` against `vars`, inserting each result and its derived
+/// color notations.
+pub fn apply(rules: &BTreeMap<String, String>, vars: &mut HashMap<String, String>) -> Result<()> {
+    let mut pending: BTreeMap<&str, Filter<'_>> = BTreeMap::new();
+    for (key, rule) in rules {
+        if vars.contains_key(key) {
+            continue;
+        }
+        let call = parse_filter(rule)
+            .filter(|f| !f.args.is_empty())
+            .with_context(|| format!("derive.{key}: expected `filter(input, ...)`, got '{rule}'"))?;
+        pending.insert(key, call);
+    }
+
+    while !pending.is_empty() {
+        let ready: Vec<&str> = pending
+            .iter()
+            .filter(|(_, call)| !call.args.iter().any(|a| matches!(a, Arg::Var(k) if pending.contains_key(k))))
+            .map(|(key, _)| *key)
+            .collect();
+        if ready.is_empty() {
+            let keys: Vec<&str> = pending.keys().copied().collect();
+            bail!("derive rules depend on each other in a cycle: {}", keys.join(", "));
+        }
+        for key in ready {
+            let call = pending.remove(key).expect("ready keys are pending");
+            match eval(&call, vars).with_context(|| format!("derive.{key}"))? {
+                Some(value) => {
+                    if value.starts_with('#') {
+                        vars.extend(derive_color_keys(key, &value));
+                    }
+                    vars.insert(key.to_owned(), value);
+                }
+                None => log::warn!("derive.{key}: input is undefined in this theme, skipped"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bind `role_<name>` for every role, to the variable it names (looked up
+/// like export keys) or to a literal `#hex` color. A role whose variable is
+/// undefined is an error if `required`, and skipped otherwise.
+pub fn apply_roles(roles: &BTreeMap<String, String>, vars: &mut HashMap<String, String>, required: bool) -> Result<()> {
+    let mut bound = Vec::new();
+    for (role, source) in roles {
+        let value = if source.starts_with('#') {
+            source.clone()
+        } else {
+            match resolve_key(vars, source) {
+                Some((_, value)) => value.to_owned(),
+                None if required => bail!("role.{role}: no variable '{source}' in this theme"),
+                None => {
+                    log::debug!("role.{role}: no variable '{source}' in this theme, skipped");
+                    continue;
+                }
+            }
+        };
+        bound.push((format!("role_{role}"), value));
+    }
+    for (key, value) in bound {
+        if value.starts_with('#') {
+            vars.extend(derive_color_keys(&key, &value));
+        }
+        vars.insert(key, value);
+    }
+    Ok(())
+}
+
+/// Run one rule. Returns `Ok(None)` when its input is undefined.
+fn eval(call: &Filter<'_>, vars: &HashMap<String, String>) -> Result<Option<String>> {
+    // Bare words that name no variable stay as-is for word arguments
+    // like `alpha(0.5, hex)`.
+    let args: Vec<Arg<'_>> = call
+        .args
+        .iter()
+        .map(|arg| match arg {
+            Arg::Var(k) => resolve_key(vars, k).map_or(arg.clone(), |(_, v)| Arg::Str(v)),
+            other => other.clone(),
+        })
+        .collect();
+    let input = match &args[0] {
+        Arg::Str(s) => s.to_string(),
+        Arg::Num(n) => n.to_string(),
+        Arg::Var(_) => return Ok(None),
+    };
+    let filter = Filter {
+        name: call.name,
+        args: args[1..].to_vec(),
+    };
+    filters::apply(&filter, &input, vars).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_chain_and_themes_win() {
+        let mut vars = HashMap::from([
+            ("palette_bg".to_owned(), "#000000".to_owned()),
+            ("palette_fg".to_owned(), "#ffffff".to_owned()),
+            ("border".to_owned(), "#123456".to_owned()),
+        ]);
+        let rules = BTreeMap::from([
+            ("border".to_owned(), "lighten(bg, 15)".to_owned()),
+            ("surface".to_owned(), "mix(bg, fg, 0.5)".to_owned()),
+            ("overlay".to_owned(), "darken(surface, 10)".to_owned()),
+        ]);
+        apply(&rules, &mut vars).unwrap();
+        assert_eq!(vars["border"], "#123456");
+        assert_eq!(vars["surface"], "#636363");
+        assert_eq!(vars["surface_rgb"], "99,99,99");
+        assert!(vars.contains_key("overlay"));
+
+        let cycle = BTreeMap::from([
+            ("a".to_owned(), "lighten(b, 1)".to_owned()),
+            ("b".to_owned(), "lighten(a, 1)".to_owned()),
+        ]);
+        assert!(apply(&cycle, &mut vars).is_err());
+    }
+
+    #[test]
+    fn roles_bind_theme_variables() {
+        let mut vars = HashMap::from([
+            ("palette_base".to_owned(), "#1e1e2e".to_owned()),
+            ("palette_red".to_owned(), "#f38ba8".to_owned()),
+            ("font".to_owned(), "Iosevka".to_owned()),
+        ]);
+        let roles = BTreeMap::from([
+            ("surface".to_owned(), "base".to_owned()),
+            ("danger".to_owned(), "palette_red".to_owned()),
+            ("mono".to_owned(), "font".to_owned()),
+            ("link".to_owned(), "#89b4fa".to_owned()),
+        ]);
+        apply_roles(&roles, &mut vars, true).unwrap();
+        assert_eq!(vars["role_surface"], "#1e1e2e");
+        assert_eq!(vars["role_danger_rgb"], "243,139,168");
+        assert_eq!((vars["role_mono"].as_str(), vars["role_link"].as_str()), ("Iosevka", "#89b4fa"));
+
+        let missing = BTreeMap::from([("accent".to_owned(), "mauve".to_owned())]);
+        assert!(apply_roles(&missing, &mut vars, true).is_err());
+        apply_roles(&missing, &mut vars, false).unwrap();
+        assert!(!vars.contains_key("role_accent"));
+    }
+}
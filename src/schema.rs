@@ -0,0 +1,127 @@
+//! `schema.toml` — the variables every theme has to define, so a shared
+//! template collection can state what it relies on.
+//!
+//! ```toml
+//! [required]
+//! bg = "color"
+//! font_size = "number"
+//! ```
+
+use crate::{color::Rgb, render::engine::resolve_key};
+use anyhow::{Context, Result, bail};
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+/// What a required value has to look like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Color,
+    Number,
+    Bool,
+    String,
+}
+
+impl Kind {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s {
+            "color" => Self::Color,
+            "number" => Self::Number,
+            "bool" => Self::Bool,
+            "string" => Self::String,
+            _ => bail!("unknown type '{s}' (expected color, number, bool or string)"),
+        })
+    }
+
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Self::Color => value.starts_with('#') && Rgb::parse(value).is_some(),
+            Self::Number => value.parse::<f64>().is_ok(),
+            Self::Bool => matches!(value, "true" | "false"),
+            Self::String => true,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Color => "color",
+            Self::Number => "number",
+            Self::Bool => "bool",
+            Self::String => "string",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub required: Vec<(String, Kind)>,
+}
+
+impl Schema {
+    /// Read `path`; a missing file is an empty schema.
+    pub fn load(path: &Path) -> Result<Self> {
+        let src = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+        };
+        let root: toml::Table = src.parse().with_context(|| format!("parse {}", path.display()))?;
+        let mut schema = Self::default();
+        for (section, body) in root {
+            if section != "required" {
+                bail!("{}: unknown section [{section}]", path.display());
+            }
+            let Some(body) = body.as_table() else {
+                bail!("{}: [required] must be a table", path.display());
+            };
+            for (key, kind) in body {
+                let kind = kind
+                    .as_str()
+                    .context("type must be a string")
+                    .and_then(Kind::parse)
+                    .with_context(|| format!("{}: required.{key}", path.display()))?;
+                schema.required.push((key.clone(), kind));
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Describe every required key `vars` lacks or has with the wrong type.
+    /// Keys resolve like export keys (`bg` is satisfied by `palette_bg`).
+    pub fn check(&self, vars: &HashMap<String, String>) -> Vec<String> {
+        self.required
+            .iter()
+            .filter_map(|(key, kind)| match resolve_key(vars, key) {
+                None => Some(format!("missing required {kind} '{key}'")),
+                Some((name, value)) if !kind.accepts(value) => Some(format!("{name} = '{value}' is not a {kind}")),
+                Some(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_and_mistyped_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schema.toml");
+        fs::write(&path, "[required]\nbg = \"color\"\nfg = \"color\"\nfont_size = \"number\"\n").unwrap();
+        let schema = Schema::load(&path).unwrap();
+
+        let vars = HashMap::from([
+            ("palette_bg".to_owned(), "#1e1e2e".to_owned()),
+            ("font_size".to_owned(), "large".to_owned()),
+        ]);
+        assert_eq!(
+            schema.check(&vars),
+            ["missing required color 'fg'", "font_size = 'large' is not a number"]
+        );
+
+        fs::write(&path, "[required]\nbg = \"colour\"\n").unwrap();
+        assert!(Schema::load(&path).is_err());
+        assert!(Schema::load(&dir.path().join("none.toml")).unwrap().required.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! `oxidize generate` — a Material-style light/dark theme from one seed color.
+//!
+//! The seed's hue drives five tonal palettes (primary, secondary, tertiary,
+//! neutral, neutral variant), each a fixed chroma swept across lightness.
+//! Roles then pick a tone from a palette, as in Material 3's "tonal spot"
+//! scheme. Tones are CIE L* values, so tone 40 on any palette has the same
+//! luminance.
+
+use crate::{color::Rgb, theme::Variant};
+use anyhow::{Context, Result, bail};
+use std::{fmt::Write as _, fs, path::Path};
+
+/// libadwaita's accent colors, so `--seed` can follow GNOME's setting.
+const GNOME_ACCENTS: [(&str, &str); 9] = [
+    ("blue", "#3584e4"),
+    ("teal", "#2190a4"),
+    ("green", "#3a944a"),
+    ("yellow", "#c88800"),
+    ("orange", "#ed5b00"),
+    ("red", "#e62d42"),
+    ("pink", "#d56199"),
+    ("purple", "#9141ac"),
+    ("slate", "#6f8396"),
+];
+
+/// A hue at fixed chroma, sampled by tone.
+#[derive(Clone, Copy)]
+struct Palette {
+    hue: f64,
+    chroma: f64,
+}
+
+impl Palette {
+    fn tone(self, tone: f64) -> Rgb {
+        Rgb::from_oklch(oklch_lightness(tone), self.chroma, self.hue)
+    }
+}
+
+struct Palettes {
+    primary: Palette,
+    secondary: Palette,
+    tertiary: Palette,
+    neutral: Palette,
+    neutral_variant: Palette,
+    error: Palette,
+}
+
+impl Palettes {
+    fn from_seed(seed: Rgb) -> Self {
+        let (_, chroma, hue) = seed.to_oklch();
+        let at = |hue: f64, chroma: f64| Palette { hue, chroma };
+        Self {
+            primary: at(hue, chroma.max(0.1)),
+            secondary: at(hue, 0.04),
+            tertiary: at((hue + 60.0).rem_euclid(360.0), 0.07),
+            neutral: at(hue, 0.01),
+            neutral_variant: at(hue, 0.02),
+            error: at(25.0, 0.19),
+        }
+    }
+}
+
+/// OKLCH lightness (percent) of a gray with CIE L* `tone`.
+fn oklch_lightness(tone: f64) -> f64 {
+    let fy = (tone.clamp(0.0, 100.0) + 16.0) / 116.0;
+    let y = if fy > 6.0 / 29.0 { fy.powi(3) } else { 3.0 * (6.0f64 / 29.0).powi(2) * (fy - 4.0 / 29.0) };
+    y.cbrt() * 100.0
+}
+
+/// Resolve `--seed`: a hex color or a GNOME accent name.
+pub fn parse_seed(seed: &str) -> Result<Rgb> {
+    let hex = GNOME_ACCENTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(seed))
+        .map_or(seed, |(_, hex)| hex);
+    Rgb::parse(hex).with_context(|| {
+        let names: Vec<&str> = GNOME_ACCENTS.iter().map(|(n, _)| *n).collect();
+        format!("seed '{seed}' is neither a hex color nor one of {}", names.join(", "))
+    })
+}
+
+/// The role → color table for one variant, in output order.
+fn roles(p: &Palettes, variant: Variant) -> Vec<(&'static str, Rgb)> {
+    // (role, palette, light tone, dark tone)
+    let table: [(&str, Palette, f64, f64); 29] = [
+        ("primary", p.primary, 40.0, 80.0),
+        ("on_primary", p.primary, 100.0, 20.0),
+        ("primary_container", p.primary, 90.0, 30.0),
+        ("on_primary_container", p.primary, 10.0, 90.0),
+        ("secondary", p.secondary, 40.0, 80.0),
+        ("on_secondary", p.secondary, 100.0, 20.0),
+        ("secondary_container", p.secondary, 90.0, 30.0),
+        ("on_secondary_container", p.secondary, 10.0, 90.0),
+        ("tertiary", p.tertiary, 40.0, 80.0),
+        ("on_tertiary", p.tertiary, 100.0, 20.0),
+        ("tertiary_container", p.tertiary, 90.0, 30.0),
+        ("on_tertiary_container", p.tertiary, 10.0, 90.0),
+        ("error", p.error, 40.0, 80.0),
+        ("on_error", p.error, 100.0, 20.0),
+        ("error_container", p.error, 90.0, 30.0),
+        ("on_error_container", p.error, 10.0, 90.0),
+        ("surface", p.neutral, 98.0, 6.0),
+        ("surface_dim", p.neutral, 87.0, 6.0),
+        ("surface_bright", p.neutral, 98.0, 24.0),
+        ("surface_container_lowest", p.neutral, 100.0, 4.0),
+        ("surface_container_low", p.neutral, 96.0, 10.0),
+        ("surface_container", p.neutral, 94.0, 12.0),
+        ("surface_container_high", p.neutral, 92.0, 17.0),
+        ("surface_container_highest", p.neutral, 90.0, 22.0),
+        ("on_surface", p.neutral, 10.0, 90.0),
+        ("on_surface_variant", p.neutral_variant, 30.0, 80.0),
+        ("outline", p.neutral_variant, 50.0, 60.0),
+        ("outline_variant", p.neutral_variant, 80.0, 30.0),
+        ("inverse_primary", p.primary, 80.0, 40.0),
+    ];
+    table
+        .into_iter()
+        .map(|(role, palette, light, dark)| {
+            let tone = if variant == Variant::Light { light } else { dark };
+            (role, palette.tone(tone))
+        })
+        .collect()
+}
+
+/// The `colors-<variant>.toml` for `seed`.
+pub fn render(seed: Rgb, variant: Variant) -> String {
+    let roles = roles(&Palettes::from_seed(seed), variant);
+    let get = |name: &str| roles.iter().find(|(r, _)| *r == name).map(|(_, c)| c.to_hex()).unwrap_or_default();
+
+    let mut out = format!("# Generated by `oxidize generate --seed {}`.\n[palette]\n", seed.to_hex());
+    for (key, role) in [("bg", "surface"), ("fg", "on_surface"), ("accent", "primary")] {
+        let _ = writeln!(out, "{key} = \"{}\"", get(role));
+    }
+    out.push('\n');
+    for (role, color) in &roles {
+        let _ = writeln!(out, "{role} = \"{}\"", color.to_hex());
+    }
+    out
+}
+
+/// Write a new theme `name` with both variants into `data_dir`.
+pub fn write(data_dir: &Path, name: &str, seed: Rgb, force: bool) -> Result<()> {
+    let root = data_dir.join(name);
+    if root.exists() && !force {
+        bail!("theme '{name}' already exists (pass --force to overwrite its palettes)");
+    }
+    fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    for variant in [Variant::Light, Variant::Dark] {
+        let path = root.join(format!("colors-{}.toml", variant.as_str()));
+        fs::write(&path, render(seed, variant)).with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn on_colors_contrast_with_their_roles() {
+        for seed in ["#3584e4", "#e62d42", "#c88800", "#808080"] {
+            let seed = parse_seed(seed).unwrap();
+            for variant in [Variant::Light, Variant::Dark] {
+                let roles = roles(&Palettes::from_seed(seed), variant);
+                let get = |name: &str| roles.iter().find(|(r, _)| *r == name).unwrap().1;
+                for (fg, bg) in [("on_primary", "primary"), ("on_surface", "surface"), ("on_error", "error")] {
+                    let ratio = get(fg).contrast(get(bg));
+                    assert!(ratio >= 4.5, "{} {variant:?}: {fg}/{bg} {ratio:.2}", seed.to_hex());
+                }
+            }
+        }
+        assert_eq!(parse_seed("Teal").unwrap().to_hex(), "#2190a4");
+        assert!(parse_seed("mauve").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Scoping for users running several Wayland sessions at once.
+//!
+//! A session is identified by its `$WAYLAND_DISPLAY` socket name, which is
+//! unique per user runtime dir. Signals only go to processes whose
+//! environment names the same display, so a switch in one session doesn't
+//! reload apps in another.
+
+use std::fs;
+
+/// The current session's display name, if running under Wayland.
+pub fn current() -> Option<String> {
+    std::env::var("WAYLAND_DISPLAY")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .map(|d| d.replace('/', "_"))
+}
+
+/// PIDs of processes named exactly `name`, in any session.
+///
+/// Processes we can't inspect (other users, races with exit) are skipped.
+pub fn named(name: &str) -> Vec<u32> {
+    let Ok(procs) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    procs
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| fs::read_to_string(format!("/proc/{pid}/comm")).is_ok_and(|c| c.trim() == name))
+        .collect()
+}
+
+/// PIDs of processes named exactly `name` that belong to `display`.
+pub fn pids(name: &str, display: &str) -> Vec<u32> {
+    let want = format!("WAYLAND_DISPLAY={display}");
+
+    named(name)
+        .into_iter()
+        .filter(|pid| {
+            fs::read(format!("/proc/{pid}/environ"))
+                .is_ok_and(|env| env.split(|&b| b == 0).any(|var| var == want.as_bytes()))
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Terminal color policy shared by every command.
+//!
+//! `--color=auto` (the default) enables ANSI colors only when the stream is a
+//! terminal, `NO_COLOR` is unset or empty, and `TERM` is not `dumb`.
+
+use crate::color::Rgb;
+use clap::ValueEnum;
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+static STDOUT: AtomicBool = AtomicBool::new(false);
+static STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Resolve the policy once at startup.
+pub fn init(choice: ColorChoice) {
+    let (out, err) = match choice {
+        ColorChoice::Always => (true, true),
+        ColorChoice::Never => (false, false),
+        ColorChoice::Auto => {
+            let env_ok = std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                && std::env::var_os("TERM").is_none_or(|t| t != "dumb");
+            (
+                env_ok && std::io::stdout().is_terminal(),
+                env_ok && std::io::stderr().is_terminal(),
+            )
+        }
+    };
+    STDOUT.store(out, Ordering::Relaxed);
+    STDERR.store(err, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn stdout_color() -> bool {
+    STDOUT.load(Ordering::Relaxed)
+}
+
+#[inline]
+pub fn stderr_color() -> bool {
+    STDERR.load(Ordering::Relaxed)
+}
+
+/// A three-cell truecolor swatch, or the hex code when colors are off.
+pub fn swatch(c: Rgb) -> String {
+    if stdout_color() {
+        format!("\x1b[48;2;{};{};{}m   \x1b[0m", c.r, c.g, c.b)
+    } else {
+        c.to_hex()
+    }
+}
+
+/// Ask `question` on stderr and read a yes/no answer from stdin. Anything
+/// but `y`/`yes` (including end of input) is a no.
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    use std::io::Write as _;
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Wrap `text` in an SGR sequence (e.g. `"33"` for yellow) if `enabled`.
+pub fn paint(enabled: bool, sgr: &str, text: &str) -> String {
+    if enabled {
+        format!("\x1b[{sgr}m{text}\x1b[0m")
+    } else {
+        text.to_owned()
+    }
+}
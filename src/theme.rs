@@ -1,13 +1,41 @@
 //! Theme descriptor — everything we know about a named theme before rendering.
 
-use crate::render::engine::build_vars_from_colors;
+use crate::render::engine::{build_vars, merge_tables, palette_dropins, read_layers};
 use anyhow::{Context, Result, bail};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
 };
 
+/// One half of a theme that ships `colors-light.toml` / `colors-dark.toml`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Variant {
+    Light,
+    Dark,
+}
+
+impl Variant {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Variant::Light => "light",
+            Variant::Dark => "dark",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "light" => Some(Variant::Light),
+            "dark" => Some(Variant::Dark),
+            _ => None,
+        }
+    }
+
+    fn colors_file(self) -> String {
+        format!("colors-{}.toml", self.as_str())
+    }
+}
+
 /// A fully-loaded theme ready for rendering and applying.
 #[derive(Clone, Debug)]
 pub struct Theme {
@@ -17,36 +45,227 @@ pub struct Theme {
     pub is_light: bool,
     pub icon_theme: Option<String>,
     pub backgrounds_dir: Option<PathBuf>,
+    /// The theme this one `extends`, if any.
+    pub parent: Option<String>,
+    /// Descriptive fields from `theme.toml`.
+    pub display_name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// The variant that was loaded, for themes that have variants.
+    pub variant: Option<Variant>,
+    /// `[role]` from theme.toml, merged over the parent's.
+    pub roles: BTreeMap<String, String>,
+    /// Seasonal overlays merged into `vars` (see [`crate::overlay`]).
+    pub overlays: Vec<String>,
 }
 
+#[cfg(test)]
 impl Theme {
-    pub fn load(data_dir: &Path, name: &str) -> Result<Self> {
-        let root = data_dir.join(name);
-        if !root.is_dir() {
-            bail!("theme not found: {}", root.display());
+    /// A dark theme named "test" with just `vars`, for unit tests.
+    pub fn for_test(vars: &[(&str, &str)]) -> Self {
+        Self {
+            name: "test".into(),
+            root: PathBuf::new(),
+            vars: vars.iter().map(|&(k, v)| (k.to_owned(), v.to_owned())).collect(),
+            is_light: false,
+            icon_theme: None,
+            backgrounds_dir: None,
+            parent: None,
+            display_name: None,
+            author: None,
+            description: None,
+            tags: Vec::new(),
+            variant: None,
+            roles: BTreeMap::new(),
+            overlays: Vec::new(),
+        }
+    }
+}
+
+/// Optional `theme.toml` next to `colors.toml`.
+#[derive(Clone, Debug, Default)]
+struct Meta {
+    /// Theme whose palette, backgrounds and icon theme this one builds on.
+    extends: Option<String>,
+    display_name: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+    /// `variant = "light" | "dark"`; takes precedence over `light.mode`.
+    light: Option<bool>,
+    /// Takes precedence over `icons.theme`.
+    icon_theme: Option<String>,
+    tags: Vec<String>,
+    /// Wallpaper directory, relative to the theme dir unless absolute.
+    wallpaper_dir: Option<PathBuf>,
+    /// Semantic role → variable name.
+    roles: BTreeMap<String, String>,
+}
+
+impl Meta {
+    fn read(root: &Path) -> Result<Self> {
+        let path = root.join("theme.toml");
+        let src = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+        };
+        let table: toml::Table = src.parse().with_context(|| format!("parse {}", path.display()))?;
+        let mut meta = Self::default();
+        for (key, value) in table {
+            let string = || {
+                value
+                    .as_str()
+                    .map(str::to_owned)
+                    .with_context(|| format!("{}: {key} must be a string", path.display()))
+            };
+            match key.as_str() {
+                "extends" => meta.extends = Some(string()?),
+                "display_name" => meta.display_name = Some(string()?),
+                "author" => meta.author = Some(string()?),
+                "description" => meta.description = Some(string()?),
+                "icon_theme" => meta.icon_theme = Some(string()?),
+                "wallpaper_dir" => meta.wallpaper_dir = Some(root.join(crate::util::expand_home(&string()?))),
+                "variant" => {
+                    meta.light = match string()?.as_str() {
+                        "light" => Some(true),
+                        "dark" => Some(false),
+                        other => bail!("{}: variant must be \"light\" or \"dark\", not '{other}'", path.display()),
+                    }
+                }
+                "role" => {
+                    let table = value.as_table().with_context(|| format!("{}: [role] must be a table", path.display()))?;
+                    for (role, source) in table {
+                        let source = source
+                            .as_str()
+                            .with_context(|| format!("{}: role.{role} must be a variable name", path.display()))?;
+                        meta.roles.insert(role.clone(), source.to_owned());
+                    }
+                }
+                "tags" => {
+                    meta.tags = value
+                        .as_array()
+                        .and_then(|tags| tags.iter().map(|t| t.as_str().map(str::to_owned)).collect())
+                        .with_context(|| format!("{}: tags must be an array of strings", path.display()))?;
+                }
+                other => bail!("{}: unknown key '{other}'", path.display()),
+            }
         }
+        Ok(meta)
+    }
+}
+
+impl Theme {
+    /// Load `name` from the first of `dirs` that has it. For a theme with
+    /// variants, `variant` picks one; without it, theme.toml's `variant` or
+    /// else dark is used. Themes without variants ignore it.
+    pub fn load(dirs: &[PathBuf], name: &str, variant: Option<Variant>) -> Result<Self> {
+        Self::load_chain(dirs, name, variant, &mut Vec::new()).map(|(theme, _)| theme)
+    }
+
+    /// The variants a theme directory provides.
+    pub fn variants(root: &Path) -> Vec<Variant> {
+        [Variant::Light, Variant::Dark]
+            .into_iter()
+            .filter(|v| root.join(v.colors_file()).is_file())
+            .collect()
+    }
 
+    /// Load `name` and, through `extends`, its ancestors. Returns the merged
+    /// palette table as well so a child can layer its own on top.
+    fn load_chain(
+        dirs: &[PathBuf],
+        name: &str,
+        variant: Option<Variant>,
+        seen: &mut Vec<String>,
+    ) -> Result<(Self, toml::Value)> {
+        if seen.iter().any(|s| s == name) {
+            bail!("theme inheritance cycle: {} -> {name}", seen.join(" -> "));
+        }
+        seen.push(name.to_owned());
+
+        let Some(root) = dirs.iter().map(|d| d.join(name)).find(|r| r.is_dir()) else {
+            let searched: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+            bail!("theme not found: {name} (searched {})", searched.join(", "));
+        };
+
+        let variants = Self::variants(&root);
         let colors_file = root.join("colors.toml");
-        if !colors_file.is_file() {
+        if !colors_file.is_file() && variants.is_empty() {
             bail!(
                 "missing colors.toml in theme '{name}': {}",
                 colors_file.display()
             );
         }
 
-        let vars = build_vars_from_colors(&colors_file)
-            .with_context(|| format!("build vars for theme '{name}'"))?;
+        let meta = Meta::read(&root).with_context(|| format!("theme '{name}'"))?;
+        let chosen = (!variants.is_empty()).then(|| {
+            variant
+                .or(meta.light.map(|light| if light { Variant::Light } else { Variant::Dark }))
+                .unwrap_or(Variant::Dark)
+        });
+
+        // Shared colors.toml first, then the variant's file, then drop-ins.
+        let mut layers = Vec::new();
+        if colors_file.is_file() {
+            layers.push(colors_file);
+        }
+        if let Some(v) = chosen {
+            let file = root.join(v.colors_file());
+            if !file.is_file() {
+                bail!("theme '{name}' has no {} variant", v.as_str());
+            }
+            layers.push(file);
+        }
+        layers.extend(palette_dropins(&root)?);
+        let own = read_layers(&layers).with_context(|| format!("build vars for theme '{name}'"))?;
 
-        let bg_dir = root.join("backgrounds");
+        let (parent, table) = match &meta.extends {
+            Some(parent) => {
+                let (parent, mut table) = Self::load_chain(dirs, parent, chosen.or(variant), seen)
+                    .with_context(|| format!("load '{parent}', extended by '{name}'"))?;
+                merge_tables(&mut table, own);
+                (Some(parent), table)
+            }
+            None => (None, own),
+        };
 
-        Ok(Self {
+        let bg_dir = meta.wallpaper_dir.unwrap_or_else(|| root.join("backgrounds"));
+        let icon_theme = match meta.icon_theme {
+            Some(icons) => Some(icons),
+            None => read_trimmed(&root.join("icons.theme"))?,
+        };
+        let theme = Self {
             name: name.to_owned(),
-            is_light: root.join("light.mode").is_file(),
-            icon_theme: read_trimmed(&root.join("icons.theme"))?,
-            backgrounds_dir: bg_dir.is_dir().then_some(bg_dir),
+            is_light: match chosen {
+                Some(v) => v == Variant::Light,
+                None => meta
+                    .light
+                    .or_else(|| root.join("light.mode").is_file().then_some(true))
+                    .or_else(|| parent.as_ref().map(|p| p.is_light))
+                    .unwrap_or(false),
+            },
+            icon_theme: icon_theme.or_else(|| parent.as_ref().and_then(|p| p.icon_theme.clone())),
+            backgrounds_dir: bg_dir
+                .is_dir()
+                .then_some(bg_dir)
+                .or_else(|| parent.as_ref().and_then(|p| p.backgrounds_dir.clone())),
+            vars: build_vars(&table),
+            parent: meta.extends,
+            display_name: meta.display_name,
+            author: meta.author,
+            description: meta.description,
+            tags: meta.tags,
+            variant: chosen,
+            roles: {
+                let mut roles = parent.as_ref().map(|p| p.roles.clone()).unwrap_or_default();
+                roles.extend(meta.roles);
+                roles
+            },
             root,
-            vars,
-        })
+            overlays: Vec::new(),
+        };
+        Ok((theme, table))
     }
 }
 
@@ -61,3 +280,100 @@ fn read_trimmed(path: &Path) -> Result<Option<String>> {
         Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extends_merges_palettes_recursively() {
+        let data = tempfile::tempdir().unwrap();
+        let dirs = [data.path().to_path_buf()];
+        let write = |theme: &str, file: &str, src: &str| {
+            fs::create_dir_all(data.path().join(theme)).unwrap();
+            fs::write(data.path().join(theme).join(file), src).unwrap();
+        };
+        write("base", "colors.toml", "[palette]\nbg = \"#000000\"\nfg = \"#ffffff\"\naccent = \"#ff0000\"\n");
+        write("mid", "colors.toml", "[palette]\nfg = \"#eeeeee\"\n");
+        write("mid", "theme.toml", "extends = \"base\"\n");
+        write("leaf", "colors.toml", "[palette]\naccent = \"#00ff00\"\n");
+        write("leaf", "theme.toml", "extends = \"mid\"\n");
+
+        let leaf = Theme::load(&dirs, "leaf", None).unwrap();
+        assert_eq!(leaf.vars["palette_bg"], "#000000");
+        assert_eq!(leaf.vars["palette_fg"], "#eeeeee");
+        assert_eq!(leaf.vars["palette_accent_rgb"], "0,255,0");
+        assert_eq!(leaf.parent.as_deref(), Some("mid"));
+        assert!(!leaf.is_light);
+
+        fs::write(data.path().join("base/light.mode"), "").unwrap();
+        let leaf = Theme::load(&dirs, "leaf", None).unwrap();
+        assert!(leaf.is_light);
+        write("mid", "theme.toml", "extends = \"base\"\nvariant = \"dark\"\n");
+        assert!(!Theme::load(&dirs, "leaf", None).unwrap().is_light);
+        write("mid", "theme.toml", "extends = \"base\"\n");
+
+        write("leaf", "theme.toml", "extends = \"mid\"\nvariant = \"light\"\ndisplay_name = \"Leaf\"\ntags = [\"green\"]\n");
+        let leaf = Theme::load(&dirs, "leaf", None).unwrap();
+        assert!(leaf.is_light);
+        assert_eq!((leaf.display_name.as_deref(), leaf.tags.as_slice()), (Some("Leaf"), ["green".to_owned()].as_slice()));
+
+        write("base", "theme.toml", "extends = \"leaf\"\n");
+        let err = Theme::load(&dirs, "leaf", None).unwrap_err();
+        assert!(format!("{err:#}").contains("cycle"), "{err:#}");
+    }
+
+    #[test]
+    fn variants_layer_over_shared_colors() {
+        let data = tempfile::tempdir().unwrap();
+        let dirs = [data.path().to_path_buf()];
+        let root = data.path().join("pair");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("colors.toml"), "[palette]\naccent = \"#ff0000\"\n").unwrap();
+        fs::write(root.join("colors-light.toml"), "[palette]\nbg = \"#ffffff\"\n").unwrap();
+        fs::write(root.join("colors-dark.toml"), "[palette]\nbg = \"#000000\"\n").unwrap();
+
+        let dark = Theme::load(&dirs, "pair", None).unwrap();
+        assert_eq!((dark.variant, dark.is_light), (Some(Variant::Dark), false));
+        assert_eq!(dark.vars["palette_bg"], "#000000");
+
+        let light = Theme::load(&dirs, "pair", Some(Variant::Light)).unwrap();
+        assert_eq!((light.vars["palette_bg"].as_str(), light.vars["palette_accent"].as_str()), ("#ffffff", "#ff0000"));
+        assert!(light.is_light);
+
+        fs::create_dir_all(root.join("colors.d")).unwrap();
+        fs::write(root.join("colors.d/20-late.toml"), "[palette]\naccent = \"#0000ff\"\n").unwrap();
+        fs::write(root.join("colors.d/10-early.toml"), "[palette]\naccent = \"#00ff00\"\nbg = \"#111111\"\n").unwrap();
+        let layered = Theme::load(&dirs, "pair", Some(Variant::Light)).unwrap();
+        assert_eq!((layered.vars["palette_bg"].as_str(), layered.vars["palette_accent"].as_str()), ("#111111", "#0000ff"));
+
+        assert_eq!(dark.backgrounds_dir, None);
+        fs::create_dir_all(root.join("walls")).unwrap();
+        fs::write(root.join("theme.toml"), "wallpaper_dir = \"walls\"\n").unwrap();
+        assert_eq!(Theme::load(&dirs, "pair", None).unwrap().backgrounds_dir, Some(root.join("walls")));
+        let home = crate::util::expand_home("~/Pictures/walls");
+        fs::write(root.join("theme.toml"), "wallpaper_dir = \"~/Pictures/walls\"\n").unwrap();
+        assert_eq!(Meta::read(&root).unwrap().wallpaper_dir, Some(home));
+    }
+
+    #[test]
+    fn earlier_dirs_shadow_later_ones() {
+        let user = tempfile::tempdir().unwrap();
+        let system = tempfile::tempdir().unwrap();
+        let dirs = [user.path().to_path_buf(), system.path().join("missing"), system.path().to_path_buf()];
+        let write = |dir: &Path, theme: &str, file: &str, src: &str| {
+            fs::create_dir_all(dir.join(theme)).unwrap();
+            fs::write(dir.join(theme).join(file), src).unwrap();
+        };
+        write(system.path(), "base", "colors.toml", "[palette]\nbg = \"#000000\"\nfg = \"#ffffff\"\n");
+        write(system.path(), "shared", "colors.toml", "[palette]\nbg = \"#222222\"\n");
+        write(user.path(), "shared", "colors.toml", "[palette]\nbg = \"#333333\"\n");
+        write(user.path(), "mine", "colors.toml", "[palette]\nfg = \"#eeeeee\"\n");
+        write(user.path(), "mine", "theme.toml", "extends = \"base\"\n");
+
+        assert_eq!(Theme::load(&dirs, "shared", None).unwrap().vars["palette_bg"], "#333333");
+        let mine = Theme::load(&dirs, "mine", None).unwrap();
+        assert_eq!((mine.vars["palette_bg"].as_str(), mine.vars["palette_fg"].as_str()), ("#000000", "#eeeeee"));
+        assert!(Theme::load(&dirs, "nope", None).is_err());
+    }
+}
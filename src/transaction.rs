@@ -1,34 +1,59 @@
 //! Atomic publish via a temp-dir → rename protocol.
-use crate::{ctx::Ctx, util};
+use crate::{ctx::Ctx, generation, util};
 use anyhow::{Context, Result};
-use rustix::fs::{renameat_with, CWD, RenameFlags};
 use std::{
-    fs,
+    collections::BTreeMap,
+    fmt, fs,
+    os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
 };
 use tempfile::{Builder, TempDir};
+use walkdir::WalkDir;
+
+/// How one output path differs between the live tree and the stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+impl Change {
+    /// The path, relative to the live tree.
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::Added(p) | Change::Modified(p) | Change::Removed(p) => p,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(p) => write!(f, "+ {}", p.display()),
+            Change::Modified(p) => write!(f, "~ {}", p.display()),
+            Change::Removed(p) => write!(f, "- {}", p.display()),
+        }
+    }
+}
 
 pub struct Transaction {
     stage: TempDir,
-    live: PathBuf,
-    link: PathBuf,
+    ctx: Ctx,
 }
 
 impl Transaction {
     /// Create a fresh staging directory inside `generated/`.
     pub fn begin(ctx: &Ctx) -> Result<Self> {
         fs::create_dir_all(&ctx.generated_dir).context("create generated dir")?;
+        generation::adopt(ctx)?;
 
         let stage = Builder::new()
             .prefix(".stage.")
             .tempdir_in(&ctx.generated_dir)
             .context("create staging dir")?;
 
-        Ok(Self {
-            stage,
-            live: ctx.live_dir.clone(),
-            link: ctx.current_link.clone(),
-        })
+        Ok(Self { stage, ctx: ctx.clone() })
     }
 
     /// Path callers write rendered files into.
@@ -37,25 +62,78 @@ impl Transaction {
         self.stage.path()
     }
 
-    /// Atomically replace `live/` with the staged tree, then update the
-    /// `current` symlink.
-    pub fn commit(self) -> Result<()> {
+    /// Compare the staged tree against the current `live/` tree.
+    ///
+    /// Files the render hard-linked from `live/` are recognized by inode and
+    /// never read.
+    pub fn changes(&self) -> Vec<Change> {
+        diff(&self.ctx.live_dir, self.stage())
+    }
+
+    /// Publish the staged tree as a new generation (see [`generation`]),
+    /// atomically repoint `live` at it, then update the `current` symlink.
+    /// Only the newest `keep` generations are kept.
+    ///
+    /// With `fsync`, every staged file and directory is flushed before the
+    /// rename and the parent directories after it, so a power loss leaves
+    /// either the old tree or the complete new one.
+    pub fn commit(self, record: &str, keep: usize, fsync: bool) -> Result<u32> {
         // Surrender TempDir ownership before any fallible operations.
         // This ensures the staging dir is never deleted by Drop on any
         // error path that follows.
         let stage_path = self.stage.keep();
+        let number = generation::commit(&self.ctx, &stage_path, record, fsync)?;
+        generation::prune(&self.ctx, keep);
 
-        if fs::symlink_metadata(&self.live).is_ok() {
-            // live/ exists - exchange it with the staged tree atomically.
-            renameat_with(CWD, &stage_path, CWD, &self.live, RenameFlags::EXCHANGE).context("atomic exchange stage <-> live")?;
-            // remove the displaced old tree
-            fs::remove_dir_all(&stage_path).context("remove old live dir")?;
-        } else {
-            // live/ does not exist yet, plain rename is sufficient.
-            fs::rename(&stage_path, &self.live).context("rename stage -> live")?;
-        }
-        
         // Point the `current` convenience symlink at the new live tree.
-        util::symlink_force(&self.live, &self.link).context("update current symlink")
+        util::symlink_force(&self.ctx.live_dir, &self.ctx.current_link).context("update current symlink")?;
+        if fsync && let Some(parent) = self.ctx.current_link.parent() {
+            util::fsync_dir(parent)?;
+        }
+        Ok(number)
+    }
+}
+
+/// How the tree at `new` differs from the one at `old`.
+pub fn diff(old: &Path, new: &Path) -> Vec<Change> {
+    let old = entries(old);
+    let new = entries(new);
+    let mut changes = Vec::new();
+
+    for (rel, path) in &new {
+        match old.get(rel) {
+            None => changes.push(Change::Added(rel.clone())),
+            Some(prev) if differs(prev, path) => changes.push(Change::Modified(rel.clone())),
+            Some(_) => {}
+        }
+    }
+    changes.extend(old.keys().filter(|rel| !new.contains_key(*rel)).cloned().map(Change::Removed));
+    changes
+}
+
+/// Files and symlinks under `root`, keyed by relative path.
+fn entries(root: &Path) -> BTreeMap<PathBuf, PathBuf> {
+    // min_depth: `live` itself is a symlink, not an entry of the tree.
+    WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_type().is_dir())
+        .filter_map(|e| Some((e.path().strip_prefix(root).ok()?.to_owned(), e.into_path())))
+        .collect()
+}
+
+fn differs(a: &Path, b: &Path) -> bool {
+    let (Ok(ma), Ok(mb)) = (fs::symlink_metadata(a), fs::symlink_metadata(b)) else {
+        return true;
+    };
+    if (ma.dev(), ma.ino()) == (mb.dev(), mb.ino()) {
+        return false;
+    }
+    if ma.file_type().is_symlink() || mb.file_type().is_symlink() {
+        return fs::read_link(a).ok() != fs::read_link(b).ok();
     }
+    ma.len() != mb.len()
+        || ma.mode() != mb.mode()
+        || fs::read(a).ok() != fs::read(b).ok()
 }
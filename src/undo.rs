@@ -0,0 +1,174 @@
+//! Desktop state captured before each switch, for `oxidize undo`.
+//!
+//! The live tree itself is kept by [`crate::generation`]; this covers what a
+//! switch changes outside it: `current.theme`, the background link, and the
+//! GNOME settings. The snapshot taken before the switch to generation `n`
+//! is stored as `gen-<n>.undo`.
+//!
+//! The GNOME settings from before oxidize first changed them are also kept
+//! once, in `themes/gsettings.orig`, for `oxidize gnome --restore`.
+
+use crate::{apply::gnome, ctx::Ctx, generation, util};
+use anyhow::{Context, Result};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// The generation `live` pointed at.
+    pub generation: Option<u32>,
+    /// Contents of `current.theme`.
+    pub current_theme: Option<String>,
+    /// Target of the background link.
+    pub background: Option<PathBuf>,
+    /// `(key, value)` under [`gnome::SCHEMA`]; empty if not captured.
+    pub gsettings: Vec<(String, String)>,
+}
+
+impl Snapshot {
+    /// Record the current state. `gsettings` reads the GNOME keys too (which
+    /// spawns `gsettings`).
+    pub fn capture(ctx: &Ctx, gsettings: bool) -> Self {
+        Self {
+            generation: generation::current(ctx),
+            current_theme: fs::read_to_string(&ctx.current_theme_file).ok(),
+            background: fs::read_link(&ctx.background_link).ok(),
+            gsettings: if gsettings {
+                gnome::KEYS
+                    .iter()
+                    .filter_map(|key| Some((key.to_string(), gnome::gsettings_get(gnome::SCHEMA, key)?)))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        let mut out = String::new();
+        if let Some(g) = self.generation {
+            let _ = writeln!(out, "generation = {g}");
+        }
+        if let Some(t) = &self.current_theme {
+            let _ = writeln!(out, "current_theme = {}", quote(t));
+        }
+        if let Some(b) = &self.background {
+            let _ = writeln!(out, "background = {}", quote(&b.display().to_string()));
+        }
+        out.push_str("\n[gsettings]\n");
+        for (key, value) in &self.gsettings {
+            let _ = writeln!(out, "{} = {}", quote(key), quote(value));
+        }
+        out
+    }
+
+    fn from_toml(src: &str) -> Result<Self> {
+        let table: toml::Table = toml::from_str(src)?;
+        let string = |key: &str| table.get(key).and_then(|v| v.as_str()).map(str::to_owned);
+        Ok(Self {
+            generation: table.get("generation").and_then(|v| v.as_integer()).and_then(|g| u32::try_from(g).ok()),
+            current_theme: string("current_theme"),
+            background: string("background").map(PathBuf::from),
+            gsettings: table
+                .get("gsettings")
+                .and_then(|v| v.as_table())
+                .map(|t| t.iter().filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_owned()))).collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// A TOML basic string.
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04X}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn path(ctx: &Ctx, number: u32) -> PathBuf {
+    generation::sidecar(&ctx.generated_dir, number, "undo")
+}
+
+/// Keep `snapshot` as the state to return to when undoing generation `number`.
+pub fn save(ctx: &Ctx, number: u32, snapshot: &Snapshot) -> Result<()> {
+    let path = path(ctx, number);
+    fs::write(&path, snapshot.to_toml()).with_context(|| format!("write {}", path.display()))
+}
+
+/// The state from before the switch to generation `number`, if recorded.
+pub fn load(ctx: &Ctx, number: u32) -> Result<Option<Snapshot>> {
+    read(&path(ctx, number))
+}
+
+/// Record the GNOME settings as they are now, unless a baseline already
+/// exists. Call before anything writes them.
+pub fn keep_baseline(ctx: &Ctx) -> Result<()> {
+    if ctx.gsettings_baseline.exists() {
+        return Ok(());
+    }
+    let Snapshot { gsettings, .. } = Snapshot::capture(ctx, true);
+    if gsettings.is_empty() {
+        return Ok(());
+    }
+    let baseline = Snapshot { gsettings, ..Snapshot::default() };
+    let path = &ctx.gsettings_baseline;
+    fs::write(path, baseline.to_toml()).with_context(|| format!("write {}", path.display()))
+}
+
+/// The GNOME settings from before oxidize first changed them.
+pub fn load_baseline(ctx: &Ctx) -> Result<Option<Snapshot>> {
+    read(&ctx.gsettings_baseline)
+}
+
+fn read(path: &Path) -> Result<Option<Snapshot>> {
+    match fs::read_to_string(path) {
+        Ok(src) => Snapshot::from_toml(&src).with_context(|| format!("parse {}", path.display())).map(Some),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+/// Put `current.theme` back as it was (or remove it).
+pub fn restore_current_theme(ctx: &Ctx, snapshot: &Snapshot, fsync: bool) -> Result<()> {
+    let path = &ctx.current_theme_file;
+    match &snapshot.current_theme {
+        Some(record) => util::write_atomic(path, record, fsync),
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e).with_context(|| format!("remove {}", path.display())),
+            _ => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_round_trip() {
+        let snapshot = Snapshot {
+            generation: Some(7),
+            current_theme: Some("nord\ndark\n".into()),
+            background: Some("/walls/\"odd\" \\ name\u{1}.png".into()),
+            gsettings: vec![("color-scheme".into(), "prefer-dark".into()), ("gtk-theme".into(), "adw-gtk3-dark".into())],
+        };
+        assert_eq!(Snapshot::from_toml(&snapshot.to_toml()).unwrap(), snapshot);
+        assert_eq!(Snapshot::from_toml(&Snapshot::default().to_toml()).unwrap(), Snapshot::default());
+    }
+}
@@ -1,7 +1,11 @@
 //! Filesystem utility functions used across the crate.
 
 use anyhow::{Context, Result};
-use std::{fs, path::Path};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
 
 /// Create (or replace) a Unix symlink atomically.
 ///
@@ -23,6 +27,61 @@ pub fn symlink_force(target: &Path, link: &Path) -> Result<()> {
     fs::rename(&tmp, link).with_context(|| format!("atomic rename symlink into place {}", link.display()))
 }
 
+/// `path` with a leading `~` or `~/` replaced by `$HOME`. Left as is if
+/// `$HOME` isn't set, which [`crate::ctx::Ctx::new`] already refuses.
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some("") => "",
+        Some(rest) if rest.starts_with('/') => rest.trim_start_matches('/'),
+        _ => return PathBuf::from(path),
+    };
+    match std::env::var_os("HOME") {
+        Some(home) => Path::new(&home).join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Replace `path` with `contents` via a sibling temp file and rename(2), so
+/// readers see either the old or the new contents, never a partial write.
+/// With `fsync`, the data and the rename are flushed to disk.
+pub fn write_atomic(path: &Path, contents: &str, fsync: bool) -> Result<()> {
+    use std::io::Write as _;
+
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp).with_context(|| format!("create {}", tmp.display()))?;
+    file.write_all(contents.as_bytes()).with_context(|| format!("write {}", tmp.display()))?;
+    if fsync {
+        file.sync_all().with_context(|| format!("fsync {}", tmp.display()))?;
+    }
+    fs::rename(&tmp, path).with_context(|| format!("rename into place {}", path.display()))?;
+    if fsync && let Some(parent) = path.parent() {
+        fsync_dir(parent)?;
+    }
+    Ok(())
+}
+
+/// Flush `dir`'s entries (new names, renames) to disk.
+pub fn fsync_dir(dir: &Path) -> Result<()> {
+    File::open(dir)
+        .and_then(|d| d.sync_all())
+        .with_context(|| format!("fsync {}", dir.display()))
+}
+
+/// Flush every regular file and directory under `root` to disk, so a crash
+/// after a later rename can't expose truncated files.
+pub fn fsync_tree(root: &Path) -> Result<()> {
+    for entry in WalkDir::new(root) {
+        let entry = entry.with_context(|| format!("walk {}", root.display()))?;
+        let kind = entry.file_type();
+        if kind.is_file() || kind.is_dir() {
+            File::open(entry.path())
+                .and_then(|f| f.sync_all())
+                .with_context(|| format!("fsync {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(not(unix))]
 pub fn symlink_force(_target: &Path, _link: &Path) -> Result<()> {
     anyhow::bail!("symlinks are not supported on this platform")
@@ -0,0 +1,89 @@
+//! `oxidize validate` — check themes before they reach the desktop.
+
+use crate::{contrast, ctx::Ctx, cvd, render::engine, schema::Schema, term, theme::Theme};
+use anyhow::{Context, Result};
+use std::fs;
+
+/// Which checks to run on top of loading each theme.
+#[derive(Debug, Clone, Default)]
+pub struct Checks {
+    /// Expand every template against the theme and report unresolved keys.
+    pub against_templates: bool,
+    /// Color pairs whose WCAG contrast is reported (as warnings) when too low.
+    pub contrast: contrast::Rules,
+    /// Color pairs reported (as warnings) when a color vision deficiency
+    /// makes them indistinguishable.
+    pub cvd: cvd::Rules,
+    /// Keys and types from `schema.toml` every theme must satisfy.
+    pub schema: Schema,
+}
+
+/// Validate the named themes (all installed themes if `names` is empty).
+/// Prints a report and returns the number of themes with problems.
+pub fn run(ctx: &Ctx, names: &[String], checks: Checks, load: impl Fn(&str) -> Result<Theme>) -> Result<usize> {
+    let names = if names.is_empty() { all_themes(ctx)? } else { names.to_vec() };
+    let color = term::stdout_color();
+    let mut failed = 0;
+
+    for name in &names {
+        let (problems, warnings) = match load(name) {
+            Ok(theme) => {
+                let mut warnings = contrast::check(&checks.contrast, &theme.vars);
+                warnings.extend(cvd::check(&checks.cvd, &theme.vars));
+                (check(ctx, &theme, &checks)?, warnings)
+            }
+            Err(e) => (vec![format!("failed to load: {e:#}")], Vec::new()),
+        };
+
+        if !problems.is_empty() {
+            failed += 1;
+            println!("{} {name}", term::paint(color, "31", "FAIL"));
+        } else if !warnings.is_empty() {
+            println!("{} {name}", term::paint(color, "33", "warn"));
+        } else {
+            println!("{} {name}", term::paint(color, "32", "ok  "));
+        }
+        for p in problems.iter().chain(&warnings) {
+            println!("       {p}");
+        }
+    }
+
+    Ok(failed)
+}
+
+fn check(ctx: &Ctx, theme: &Theme, checks: &Checks) -> Result<Vec<String>> {
+    let mut problems = checks.schema.check(&theme.vars);
+
+    if checks.against_templates {
+        let theme_files = engine::ThemeFiles { dir: &theme.root, backgrounds: theme.backgrounds_dir.as_deref() };
+        let jobs = engine::plan(&ctx.templates_dirs, &ctx.user_templates_dir, theme_files)?;
+        for (out, missing) in engine::dry_run(&jobs, &theme.vars)? {
+            problems.push(format!("{}: unresolved {}", out.display(), missing.join(", ")));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Names of every directory in the theme search path that has a
+/// `colors.toml` or variant palettes.
+pub fn all_themes(ctx: &Ctx) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for dir in &ctx.theme_dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            // Only the user's own data dir has to exist.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && dir != &ctx.data_dir => continue,
+            Err(e) => return Err(e).with_context(|| format!("read {}", dir.display())),
+        };
+        names.extend(
+            entries
+                .flatten()
+                .filter(|e| e.path().join("colors.toml").is_file() || !Theme::variants(&e.path()).is_empty())
+                .filter_map(|e| e.file_name().into_string().ok()),
+        );
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
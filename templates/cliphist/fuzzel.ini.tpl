@@ -0,0 +1,18 @@
+# cliphist picker: cliphist list | fuzzel --dmenu --config ~/.config/oxidize/themes/current/cliphist/fuzzel.ini | cliphist decode | wl-copy
+[main]
+prompt="clip> "
+width=60
+lines=12
+
+[colors]
+background={{ palette_bg_strip }}f2
+text={{ palette_fg_strip }}ff
+match={{ palette_accent_strip }}ff
+selection={{ palette_accent_strip }}ff
+selection-text={{ palette_bg_strip }}ff
+selection-match={{ palette_bg_strip }}ff
+border={{ palette_accent_strip }}ff
+
+[border]
+width=2
+radius=8
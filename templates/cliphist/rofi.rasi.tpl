@@ -0,0 +1,57 @@
+/* cliphist picker: cliphist list | rofi -dmenu -theme ~/.config/oxidize/themes/current/cliphist/rofi.rasi | cliphist decode | wl-copy */
+* {
+    bg:     {{ palette_bg }};
+    fg:     {{ palette_fg }};
+    accent: {{ palette_accent }};
+    muted:  {{ palette_fg | mix(palette_bg) }};
+
+    background-color: transparent;
+    text-color:       @fg;
+}
+
+window {
+    width:            40%;
+    background-color: @bg;
+    border:           2px;
+    border-color:     @accent;
+    border-radius:    8px;
+}
+
+mainbox {
+    padding:  12px;
+    children: [ inputbar, listview ];
+}
+
+inputbar {
+    padding:  0 0 8px 0;
+    children: [ prompt, entry ];
+}
+
+prompt {
+    text-color: @accent;
+    padding:    0 8px 0 0;
+}
+
+entry {
+    placeholder:       "Search clipboard";
+    placeholder-color: @muted;
+}
+
+listview {
+    lines:   12;
+    fixed-height: false;
+}
+
+element {
+    padding:       4px 8px;
+    border-radius: 4px;
+}
+
+element selected {
+    background-color: @accent;
+    text-color:       @bg;
+}
+
+element-text {
+    text-color: inherit;
+}
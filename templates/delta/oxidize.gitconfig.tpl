@@ -0,0 +1,35 @@
+# delta colors. In ~/.gitconfig:
+#   [include]
+#       path = ~/.config/oxidize/themes/current/delta/oxidize.gitconfig
+#   [delta]
+#       features = oxidize
+[delta "oxidize"]
+{% if theme_is_light %}
+    light = true
+{% else %}
+    dark = true
+{% endif %}
+    syntax-theme = oxidize
+{% if palette_color2 %}
+    minus-style = syntax "{{ palette_bg | mix(palette_color1, 0.2) }}"
+    minus-emph-style = syntax "{{ palette_bg | mix(palette_color1, 0.4) }}"
+    plus-style = syntax "{{ palette_bg | mix(palette_color2, 0.2) }}"
+    plus-emph-style = syntax "{{ palette_bg | mix(palette_color2, 0.4) }}"
+    line-numbers-minus-style = "{{ palette_color1 }}"
+    line-numbers-plus-style = "{{ palette_color2 }}"
+{% else %}
+    minus-style = syntax "{{ palette_bg | mix(palette_fg, 0.1) }}"
+    minus-emph-style = syntax "{{ palette_bg | mix(palette_fg, 0.2) }}"
+    plus-style = syntax "{{ palette_bg | mix(palette_accent, 0.2) }}"
+    plus-emph-style = syntax "{{ palette_bg | mix(palette_accent, 0.4) }}"
+    line-numbers-minus-style = "{{ palette_fg }}"
+    line-numbers-plus-style = "{{ palette_accent }}"
+{% endif %}
+    line-numbers-zero-style = "{{ palette_fg | mix(palette_bg) }}"
+    line-numbers-left-style = "{{ palette_fg | mix(palette_bg) }}"
+    line-numbers-right-style = "{{ palette_fg | mix(palette_bg) }}"
+    file-style = "{{ palette_accent }}" bold
+    file-decoration-style = "{{ palette_accent }}" ul
+    hunk-header-style = file line-number syntax
+    hunk-header-decoration-style = "{{ palette_fg | mix(palette_bg) }}" box
+    commit-decoration-style = "{{ palette_accent }}" box
@@ -0,0 +1,35 @@
+;;; oxidize-theme.el --- Generated by oxidize  -*- lexical-binding: t -*-
+;; Loaded into running Emacs by `set` when oxidize.toml has
+;; `[emacs] generated = true`.
+
+(deftheme oxidize "The current oxidize theme.")
+
+(let ((bg "{{ palette_bg }}")
+      (fg "{{ palette_fg }}")
+      (accent "{{ palette_accent }}")
+      (surface "{{ palette_bg | mix(palette_fg, 0.08) }}")
+      (selection "{{ palette_bg | mix(palette_accent, 0.3) }}")
+      (muted "{{ palette_fg | mix(palette_bg) }}"))
+  (custom-theme-set-faces
+   'oxidize
+   `(default ((t (:background ,bg :foreground ,fg))))
+   `(cursor ((t (:background ,accent))))
+   `(region ((t (:background ,selection))))
+   `(hl-line ((t (:background ,surface))))
+   `(fringe ((t (:background ,bg))))
+   `(vertical-border ((t (:foreground ,muted))))
+   `(mode-line ((t (:background ,surface :foreground ,fg))))
+   `(mode-line-inactive ((t (:background ,bg :foreground ,muted))))
+   `(line-number ((t (:foreground ,muted))))
+   `(line-number-current-line ((t (:foreground ,accent :weight bold))))
+   `(minibuffer-prompt ((t (:foreground ,accent :weight bold))))
+   `(isearch ((t (:background ,accent :foreground ,bg))))
+   `(lazy-highlight ((t (:background ,selection))))
+   `(link ((t (:foreground ,accent :underline t))))
+   `(font-lock-comment-face ((t (:foreground ,muted :slant italic))))
+   `(font-lock-keyword-face ((t (:foreground ,accent))))
+   `(font-lock-function-name-face ((t (:foreground ,accent))))
+   `(font-lock-string-face ((t (:foreground ,fg))))
+   `(font-lock-constant-face ((t (:foreground ,accent))))))
+
+(provide-theme 'oxidize)
@@ -0,0 +1,45 @@
+/* Firefox chrome colors, copied into each profile's chrome/ dir by `set`
+ * when oxidize.toml has `[firefox] enable = true`. Takes effect on the next
+ * Firefox start. */
+
+:root {
+  --toolbar-bgcolor: {{ palette_bg }} !important;
+  --toolbar-color: {{ palette_fg }} !important;
+  --toolbar-field-background-color: {{ palette_bg | mix(palette_fg, 0.08) }} !important;
+  --toolbar-field-color: {{ palette_fg }} !important;
+  --toolbar-field-focus-background-color: {{ palette_bg | mix(palette_fg, 0.08) }} !important;
+  --toolbar-field-focus-color: {{ palette_fg }} !important;
+  --toolbar-field-focus-border-color: {{ palette_accent }} !important;
+  --lwt-accent-color: {{ palette_bg | mix(palette_fg, 0.04) }} !important;
+  --lwt-text-color: {{ palette_fg }} !important;
+  --tab-selected-bgcolor: {{ palette_bg }} !important;
+  --tab-selected-textcolor: {{ palette_fg }} !important;
+  --tab-loading-fill: {{ palette_accent }} !important;
+  --arrowpanel-background: {{ palette_bg }} !important;
+  --arrowpanel-color: {{ palette_fg }} !important;
+  --arrowpanel-border-color: {{ palette_fg | mix(palette_bg) }} !important;
+  --sidebar-background-color: {{ palette_bg }} !important;
+  --sidebar-text-color: {{ palette_fg }} !important;
+  --focus-outline-color: {{ palette_accent }} !important;
+}
+
+#navigator-toolbox,
+#TabsToolbar {
+  background-color: {{ palette_bg | mix(palette_fg, 0.04) }} !important;
+  color: {{ palette_fg }} !important;
+}
+
+#nav-bar,
+#PersonalToolbar {
+  background-color: {{ palette_bg }} !important;
+  color: {{ palette_fg }} !important;
+}
+
+.tabbrowser-tab[selected] .tab-background {
+  background-color: {{ palette_bg }} !important;
+  box-shadow: inset 0 -2px 0 {{ palette_accent }} !important;
+}
+
+#urlbar-background {
+  border-color: {{ palette_fg | mix(palette_bg) }} !important;
+}
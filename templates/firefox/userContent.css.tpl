@@ -0,0 +1,18 @@
+/* Colors for Firefox's own pages (about:*), copied into each profile's
+ * chrome/ dir along with userChrome.css. */
+
+@-moz-document url-prefix("about:") {
+  :root {
+    --in-content-page-background: {{ palette_bg }} !important;
+    --in-content-page-color: {{ palette_fg }} !important;
+    --in-content-text-color: {{ palette_fg }} !important;
+    --in-content-box-background: {{ palette_bg | mix(palette_fg, 0.06) }} !important;
+    --in-content-box-border-color: {{ palette_fg | mix(palette_bg) }} !important;
+    --in-content-primary-button-background: {{ palette_accent }} !important;
+    --in-content-primary-button-text-color: {{ palette_bg }} !important;
+    --in-content-accent-color: {{ palette_accent }} !important;
+    --in-content-link-color: {{ palette_accent }} !important;
+    --newtab-background-color: {{ palette_bg }} !important;
+    --newtab-text-primary-color: {{ palette_fg }} !important;
+  }
+}
@@ -0,0 +1,25 @@
+# fish colors. `set` runs this file with `fish -c source` after each switch.
+# The variables are universal, so every open shell picks them up at its next
+# prompt, and new shells start with them.
+set -U fish_color_normal '{{ palette_fg }}'
+set -U fish_color_command '{{ palette_accent }}'
+set -U fish_color_keyword '{{ palette_accent }}'
+set -U fish_color_param '{{ palette_fg }}'
+set -U fish_color_option '{{ palette_fg }}'
+set -U fish_color_quote '{{ palette_color2 | default(palette_fg) }}'
+set -U fish_color_redirection '{{ palette_color6 | default(palette_accent) }}'
+set -U fish_color_escape '{{ palette_color6 | default(palette_accent) }}'
+set -U fish_color_end '{{ palette_color5 | default(palette_accent) }}'
+set -U fish_color_operator '{{ palette_color5 | default(palette_accent) }}'
+set -U fish_color_error '{{ palette_color1 | default(palette_accent) }}'
+set -U fish_color_comment '{{ palette_fg | mix(palette_bg) }}'
+set -U fish_color_autosuggestion '{{ palette_fg | mix(palette_bg) }}'
+set -U fish_color_valid_path --underline
+set -U fish_color_selection --background='{{ palette_bg | mix(palette_accent, 0.3) }}'
+set -U fish_color_search_match --background='{{ palette_bg | mix(palette_accent, 0.3) }}'
+set -U fish_color_cancel '{{ palette_color1 | default(palette_accent) }}'
+set -U fish_pager_color_prefix '{{ palette_accent }}' --bold
+set -U fish_pager_color_completion '{{ palette_fg }}'
+set -U fish_pager_color_description '{{ palette_fg | mix(palette_bg) }}'
+set -U fish_pager_color_progress '{{ palette_fg | mix(palette_bg) }}'
+set -U fish_pager_color_selected_background --background='{{ palette_bg | mix(palette_fg, 0.1) }}'
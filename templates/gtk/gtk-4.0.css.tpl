@@ -0,0 +1,28 @@
+#! out = "~/.config/gtk-4.0/gtk.css"
+/* GTK 4 colors: libadwaita's named colors. Linked to
+ * ~/.config/gtk-4.0/gtk.css. */
+
+@define-color accent_color {{ palette_accent }};
+@define-color accent_bg_color {{ palette_accent }};
+@define-color accent_fg_color {{ palette_accent | on }};
+@define-color destructive_color {{ palette_color1 | default(palette_accent) }};
+@define-color destructive_bg_color {{ palette_color1 | default(palette_accent) }};
+@define-color destructive_fg_color {{ palette_color1 | default(palette_accent) | on }};
+
+@define-color window_bg_color {{ palette_bg }};
+@define-color window_fg_color {{ palette_fg }};
+@define-color view_bg_color {{ palette_bg | mix(palette_fg, 0.03) }};
+@define-color view_fg_color {{ palette_fg }};
+@define-color headerbar_bg_color {{ palette_bg | mix(palette_fg, 0.06) }};
+@define-color headerbar_fg_color {{ palette_fg }};
+@define-color headerbar_border_color {{ palette_fg | mix(palette_bg) }};
+@define-color headerbar_backdrop_color {{ palette_bg }};
+@define-color sidebar_bg_color {{ palette_bg | mix(palette_fg, 0.06) }};
+@define-color sidebar_fg_color {{ palette_fg }};
+@define-color sidebar_backdrop_color {{ palette_bg | mix(palette_fg, 0.03) }};
+@define-color card_bg_color {{ palette_bg | mix(palette_fg, 0.08) }};
+@define-color card_fg_color {{ palette_fg }};
+@define-color dialog_bg_color {{ palette_bg | mix(palette_fg, 0.06) }};
+@define-color dialog_fg_color {{ palette_fg }};
+@define-color popover_bg_color {{ palette_bg | mix(palette_fg, 0.06) }};
+@define-color popover_fg_color {{ palette_fg }};
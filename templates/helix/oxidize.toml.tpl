@@ -0,0 +1,46 @@
+#! out = "~/.config/helix/themes/oxidize.toml"
+# Helix theme. Set `theme = "oxidize"` in ~/.config/helix/config.toml.
+"ui.background" = { bg = "bg" }
+"ui.text" = "fg"
+"ui.text.focus" = { fg = "accent" }
+"ui.cursor" = { fg = "bg", bg = "fg" }
+"ui.cursor.primary" = { fg = "bg", bg = "accent" }
+"ui.cursor.match" = { fg = "accent", modifiers = ["bold"] }
+"ui.cursorline.primary" = { bg = "surface" }
+"ui.selection" = { bg = "selection" }
+"ui.linenr" = "muted"
+"ui.linenr.selected" = { fg = "accent", modifiers = ["bold"] }
+"ui.statusline" = { fg = "fg", bg = "surface" }
+"ui.statusline.inactive" = { fg = "muted", bg = "surface" }
+"ui.statusline.insert" = { fg = "bg", bg = "accent" }
+"ui.statusline.select" = { fg = "bg", bg = "accent" }
+"ui.popup" = { fg = "fg", bg = "surface" }
+"ui.window" = "muted"
+"ui.help" = { fg = "fg", bg = "surface" }
+"ui.menu" = { fg = "fg", bg = "surface" }
+"ui.menu.selected" = { fg = "bg", bg = "accent" }
+"ui.virtual.whitespace" = "muted"
+"ui.virtual.ruler" = { bg = "surface" }
+
+"comment" = { fg = "muted", modifiers = ["italic"] }
+"keyword" = "accent"
+"function" = "accent"
+"string" = "fg"
+"constant" = "accent"
+"type" = "fg"
+"variable" = "fg"
+"punctuation" = "muted"
+
+"diagnostic" = { underline = { style = "curl" } }
+"error" = "accent"
+"warning" = "accent"
+"info" = "fg"
+"hint" = "muted"
+
+[palette]
+bg = "{{ palette_bg }}"
+fg = "{{ palette_fg }}"
+accent = "{{ palette_accent }}"
+surface = "{{ palette_bg | mix(palette_fg, 0.08) }}"
+selection = "{{ palette_bg | mix(palette_accent, 0.3) }}"
+muted = "{{ palette_fg | mix(palette_bg) }}"
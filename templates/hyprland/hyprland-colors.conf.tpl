@@ -0,0 +1,25 @@
+# Hyprland colors: add `source = ~/.config/oxidize/themes/current/hyprland/hyprland-colors.conf`
+# to hyprland.conf, after any lines that set these colors.
+$oxidize_bg = rgb({{ palette_bg_strip }})
+$oxidize_fg = rgb({{ palette_fg_strip }})
+$oxidize_accent = rgb({{ palette_accent_strip }})
+
+general {
+    col.active_border = {{ hypr_gradient_active }}
+    col.inactive_border = {{ hypr_gradient_inactive }}
+}
+
+group {
+    col.border_active = {{ hypr_gradient_active }}
+    col.border_inactive = {{ hypr_gradient_inactive }}
+
+    groupbar {
+        col.active = $oxidize_accent
+        col.inactive = $oxidize_bg
+        text_color = $oxidize_fg
+    }
+}
+
+misc {
+    background_color = $oxidize_bg
+}
@@ -0,0 +1,47 @@
+#! out = "~/.local/share/color-schemes/Oxidize.colors"
+{% macro group(name, bg) %}
+[Colors:{{ name }}]
+BackgroundAlternate={{ bg | mix(palette_fg, 0.04) }}
+BackgroundNormal={{ bg }}
+DecorationFocus={{ palette_accent }}
+DecorationHover={{ palette_accent }}
+ForegroundActive={{ palette_accent }}
+ForegroundInactive={{ palette_fg | mix(palette_bg) }}
+ForegroundLink={{ palette_accent }}
+ForegroundNegative={{ palette_color1 | default(palette_accent) }}
+ForegroundNeutral={{ palette_color3 | default(palette_accent) }}
+ForegroundNormal={{ palette_fg }}
+ForegroundPositive={{ palette_color2 | default(palette_accent) }}
+ForegroundVisited={{ palette_color5 | default(palette_accent) }}
+{% endmacro %}
+[General]
+ColorScheme=Oxidize
+Name=Oxidize
+
+{{ group("Window", palette_bg) }}
+{{ group("View", palette_bg) }}
+{{ group("Button", palette_bg) }}
+{{ group("Header", palette_bg) }}
+{{ group("Tooltip", palette_bg) }}
+{{ group("Complementary", palette_bg) }}
+[Colors:Selection]
+BackgroundAlternate={{ palette_accent | mix(palette_bg, 0.2) }}
+BackgroundNormal={{ palette_accent }}
+DecorationFocus={{ palette_accent }}
+DecorationHover={{ palette_accent }}
+ForegroundActive={{ palette_accent | on }}
+ForegroundInactive={{ palette_accent | on }}
+ForegroundLink={{ palette_accent | on }}
+ForegroundNegative={{ palette_accent | on }}
+ForegroundNeutral={{ palette_accent | on }}
+ForegroundNormal={{ palette_accent | on }}
+ForegroundPositive={{ palette_accent | on }}
+ForegroundVisited={{ palette_accent | on }}
+
+[WM]
+activeBackground={{ palette_bg }}
+activeBlend={{ palette_bg }}
+activeForeground={{ palette_fg }}
+inactiveBackground={{ palette_bg }}
+inactiveBlend={{ palette_bg }}
+inactiveForeground={{ palette_fg | mix(palette_bg) }}
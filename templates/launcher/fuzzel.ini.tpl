@@ -0,0 +1,16 @@
+# fuzzel launcher colors: linked as ~/.config/fuzzel/oxidize.ini. Add
+# `include=~/.config/fuzzel/oxidize.ini` to fuzzel.ini (fuzzel 1.10+).
+[colors]
+background={{ palette_bg_strip }}f2
+text={{ palette_fg_strip }}ff
+prompt={{ palette_accent_strip }}ff
+input={{ palette_fg_strip }}ff
+match={{ palette_accent_strip }}ff
+selection={{ palette_accent_strip }}ff
+selection-text={{ palette_bg_strip }}ff
+selection-match={{ palette_bg_strip }}ff
+border={{ palette_accent_strip }}ff
+
+[border]
+width=2
+radius=8
@@ -0,0 +1,52 @@
+/* rofi launcher theme: linked as ~/.config/rofi/themes/oxidize.rasi, so
+   `@theme "oxidize"` in config.rasi or `rofi -theme oxidize` picks it up. */
+* {
+    bg:     {{ palette_bg }};
+    fg:     {{ palette_fg }};
+    accent: {{ palette_accent }};
+    muted:  {{ palette_fg | mix(palette_bg) }};
+
+    background-color: transparent;
+    text-color:       @fg;
+}
+
+window {
+    width:            40%;
+    background-color: @bg;
+    border:           2px;
+    border-color:     @accent;
+    border-radius:    8px;
+}
+
+mainbox {
+    padding:  12px;
+    children: [ inputbar, listview ];
+}
+
+inputbar {
+    padding:  0 0 8px 0;
+    children: [ prompt, entry ];
+}
+
+prompt {
+    text-color: @accent;
+    padding:    0 8px 0 0;
+}
+
+entry {
+    placeholder-color: @muted;
+}
+
+element {
+    padding:       4px 8px;
+    border-radius: 4px;
+}
+
+element selected {
+    background-color: @accent;
+    text-color:       @bg;
+}
+
+element-text, element-icon {
+    text-color: inherit;
+}
@@ -0,0 +1,36 @@
+/* wofi launcher style: linked as ~/.config/wofi/style.css unless that
+   file already exists; otherwise pass `--style` with this file. */
+window {
+    background-color: {{ palette_bg }};
+    border: 2px solid {{ palette_accent }};
+    border-radius: 8px;
+}
+
+#input {
+    margin: 8px;
+    color: {{ palette_fg }};
+    background-color: {{ palette_bg }};
+    border: 1px solid {{ palette_fg | mix(palette_bg) }};
+    border-radius: 4px;
+}
+
+#inner-box, #outer-box, #scroll {
+    background-color: transparent;
+}
+
+#text {
+    color: {{ palette_fg }};
+}
+
+#entry {
+    padding: 4px 8px;
+    border-radius: 4px;
+}
+
+#entry:selected {
+    background-color: {{ palette_accent }};
+}
+
+#entry:selected #text {
+    color: {{ palette_bg }};
+}
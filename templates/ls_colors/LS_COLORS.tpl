@@ -0,0 +1,6 @@
+{% macro c(color, style) %}{{ style }}38;2;{{ color_r }};{{ color_g }};{{ color_b }}{% endmacro %}
+{% if palette_color15 %}
+rs=0:di={{ c(palette_accent, "1;") }}:ln={{ c(palette_color6, "") }}:or={{ c(palette_color1, "") }}:mi={{ c(palette_color1, "") }}:pi={{ c(palette_color3, "") }}:so={{ c(palette_color5, "") }}:do={{ c(palette_color5, "") }}:bd={{ c(palette_color3, "1;") }}:cd={{ c(palette_color3, "1;") }}:su={{ c(palette_color1, "7;") }}:sg={{ c(palette_color3, "7;") }}:tw={{ c(palette_accent, "7;") }}:ow={{ c(palette_accent, "4;") }}:st={{ c(palette_accent, "7;") }}:ex={{ c(palette_color2, "1;") }}:*.tar={{ c(palette_color1, "") }}:*.tgz={{ c(palette_color1, "") }}:*.gz={{ c(palette_color1, "") }}:*.xz={{ c(palette_color1, "") }}:*.zst={{ c(palette_color1, "") }}:*.bz2={{ c(palette_color1, "") }}:*.zip={{ c(palette_color1, "") }}:*.7z={{ c(palette_color1, "") }}:*.rar={{ c(palette_color1, "") }}:*.deb={{ c(palette_color1, "") }}:*.rpm={{ c(palette_color1, "") }}:*.jpg={{ c(palette_color5, "") }}:*.jpeg={{ c(palette_color5, "") }}:*.png={{ c(palette_color5, "") }}:*.gif={{ c(palette_color5, "") }}:*.webp={{ c(palette_color5, "") }}:*.svg={{ c(palette_color5, "") }}:*.mp3={{ c(palette_color5, "") }}:*.flac={{ c(palette_color5, "") }}:*.ogg={{ c(palette_color5, "") }}:*.mp4={{ c(palette_color5, "") }}:*.mkv={{ c(palette_color5, "") }}:*.webm={{ c(palette_color5, "") }}:*.pdf={{ c(palette_color3, "") }}:*.md={{ c(palette_color3, "") }}:*~={{ c(palette_color8, "") }}:*.bak={{ c(palette_color8, "") }}:*.tmp={{ c(palette_color8, "") }}:*.log={{ c(palette_color8, "") }}
+{% else %}
+rs=0:di={{ c(palette_accent, "1;") }}:ln={{ c(palette_accent, "") }}:or={{ c(palette_fg, "9;") }}:mi={{ c(palette_fg, "9;") }}:pi={{ c(palette_accent, "") }}:so={{ c(palette_accent, "") }}:bd={{ c(palette_accent, "1;") }}:cd={{ c(palette_accent, "1;") }}:ex={{ c(palette_fg, "1;") }}
+{% endif %}
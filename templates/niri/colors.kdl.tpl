@@ -0,0 +1,15 @@
+// niri colors: add `include "../oxidize/themes/current/niri/colors.kdl"`
+// to ~/.config/niri/config.kdl, after your own layout section.
+layout {
+    focus-ring {
+        active-color "{{ palette_accent }}"
+        inactive-color "{{ palette_bg }}"
+    }
+
+    border {
+        active-color "{{ palette_accent }}"
+        inactive-color "{{ palette_bg }}"
+    }
+
+    background-color "{{ palette_bg }}"
+}
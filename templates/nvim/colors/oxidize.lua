@@ -0,0 +1,75 @@
+-- oxidize colorscheme. Reads the palette oxidize writes to
+-- lua/oxidize/palette.lua next to this dir. In init.lua:
+--   vim.opt.runtimepath:append(vim.fn.expand("~/.config/oxidize/themes/current/nvim"))
+--   vim.cmd.colorscheme("oxidize")
+package.loaded["oxidize.palette"] = nil
+local ok, p = pcall(require, "oxidize.palette")
+if not ok then
+  vim.notify("oxidize: no palette (" .. tostring(p) .. ")", vim.log.levels.WARN)
+  return
+end
+
+vim.cmd("highlight clear")
+vim.o.background = p.background
+vim.g.colors_name = "oxidize"
+
+local c = p.colors
+local bg, fg, accent = c.bg, c.fg, c.accent or c.fg
+local surface = c.color0 or bg
+local muted = c.color8 or fg
+local red = c.red or c.color1 or accent
+local green = c.green or c.color2 or accent
+local yellow = c.yellow or c.color3 or accent
+local blue = c.blue or c.color4 or accent
+local magenta = c.magenta or c.color5 or accent
+local cyan = c.cyan or c.color6 or accent
+local orange = c.orange or yellow
+
+local function hl(group, spec)
+  vim.api.nvim_set_hl(0, group, spec)
+end
+
+hl("Normal", { fg = fg, bg = bg })
+hl("NormalFloat", { fg = fg, bg = bg })
+hl("FloatBorder", { fg = accent, bg = bg })
+hl("CursorLine", { bg = surface })
+hl("ColorColumn", { bg = surface })
+hl("LineNr", { fg = muted })
+hl("CursorLineNr", { fg = accent, bold = true })
+hl("SignColumn", { bg = bg })
+hl("WinSeparator", { fg = muted })
+hl("StatusLine", { fg = fg, bg = surface })
+hl("StatusLineNC", { fg = muted, bg = surface })
+hl("Visual", { fg = bg, bg = accent })
+hl("Search", { fg = bg, bg = yellow })
+hl("IncSearch", { fg = bg, bg = orange })
+hl("Pmenu", { fg = fg, bg = surface })
+hl("PmenuSel", { fg = bg, bg = accent })
+hl("MatchParen", { fg = accent, bold = true })
+
+hl("Comment", { fg = muted, italic = true })
+hl("Constant", { fg = orange })
+hl("String", { fg = green })
+hl("Identifier", { fg = fg })
+hl("Function", { fg = blue })
+hl("Statement", { fg = magenta })
+hl("Keyword", { fg = magenta })
+hl("Type", { fg = yellow })
+hl("PreProc", { fg = cyan })
+hl("Special", { fg = cyan })
+hl("Error", { fg = red })
+hl("Todo", { fg = bg, bg = yellow, bold = true })
+
+hl("DiagnosticError", { fg = red })
+hl("DiagnosticWarn", { fg = yellow })
+hl("DiagnosticInfo", { fg = blue })
+hl("DiagnosticHint", { fg = cyan })
+hl("DiffAdd", { fg = green })
+hl("DiffChange", { fg = yellow })
+hl("DiffDelete", { fg = red })
+
+for i = 0, 15 do
+  if c["color" .. i] then
+    vim.g["terminal_color_" .. i] = c["color" .. i]
+  end
+end
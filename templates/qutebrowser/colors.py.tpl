@@ -0,0 +1,75 @@
+# qutebrowser colors: add to config.py
+#   config.source(os.path.expanduser("~/.config/oxidize/themes/current/qutebrowser/colors.py"))
+# `set` runs `:config-source` in the running qutebrowser after each switch.
+# pylint: disable=undefined-variable
+
+bg = "{{ palette_bg }}"
+fg = "{{ palette_fg }}"
+accent = "{{ palette_accent }}"
+surface = "{{ palette_bg | mix(palette_fg, 0.08) }}"
+muted = "{{ palette_fg | mix(palette_bg) }}"
+
+c.colors.completion.fg = fg
+c.colors.completion.odd.bg = surface
+c.colors.completion.even.bg = bg
+c.colors.completion.category.fg = accent
+c.colors.completion.category.bg = bg
+c.colors.completion.category.border.top = bg
+c.colors.completion.category.border.bottom = bg
+c.colors.completion.item.selected.fg = bg
+c.colors.completion.item.selected.bg = accent
+c.colors.completion.item.selected.border.top = accent
+c.colors.completion.item.selected.border.bottom = accent
+c.colors.completion.match.fg = accent
+c.colors.completion.item.selected.match.fg = bg
+c.colors.completion.scrollbar.fg = muted
+c.colors.completion.scrollbar.bg = bg
+
+c.colors.statusbar.normal.bg = bg
+c.colors.statusbar.normal.fg = fg
+c.colors.statusbar.insert.bg = accent
+c.colors.statusbar.insert.fg = bg
+c.colors.statusbar.passthrough.bg = muted
+c.colors.statusbar.passthrough.fg = bg
+c.colors.statusbar.command.bg = bg
+c.colors.statusbar.command.fg = fg
+c.colors.statusbar.caret.bg = accent
+c.colors.statusbar.caret.fg = bg
+c.colors.statusbar.progress.bg = accent
+c.colors.statusbar.url.fg = fg
+c.colors.statusbar.url.success.http.fg = fg
+c.colors.statusbar.url.success.https.fg = accent
+c.colors.statusbar.url.hover.fg = accent
+
+c.colors.tabs.bar.bg = surface
+c.colors.tabs.odd.bg = surface
+c.colors.tabs.even.bg = surface
+c.colors.tabs.odd.fg = muted
+c.colors.tabs.even.fg = muted
+c.colors.tabs.selected.odd.bg = bg
+c.colors.tabs.selected.even.bg = bg
+c.colors.tabs.selected.odd.fg = fg
+c.colors.tabs.selected.even.fg = fg
+c.colors.tabs.indicator.start = accent
+c.colors.tabs.indicator.stop = muted
+
+c.colors.hints.bg = accent
+c.colors.hints.fg = bg
+c.hints.border = "1px solid " + accent
+c.colors.keyhint.bg = surface
+c.colors.keyhint.fg = fg
+c.colors.keyhint.suffix.fg = accent
+c.colors.prompts.bg = surface
+c.colors.prompts.fg = fg
+c.colors.prompts.selected.bg = accent
+c.colors.prompts.selected.fg = bg
+c.colors.messages.info.bg = surface
+c.colors.messages.info.fg = fg
+c.colors.messages.info.border = surface
+c.colors.downloads.bar.bg = surface
+
+{% if theme_is_light %}
+c.colors.webpage.preferred_color_scheme = "light"
+{% else %}
+c.colors.webpage.preferred_color_scheme = "dark"
+{% endif %}
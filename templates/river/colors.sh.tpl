@@ -0,0 +1,6 @@
+#!/bin/sh
+# river colors: run `~/.config/oxidize/themes/current/river/colors.sh` from
+# river's init script.
+riverctl border-color-focused {{ palette_accent_0x }}
+riverctl border-color-unfocused {{ palette_bg_0x }}
+riverctl background-color {{ palette_bg_0x }}
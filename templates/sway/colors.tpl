@@ -0,0 +1,11 @@
+# sway colors: add `include ~/.config/oxidize/themes/current/sway/colors`
+# to the sway config, after any lines that set these colors.
+set $oxidize_bg {{ palette_bg }}
+set $oxidize_fg {{ palette_fg }}
+set $oxidize_accent {{ palette_accent }}
+
+# class                 border          background      text        indicator       child_border
+client.focused          $oxidize_accent $oxidize_accent $oxidize_bg $oxidize_accent $oxidize_accent
+client.focused_inactive $oxidize_bg     $oxidize_bg     $oxidize_fg $oxidize_bg     $oxidize_bg
+client.unfocused        $oxidize_bg     $oxidize_bg     $oxidize_fg $oxidize_bg     $oxidize_bg
+client.background       $oxidize_bg
@@ -0,0 +1,8 @@
+# tmux colors: add `source-file ~/.config/oxidize/themes/current/tmux/tmux-theme.conf`
+# to tmux.conf. `set` sources it again in the running server after each switch.
+set -g status-style "bg={{ palette_bg }},fg={{ palette_fg }}"
+set -g window-status-current-style "fg={{ palette_accent }},bold"
+set -g pane-border-style "fg={{ palette_fg | mix(palette_bg) }}"
+set -g pane-active-border-style "fg={{ palette_accent }}"
+set -g message-style "bg={{ palette_accent }},fg={{ palette_bg }}"
+set -g mode-style "bg={{ palette_accent }},fg={{ palette_bg }}"
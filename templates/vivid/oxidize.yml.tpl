@@ -0,0 +1,86 @@
+# vivid theme. Generate LS_COLORS from it with
+#   vivid generate ~/.config/oxidize/themes/current/vivid/oxidize.yml
+colors:
+  fg: "{{ palette_fg_strip }}"
+  accent: "{{ palette_accent_strip }}"
+  muted: "{{ palette_color8_strip | default(palette_fg_strip) }}"
+  red: "{{ palette_color1_strip | default(palette_accent_strip) }}"
+  green: "{{ palette_color2_strip | default(palette_fg_strip) }}"
+  yellow: "{{ palette_color3_strip | default(palette_accent_strip) }}"
+  magenta: "{{ palette_color5_strip | default(palette_accent_strip) }}"
+  cyan: "{{ palette_color6_strip | default(palette_accent_strip) }}"
+
+core:
+  normal_text: {}
+  regular_file: {}
+  reset_to_normal: {}
+  directory:
+    foreground: accent
+    font-style: bold
+  symlink:
+    foreground: cyan
+  multi_hard_link: {}
+  fifo:
+    foreground: yellow
+  socket:
+    foreground: magenta
+  door:
+    foreground: magenta
+  block_device:
+    foreground: yellow
+    font-style: bold
+  character_device:
+    foreground: yellow
+    font-style: bold
+  broken_symlink:
+    foreground: red
+  missing_symlink_target:
+    foreground: red
+  setuid:
+    foreground: red
+    font-style: reverse
+  setgid:
+    foreground: yellow
+    font-style: reverse
+  file_with_capability: {}
+  sticky_other_writable:
+    foreground: accent
+    font-style: reverse
+  other_writable:
+    foreground: accent
+    font-style: underline
+  sticky:
+    foreground: accent
+    font-style: reverse
+  executable_file:
+    foreground: green
+    font-style: bold
+
+text:
+  foreground: fg
+  todo:
+    foreground: yellow
+    font-style: bold
+
+markup:
+  foreground: yellow
+
+programming:
+  source:
+    foreground: fg
+
+media:
+  foreground: magenta
+
+office:
+  foreground: yellow
+
+archives:
+  foreground: red
+
+executable:
+  foreground: green
+  font-style: bold
+
+unimportant:
+  foreground: muted
@@ -0,0 +1,18 @@
+# WezTerm color scheme. In wezterm.lua:
+#   config.color_scheme_dirs = { wezterm.home_dir .. "/.config/oxidize/themes/current/wezterm" }
+#   config.color_scheme = "oxidize"
+[metadata]
+name = "oxidize"
+
+[colors]
+foreground = "{{ palette_fg }}"
+background = "{{ palette_bg }}"
+cursor_bg = "{{ palette_accent }}"
+cursor_fg = "{{ palette_bg }}"
+cursor_border = "{{ palette_accent }}"
+selection_fg = "{{ palette_bg }}"
+selection_bg = "{{ palette_accent }}"
+scrollbar_thumb = "{{ palette_fg | mix(palette_bg) }}"
+split = "{{ palette_accent }}"
+ansi = {{ palette_wezterm_ansi }}
+brights = {{ palette_wezterm_brights }}
@@ -0,0 +1,26 @@
+! X resources for XWayland apps (xterm, Xaw and Motif dialogs, some Java
+! apps). `set` merges this into the X server with `xrdb -merge` when
+! $DISPLAY is set.
+*background: {{ palette_bg }}
+*foreground: {{ palette_fg }}
+*cursorColor: {{ palette_accent }}
+*highlightColor: {{ palette_accent }}
+*borderColor: {{ palette_fg | mix(palette_bg) }}
+{% if palette_color15 %}
+*color0: {{ palette_color0 }}
+*color1: {{ palette_color1 }}
+*color2: {{ palette_color2 }}
+*color3: {{ palette_color3 }}
+*color4: {{ palette_color4 }}
+*color5: {{ palette_color5 }}
+*color6: {{ palette_color6 }}
+*color7: {{ palette_color7 }}
+*color8: {{ palette_color8 }}
+*color9: {{ palette_color9 }}
+*color10: {{ palette_color10 }}
+*color11: {{ palette_color11 }}
+*color12: {{ palette_color12 }}
+*color13: {{ palette_color13 }}
+*color14: {{ palette_color14 }}
+*color15: {{ palette_color15 }}
+{% endif %}